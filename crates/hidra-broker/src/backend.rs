@@ -2,12 +2,62 @@
 pub mod driver;
 pub mod mock;
 
-use anyhow::Result;
-use hidra_protocol::{DeviceKind, PadState};
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use hidra_protocol::{
+    DeviceConfig, DeviceKind, KeyboardState, MouseState, PadState, VersionInfo, output::OutputEvent,
+};
+use tokio::sync::broadcast;
 
 #[async_trait::async_trait]
 pub trait Backend: Send + Sync + 'static {
-    async fn create(&self, kind: DeviceKind, features: u32) -> Result<u64>;
+    /// What the device side supports, reported to clients on `Hello`.
+    fn info(&self) -> VersionInfo;
+    async fn create(&self, kind: DeviceKind, config: DeviceConfig) -> Result<u64>;
     async fn destroy(&self, handle: u64) -> Result<()>;
     async fn update(&self, handle: u64, state: PadState) -> Result<()>;
+    async fn update_keyboard(&self, handle: u64, state: KeyboardState) -> Result<()>;
+    async fn update_mouse(&self, handle: u64, state: MouseState) -> Result<()>;
+    /// Decoded output reports of `handle`; the channel closes when it is destroyed.
+    fn subscribe(&self, handle: u64) -> Result<broadcast::Receiver<OutputEvent>>;
+    /// Handle `report` as an output report from the host.
+    async fn inject_output(&self, handle: u64, report: Vec<u8>) -> Result<()>;
+    /// Feature report `id` of `handle`, as the host reads it.
+    async fn get_feature(&self, handle: u64, id: u8) -> Result<Vec<u8>>;
+    /// Handle `report` as a feature report the host sets.
+    async fn set_feature(&self, handle: u64, report: Vec<u8>) -> Result<()>;
+}
+
+/// Output events the subscribers of a device haven't read yet.
+const OUTPUT_BACKLOG: usize = 64;
+
+/// One output channel per live device.
+#[derive(Default)]
+pub struct Outputs {
+    map: DashMap<u64, broadcast::Sender<OutputEvent>>,
+}
+
+impl Outputs {
+    pub fn open(&self, handle: u64) {
+        self.map.insert(handle, broadcast::channel(OUTPUT_BACKLOG).0);
+    }
+
+    /// Ends every subscription to `handle`.
+    pub fn close(&self, handle: u64) {
+        self.map.remove(&handle);
+    }
+
+    pub fn subscribe(&self, handle: u64) -> Result<broadcast::Receiver<OutputEvent>> {
+        let tx = self.map.get(&handle).with_context(|| format!("invalid handle: {handle}"))?;
+        Ok(tx.subscribe())
+    }
+
+    /// Fan `events` out to the subscribers of `handle`, if any.
+    pub fn send(&self, handle: u64, events: impl IntoIterator<Item = OutputEvent>) {
+        if let Some(tx) = self.map.get(&handle) {
+            for event in events {
+                let _ = tx.send(event);
+            }
+        }
+    }
 }
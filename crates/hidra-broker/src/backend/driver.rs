@@ -1,9 +1,12 @@
 use super::Backend;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dashmap::DashMap;
 use hidra_protocol::{
-    CreateIn, CreateOut, DestroyIn, DeviceKind, HIDRA_INTERFACE_GUID, IOCTL_HIDRA_CREATE,
-    IOCTL_HIDRA_DESTROY, IOCTL_HIDRA_UPDATE, PadState, UpdateIn,
+    CreateGenericIn, CreateIn, CreateOut, DestroyIn, DeviceConfig, DeviceIdentity, DeviceKind,
+    HIDRA_INTERFACE_GUID, IOCTL_HIDRA_CREATE, IOCTL_HIDRA_DESTROY, IOCTL_HIDRA_GET_VERSION,
+    IOCTL_HIDRA_UPDATE, IOCTL_HIDRA_UPDATE_KEYBOARD, IOCTL_HIDRA_UPDATE_MOUSE, KeyboardState,
+    MouseState, PadState, UpdateIn, UpdateKeyboardIn, UpdateMouseIn, VersionInfo, Wire,
+    output::OutputEvent,
 };
 use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
 use std::{
@@ -13,7 +16,8 @@ use std::{
         atomic::{AtomicU64, Ordering},
     },
 };
-use tracing::debug;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
 use windows::Win32::Devices::DeviceAndDriverInstallation::{
     DIGCF_DEVICEINTERFACE, DIGCF_PRESENT, SP_DEVICE_INTERFACE_DATA,
     SP_DEVICE_INTERFACE_DETAIL_DATA_W, SetupDiEnumDeviceInterfaces, SetupDiGetClassDevsW,
@@ -31,63 +35,128 @@ pub struct Driver {
     next: AtomicU64,
     live: DashMap<u64, DeviceKind>,
     hdev: OwnedHandle,
+    info: VersionInfo,
 }
 
 impl Driver {
-    pub fn new() -> Arc<Self> {
-        let h = open_by_interface_guid(&HIDRA_INTERFACE_GUID).expect("unable to open handle");
-        Arc::new(Self { next: AtomicU64::new(1), live: DashMap::new(), hdev: h })
+    /// Opens HIDraBus and refuses to run against a driver built for another ABI version.
+    pub fn new() -> Result<Arc<Self>> {
+        let h = open_by_interface_guid(&HIDRA_INTERFACE_GUID).context("unable to open HIDraBus")?;
+        let mut out = [0u8; VersionInfo::LEN];
+        let n = ioctl(as_handle(&h), IOCTL_HIDRA_GET_VERSION, &[], &mut out)
+            .context("HIDraBus does not answer IOCTL_HIDRA_GET_VERSION; driver too old?")?;
+        let info = VersionInfo::decode(&out[..n])?;
+        info.check().context("HIDraBus driver")?;
+        info!(abi = info.abi_version, kinds = info.kinds, features = info.features, "driver");
+        Ok(Arc::new(Self { next: AtomicU64::new(1), live: DashMap::new(), hdev: h, info }))
     }
 }
 
 #[async_trait::async_trait]
 impl Backend for Driver {
-    async fn create(&self, kind: DeviceKind, features: u32) -> Result<u64> {
+    fn info(&self) -> VersionInfo {
+        self.info
+    }
+
+    async fn create(&self, kind: DeviceKind, config: DeviceConfig) -> Result<u64> {
         let h = self.next.fetch_add(1, Ordering::SeqCst);
-        let cin = CreateIn { kind, features };
-        let mut cout = CreateOut { handle: 0 };
-        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_CREATE, Some(&cin), Some(&mut cout))?;
+        // generic pads append their spec; the driver tells the two apart by length.
+        // The identity trails either.
+        let mut cin = [0u8; CreateGenericIn::LEN + DeviceIdentity::LEN];
+        let mut len = match kind {
+            DeviceKind::Generic => CreateGenericIn::new(&config).encode(&mut cin)?,
+            _ => CreateIn::new(kind, &config).encode(&mut cin)?,
+        };
+        len += config.identity.encode(&mut cin[len..])?;
+        let mut out = [0u8; CreateOut::LEN];
+        let n = ioctl(as_handle(&self.hdev), IOCTL_HIDRA_CREATE, &cin[..len], &mut out)?;
+        let cout = CreateOut::decode(&out[..n])?;
         self.live.insert(h, kind);
-        debug!(host_handle = h, drv_handle = cout.handle, ?kind, features, "created");
+        debug!(host_handle = h, drv_handle = cout.handle, ?kind, ?config, "created");
         // You can choose to return cout.handle instead of h if you want the driver to own ids.
         Ok(h)
     }
 
     async fn destroy(&self, handle: u64) -> Result<()> {
-        let din = DestroyIn { handle };
-        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_DESTROY, Some(&din), Option::<&mut ()>::None)?;
+        let mut din = [0u8; DestroyIn::LEN];
+        DestroyIn { handle }.encode(&mut din)?;
+        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_DESTROY, &din, &mut [])?;
         self.live.remove(&handle);
         Ok(())
     }
 
     async fn update(&self, h: u64, s: PadState) -> Result<()> {
-        // Optionally pack per-kind here (X360/DS4/DS5) into UpdateIn payload
-        let uin = UpdateIn { handle: h, state: s };
-        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_UPDATE, Some(&uin), Option::<&mut ()>::None)?;
+        self.check_kind(h, |k| k.is_pad(), "a pad")?;
+        // Optionally pack per-kind here (X360/DS4/DS5/DS5 Edge/Xbox One/Elite/Switch Pro/generic/wheel) into UpdateIn payload
+        let mut uin = [0u8; UpdateIn::LEN];
+        UpdateIn { handle: h, state: s }.encode(&mut uin)?;
+        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_UPDATE, &uin, &mut [])?;
+        Ok(())
+    }
+
+    async fn update_keyboard(&self, h: u64, s: KeyboardState) -> Result<()> {
+        self.check_kind(h, |k| k == DeviceKind::Keyboard, "a keyboard")?;
+        let mut uin = [0u8; UpdateKeyboardIn::LEN];
+        UpdateKeyboardIn { handle: h, state: s }.encode(&mut uin)?;
+        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_UPDATE_KEYBOARD, &uin, &mut [])?;
+        Ok(())
+    }
+
+    async fn update_mouse(&self, h: u64, s: MouseState) -> Result<()> {
+        self.check_kind(h, |k| k == DeviceKind::Mouse, "a mouse")?;
+        let mut uin = [0u8; UpdateMouseIn::LEN];
+        UpdateMouseIn { handle: h, state: s }.encode(&mut uin)?;
+        ioctl(as_handle(&self.hdev), IOCTL_HIDRA_UPDATE_MOUSE, &uin, &mut [])?;
+        Ok(())
+    }
+
+    fn subscribe(&self, _h: u64) -> Result<broadcast::Receiver<OutputEvent>> {
+        anyhow::bail!(
+            "HIDraBus doesn't hand output reports back yet; subscribe needs the mock backend"
+        )
+    }
+
+    async fn inject_output(&self, _h: u64, _report: Vec<u8>) -> Result<()> {
+        anyhow::bail!(
+            "output reports come from the host; only the mock backend takes injected ones"
+        )
+    }
+
+    async fn get_feature(&self, _h: u64, _id: u8) -> Result<Vec<u8>> {
+        anyhow::bail!("HIDraBus doesn't serve feature reports yet")
+    }
+
+    async fn set_feature(&self, _h: u64, _report: Vec<u8>) -> Result<()> {
+        anyhow::bail!("feature reports come from the host; only the mock backend takes set ones")
+    }
+}
+
+impl Driver {
+    /// Refuse an update whose state doesn't fit the device behind `h`.
+    fn check_kind(&self, h: u64, ok: impl Fn(DeviceKind) -> bool, what: &str) -> Result<()> {
+        let kind = *self.live.get(&h).with_context(|| format!("invalid handle: {h}"))?;
+        anyhow::ensure!(ok(kind), "handle {h} is not {what}");
         Ok(())
     }
 }
 
-fn ioctl<TIn: Sized, TOut: Sized>(
-    h: HANDLE,
-    code: u32,
-    inp: Option<&TIn>,
-    out: Option<&mut TOut>,
-) -> Result<()> {
+/// Send `inp` as-is; returns how many bytes of `out` the driver filled.
+/// Buffers come from `Wire::encode`, so their lengths are the struct sizes.
+fn ioctl(h: HANDLE, code: u32, inp: &[u8], out: &mut [u8]) -> Result<usize> {
     let mut bytes: u32 = 0;
     unsafe {
         DeviceIoControl(
             h,
             code,
-            inp.map(|p| (p as *const TIn).cast()),
-            std::mem::size_of::<TIn>() as u32,
-            out.map(|p| (p as *mut TOut).cast()),
-            std::mem::size_of::<TOut>() as u32,
+            (!inp.is_empty()).then(|| inp.as_ptr().cast()),
+            inp.len() as u32,
+            (!out.is_empty()).then(|| out.as_mut_ptr().cast()),
+            out.len() as u32,
             Some(&mut bytes),
             None,
         )
     }?;
-    Ok(())
+    Ok(bytes as usize)
 }
 
 fn open_by_interface_guid(iface: &GUID) -> windows::core::Result<OwnedHandle> {
@@ -1,66 +1,290 @@
-use super::Backend;
+use super::{Backend, Outputs};
 use anyhow::Result;
 use dashmap::DashMap;
 use hidra_protocol::{
-    DeviceKind, PadState,
-    report::{DS4Report, DS5Report, X360Report},
+    DeviceConfig, DeviceKind, Features, KeyboardState, MouseState, PadState, Transport,
+    VersionInfo,
+    feature::{self, FeatureConfig},
+    output,
+    pid::{self, EffectBlocks},
+    report::{
+        DS4BtReport, DS4Report, DS5BtReport, DS5Report, GenericReport, KeyboardNkroReport,
+        KeyboardReport, MouseReport, ReportClock, SwitchProReport, WheelReport, X360Report,
+        XboxOneReport,
+    },
 };
 use std::sync::{
     Arc,
     atomic::{AtomicU64, Ordering},
 };
+use std::time::Instant;
+use tokio::sync::broadcast;
 use tracing::debug;
 
 pub struct Mock {
     next: AtomicU64,
     live: DashMap<u64, Info>,
+    outputs: Outputs,
 }
 
 #[derive(Clone, Copy)]
 struct Info {
     kind: DeviceKind,
-    #[allow(dead_code)] //TODO: remove once features are implemented
-    features: u32,
+    config: DeviceConfig,
+    clock: ReportClock,
+    last: Instant,
+    /// Force feedback effect blocks the host has allocated on a wheel.
+    blocks: EffectBlocks,
 }
 
 impl Mock {
     pub fn new() -> Arc<Self> {
-        Arc::new(Self { next: AtomicU64::new(1), live: DashMap::new() })
+        Arc::new(Self {
+            next: AtomicU64::new(1),
+            live: DashMap::new(),
+            outputs: Outputs::default(),
+        })
     }
 }
 
 #[async_trait::async_trait]
 impl Backend for Mock {
-    async fn create(&self, kind: DeviceKind, features: u32) -> Result<u64> {
+    fn info(&self) -> VersionInfo {
+        VersionInfo::new(&DeviceKind::ALL, Features::all())
+    }
+
+    async fn create(&self, kind: DeviceKind, config: DeviceConfig) -> Result<u64> {
         let h = self.next.fetch_add(1, Ordering::SeqCst);
-        self.live.insert(h, Info { kind, features });
+        let id = &config.identity;
+        debug!(
+            handle = h,
+            vid = id.vendor_id,
+            pid = id.product_id,
+            serial = %String::from_utf16_lossy(id.serial_units()),
+            "mock create"
+        );
+        let info = Info {
+            kind,
+            config,
+            clock: ReportClock::default(),
+            last: Instant::now(),
+            blocks: EffectBlocks::default(),
+        };
+        self.live.insert(h, info);
+        self.outputs.open(h);
         Ok(h)
     }
 
     async fn destroy(&self, handle: u64) -> Result<()> {
         self.live.remove(&handle);
+        self.outputs.close(handle);
         Ok(())
     }
 
     async fn update(&self, h: u64, s: PadState) -> Result<()> {
-        if let Some(info) = self.live.get(&h) {
+        if let Some(mut info) = self.live.get_mut(&h) {
+            let now = Instant::now();
+            let elapsed = now - info.last;
+            info.clock.tick(elapsed);
+            info.last = now;
+            let bt = info.config.transport == Transport::Bluetooth;
             match info.kind {
                 DeviceKind::X360 => {
-                    let rpt = X360Report::from(&s);
+                    let rpt = X360Report::pack(&s, &info.config);
                     debug!(handle = h, ?s, report = ?rpt, "mock update X360");
                 }
+                DeviceKind::DS4 if bt => {
+                    let rpt = DS4BtReport::pack(&s, &info.clock, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update DS4 (BT)");
+                }
                 DeviceKind::DS4 => {
-                    let rpt = DS4Report::from(&s);
+                    let rpt = DS4Report::pack(&s, &info.clock, &info.config);
                     debug!(handle = h, ?s, report = ?rpt, "mock update DS4");
                 }
+                DeviceKind::DS5 if bt => {
+                    let rpt = DS5BtReport::pack(&s, &info.clock, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update DS5 (BT)");
+                }
                 DeviceKind::DS5 => {
-                    let rpt = DS5Report::from(&s);
+                    let rpt = DS5Report::pack(&s, &info.clock, &info.config);
                     debug!(handle = h, ?s, report = ?rpt, "mock update DS5");
                 }
+                DeviceKind::DS5Edge if bt => {
+                    let rpt = DS5BtReport::pack_edge(&s, &info.clock, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update DS5 Edge (BT)");
+                }
+                DeviceKind::DS5Edge => {
+                    let rpt = DS5Report::pack_edge(&s, &info.clock, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update DS5 Edge");
+                }
+                DeviceKind::XboxOne => {
+                    let rpt = XboxOneReport::pack(&s, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update Xbox One");
+                }
+                DeviceKind::XboxElite => {
+                    let rpt = XboxOneReport::pack_elite(&s, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update Xbox Elite");
+                }
+                DeviceKind::SwitchPro => {
+                    let rpt = SwitchProReport::pack(&s, &info.clock, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update Switch Pro");
+                }
+                DeviceKind::Wheel => {
+                    let rpt = WheelReport::pack(&s, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt, "mock update wheel");
+                }
+                DeviceKind::Generic => {
+                    let rpt = GenericReport::pack(&s, &info.config);
+                    debug!(handle = h, ?s, report = ?rpt.as_bytes(), "mock update generic");
+                }
+                DeviceKind::Keyboard | DeviceKind::Mouse => {
+                    anyhow::bail!("handle {h} is a {}, not a pad", info.kind.name())
+                }
             }
             Ok(())
         } else {
             Err(anyhow::anyhow!("invalid handle: {}", h))
         }
     }
+
+    async fn update_keyboard(&self, h: u64, s: KeyboardState) -> Result<()> {
+        let info = self.live.get(&h).ok_or_else(|| anyhow::anyhow!("invalid handle: {}", h))?;
+        anyhow::ensure!(info.kind == DeviceKind::Keyboard, "handle {h} is not a keyboard");
+        if info.config.features.contains(Features::NKRO) {
+            let rpt = KeyboardNkroReport::pack(&s);
+            debug!(handle = h, ?s, report = ?rpt, "mock update keyboard (NKRO)");
+        } else {
+            let rpt = KeyboardReport::pack(&s);
+            debug!(handle = h, ?s, report = ?rpt, "mock update keyboard");
+        }
+        Ok(())
+    }
+
+    async fn update_mouse(&self, h: u64, s: MouseState) -> Result<()> {
+        let info = self.live.get(&h).ok_or_else(|| anyhow::anyhow!("invalid handle: {}", h))?;
+        anyhow::ensure!(info.kind == DeviceKind::Mouse, "handle {h} is not a mouse");
+        let mut rest = s;
+        loop {
+            let rpt = MouseReport::take(&mut rest);
+            debug!(handle = h, ?s, report = ?rpt, "mock update mouse");
+            if rest.is_still() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, h: u64) -> Result<broadcast::Receiver<output::OutputEvent>> {
+        self.outputs.subscribe(h)
+    }
+
+    /// Decoded the way the device would, so subscribers see what a game's
+    /// report for this kind carries.
+    async fn inject_output(&self, h: u64, report: Vec<u8>) -> Result<()> {
+        let mut info =
+            self.live.get_mut(&h).ok_or_else(|| anyhow::anyhow!("invalid handle: {}", h))?;
+        let events = output::decode(info.kind, &report)?;
+        debug!(handle = h, ?report, ?events, "mock output report");
+        for event in &events {
+            if let output::OutputEvent::ForceFeedback { command } = event {
+                info.blocks.apply(command);
+            }
+        }
+        drop(info);
+        self.outputs.send(h, events);
+        Ok(())
+    }
+
+    /// DualShock/DualSense calibration, pairing and firmware reports carry the
+    /// device's identity; a wheel answers Block Load and Pool.
+    async fn get_feature(&self, h: u64, id: u8) -> Result<Vec<u8>> {
+        let info = self.live.get(&h).ok_or_else(|| anyhow::anyhow!("invalid handle: {}", h))?;
+        let report = match info.kind {
+            DeviceKind::Wheel => info.blocks.get_feature(id).map(|r| r.to_vec()),
+            kind => {
+                let cfg = FeatureConfig::for_identity(kind, &info.config.identity);
+                feature::get_feature(kind, id, &cfg)
+            }
+        };
+        report.ok_or_else(|| {
+            anyhow::anyhow!("a {} has no feature report {id:#04x}", info.kind.name())
+        })
+    }
+
+    /// A wheel takes Create New Effect and allocates a block for the next
+    /// Block Load read.
+    async fn set_feature(&self, h: u64, report: Vec<u8>) -> Result<()> {
+        let mut info =
+            self.live.get_mut(&h).ok_or_else(|| anyhow::anyhow!("invalid handle: {}", h))?;
+        match (info.kind, report.first()) {
+            (DeviceKind::Wheel, Some(&pid::CREATE_NEW_EFFECT_ID)) => {
+                let index = info.blocks.create(&report)?;
+                debug!(handle = h, ?report, ?index, "mock create new effect");
+                Ok(())
+            }
+            (kind, id) => anyhow::bail!("a {} takes no feature report {id:02x?}", kind.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hidra_protocol::output::OutputEvent;
+
+    #[tokio::test]
+    async fn injected_reports_reach_subscribers() {
+        let mock = Mock::new();
+        let h =
+            mock.create(DeviceKind::X360, DeviceConfig::for_kind(DeviceKind::X360)).await.unwrap();
+        let mut rx = mock.subscribe(h).unwrap();
+        mock.inject_output(h, vec![0x00, 0x08, 0x00, 0xC0, 0x40, 0, 0, 0]).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), OutputEvent::Rumble { strong: 0xC0, weak: 0x40 });
+        assert!(mock.inject_output(h, vec![0x07, 0x00]).await.is_err());
+        mock.destroy(h).await.unwrap();
+        assert!(rx.recv().await.is_err());
+        assert!(mock.subscribe(h).is_err());
+    }
+
+    #[tokio::test]
+    async fn pads_serve_feature_reports() {
+        let mock = Mock::new();
+        let mut ds4 = DeviceConfig { slot: 2, ..DeviceConfig::for_kind(DeviceKind::DS4) };
+        ds4.resolve_identity(DeviceKind::DS4);
+        let mac = ds4.identity.mac;
+        let h = mock.create(DeviceKind::DS4, ds4).await.unwrap();
+        let pairing = mock.get_feature(h, feature::DS4_PAIRING_ID).await.unwrap();
+        assert_eq!(pairing.len(), feature::DS4_PAIRING_LEN);
+        assert!(pairing[1..7].iter().eq(mac.iter().rev()));
+        let calibration = mock.get_feature(h, feature::DS4_CALIBRATION_ID).await.unwrap();
+        assert_eq!(calibration[0], feature::DS4_CALIBRATION_ID);
+        assert!(mock.get_feature(h, feature::DS5_FIRMWARE_ID).await.is_err());
+
+        let h =
+            mock.create(DeviceKind::DS5, DeviceConfig::for_kind(DeviceKind::DS5)).await.unwrap();
+        let firmware = mock.get_feature(h, feature::DS5_FIRMWARE_ID).await.unwrap();
+        assert_eq!(firmware.len(), feature::DS5_FIRMWARE_LEN);
+    }
+
+    #[tokio::test]
+    async fn wheel_allocates_effect_blocks() {
+        let mock = Mock::new();
+        let wheel = DeviceConfig::for_kind(DeviceKind::Wheel);
+        let h = mock.create(DeviceKind::Wheel, wheel).await.unwrap();
+        let create = vec![pid::CREATE_NEW_EFFECT_ID, pid::EffectType::Spring as u8, 0, 0];
+        mock.set_feature(h, create.clone()).await.unwrap();
+        mock.set_feature(h, create.clone()).await.unwrap();
+        let load = mock.get_feature(h, pid::BLOCK_LOAD_ID).await.unwrap();
+        assert_eq!(load[..3], [pid::BLOCK_LOAD_ID, 2, pid::BlockLoadStatus::Success as u8]);
+        assert_eq!(mock.get_feature(h, pid::POOL_ID).await.unwrap(), pid::pool());
+
+        mock.inject_output(h, vec![pid::BLOCK_FREE_ID, 1]).await.unwrap();
+        mock.set_feature(h, create).await.unwrap();
+        assert_eq!(mock.get_feature(h, pid::BLOCK_LOAD_ID).await.unwrap()[1], 1);
+
+        let x360 = DeviceConfig::for_kind(DeviceKind::X360);
+        let h = mock.create(DeviceKind::X360, x360).await.unwrap();
+        assert!(mock.get_feature(h, pid::POOL_ID).await.is_err());
+        assert!(mock.set_feature(h, vec![pid::CREATE_NEW_EFFECT_ID]).await.is_err());
+    }
 }
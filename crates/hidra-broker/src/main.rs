@@ -8,9 +8,9 @@ use hidra_ipc::{BrokerRequest, BrokerResponse, PIPE_PATH, read_json_opt, write_j
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch};
 use tokio::time;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 #[cfg(feature = "backend-driver")]
@@ -20,7 +20,13 @@ use crate::backend::{Backend, mock::Mock};
 
 #[derive(Default)]
 struct Pumps {
-    map: DashMap<u64, watch::Sender<hidra_protocol::PadState>>,
+    map: DashMap<u64, Pump>,
+}
+
+struct Pump {
+    tx: watch::Sender<hidra_protocol::PadState>,
+    ranges: hidra_protocol::InputRanges,
+    pipeline: hidra_protocol::PadPipeline,
 }
 
 #[instrument(level = "debug", skip(backend, rx))]
@@ -60,7 +66,7 @@ async fn main() -> Result<()> {
     info!(pipe=%PIPE_PATH, "hidra-broker starting");
 
     #[cfg(feature = "backend-driver")]
-    let backend: Arc<dyn Backend> = Driver::new();
+    let backend: Arc<dyn Backend> = Driver::new()?;
     #[cfg(not(feature = "backend-driver"))]
     let backend: Arc<dyn Backend> = Mock::new();
     let pumps = Arc::new(Pumps::default());
@@ -95,16 +101,66 @@ async fn serve_connected(
                 info!("client disconnected");
                 break;
             }
-            Ok(Some(BrokerRequest::Create { kind, features })) => {
-                info!(?kind, features, "create device");
-                match backend.create(kind, features).await {
+            Ok(Some(BrokerRequest::Create {
+                kind,
+                features,
+                ranges,
+                transport,
+                pipeline,
+                generic,
+                subtype,
+                slot,
+                identity,
+            })) => {
+                info!(
+                    ?kind,
+                    ?features,
+                    ?ranges,
+                    ?transport,
+                    ?subtype,
+                    ?pipeline,
+                    slot,
+                    "create device"
+                );
+                let caps = kind.capabilities();
+                let validated = caps
+                    .validate(kind, features.bits(), transport)
+                    .and_then(|f| caps.validate_subtype(kind, subtype).map(|()| f))
+                    .and_then(|f| match kind {
+                        hidra_protocol::DeviceKind::Generic => generic.validate().map(|()| f),
+                        _ => Ok(f),
+                    })
+                    .and_then(|f| pipeline.validate().map(|()| f));
+                let features = match validated {
+                    Ok(f) => f,
+                    Err(reason) => {
+                        error!(error=%reason, "create rejected");
+                        write_json(&mut server, &BrokerResponse::Rejected { reason }).await?;
+                        continue;
+                    }
+                };
+                let mut config = hidra_protocol::DeviceConfig {
+                    features,
+                    ranges,
+                    transport,
+                    generic,
+                    subtype,
+                    slot,
+                    identity: *identity,
+                };
+                config.resolve_identity(kind);
+                match backend.create(kind, config).await {
                     Ok(handle) => {
-                        let (tx, rx) = watch::channel::<hidra_protocol::PadState>(
-                            hidra_protocol::PadState::default(),
-                        );
-                        pumps.map.insert(handle, tx);
-                        let b = backend.clone();
-                        tokio::spawn(run_pump(b, handle, rx));
+                        // keyboard and mouse updates go straight through: mouse motion is
+                        // relative, so coalescing it like pad states would drop some
+                        if kind.is_pad() {
+                            let (tx, rx) = watch::channel::<hidra_protocol::PadState>(
+                                hidra_protocol::PadState::default(),
+                            );
+                            pumps.map.insert(handle, Pump { tx, ranges, pipeline });
+                            let b = backend.clone();
+                            tokio::spawn(run_pump(b, handle, rx));
+                        }
                         info!(handle, "created device");
                         write_json(&mut server, &BrokerResponse::OkCreate { handle }).await?;
                     }
@@ -130,16 +186,31 @@ async fn serve_connected(
                     }
                 }
             }
+            Ok(Some(BrokerRequest::Hello { abi_version })) => {
+                let ours = backend.info();
+                let theirs = hidra_protocol::VersionInfo { abi_version, ..ours };
+                let resp = match theirs.check() {
+                    Ok(()) => BrokerResponse::Hello {
+                        abi_version: ours.abi_version,
+                        kinds: ours.supported_kinds().collect(),
+                        features: ours.features(),
+                    },
+                    Err(e) => {
+                        error!(error=%e, "client ABI mismatch");
+                        BrokerResponse::Err { message: format!("client rejected: {e}") }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
             Ok(Some(BrokerRequest::Ping)) => {
                 write_json(&mut server, &BrokerResponse::Pong).await?;
             }
             Ok(Some(BrokerRequest::UpdateState { handle, state })) => {
-                let s: hidra_protocol::PadState = state.try_into()?;
-                if let Some(tx) = pumps.map.get(&handle) {
-                    let _ = tx.value().send(s);
+                if let Some(pump) = pumps.map.get(&handle) {
+                    let _ = pump.tx.send(pump.pipeline.apply(&state, pump.ranges));
                     write_json(&mut server, &BrokerResponse::Ok).await?;
                 } else {
-                    match backend.update(handle, s).await {
+                    match backend.update(handle, state).await {
                         Ok(_) => {
                             info!(handle, "updated state");
                             write_json(&mut server, &BrokerResponse::Ok).await?;
@@ -155,6 +226,86 @@ async fn serve_connected(
                     }
                 }
             }
+            Ok(Some(BrokerRequest::UpdateKeyboard { handle, state })) => {
+                let resp = match backend.update_keyboard(handle, state).await {
+                    Ok(()) => BrokerResponse::Ok,
+                    Err(e) => {
+                        error!(error=%e, "backend keyboard update error");
+                        BrokerResponse::Err { message: e.to_string() }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
+            Ok(Some(BrokerRequest::UpdateMouse { handle, state })) => {
+                let resp = match backend.update_mouse(handle, state).await {
+                    Ok(()) => BrokerResponse::Ok,
+                    Err(e) => {
+                        error!(error=%e, "backend mouse update error");
+                        BrokerResponse::Err { message: e.to_string() }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
+            Ok(Some(BrokerRequest::Subscribe { handle })) => {
+                let mut rx = match backend.subscribe(handle) {
+                    Ok(rx) => rx,
+                    Err(e) => {
+                        error!(error=%e, "subscribe error");
+                        write_json(&mut server, &BrokerResponse::Err { message: e.to_string() })
+                            .await?;
+                        continue;
+                    }
+                };
+                write_json(&mut server, &BrokerResponse::Ok).await?;
+                info!(handle, "subscribed to output");
+                // the connection belongs to the subscription now, until the device
+                // goes away or the client hangs up
+                loop {
+                    let event = match rx.recv().await {
+                        Ok(event) => event,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(handle, skipped, "subscriber lagging, events dropped");
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if write_json(&mut server, &BrokerResponse::Output { event }).await.is_err() {
+                        break;
+                    }
+                }
+                info!(handle, "output subscription ended");
+                break;
+            }
+            Ok(Some(BrokerRequest::InjectOutput { handle, report })) => {
+                let resp = match backend.inject_output(handle, report).await {
+                    Ok(()) => BrokerResponse::Ok,
+                    Err(e) => {
+                        error!(error=%e, "backend inject output error");
+                        BrokerResponse::Err { message: e.to_string() }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
+            Ok(Some(BrokerRequest::GetFeature { handle, id })) => {
+                let resp = match backend.get_feature(handle, id).await {
+                    Ok(report) => BrokerResponse::Feature { report },
+                    Err(e) => {
+                        error!(error=%e, "backend get feature error");
+                        BrokerResponse::Err { message: e.to_string() }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
+            Ok(Some(BrokerRequest::SetFeature { handle, report })) => {
+                let resp = match backend.set_feature(handle, report).await {
+                    Ok(()) => BrokerResponse::Ok,
+                    Err(e) => {
+                        error!(error=%e, "backend set feature error");
+                        BrokerResponse::Err { message: e.to_string() }
+                    }
+                };
+                write_json(&mut server, &resp).await?;
+            }
             Err(e) => {
                 // Send error back (best effort) then exit.
                 error!(error=%e, "protocol/read error");
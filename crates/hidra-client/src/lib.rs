@@ -2,14 +2,52 @@
 
 use anyhow::{Result, bail};
 use hidra_ipc::{
-    BrokerRequest, BrokerResponse, PadState, connect_client, read_json_opt, write_json,
+    BrokerRequest, BrokerResponse, connect_client, read_json_line, read_json_opt, write_json,
 };
-use hidra_protocol::DeviceKind;
+use hidra_protocol::{
+    DeviceConfig, DeviceKind, Features, HIDRA_FFI_ABI_VERSION, KeyboardState, MouseState,
+    PadPipeline, PadState, output::OutputEvent,
+};
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use tokio::net::windows::named_pipe::NamedPipeClient;
 use tracing::{debug, info, instrument};
 
 #[derive(Debug, Clone, Copy)]
 pub struct GamepadHandle(pub u64);
 
+/// What the broker (and the driver behind it) reported during the handshake.
+#[derive(Debug, Clone)]
+pub struct BrokerInfo {
+    pub abi_version: u32,
+    pub kinds: Vec<DeviceKind>,
+    pub features: Features,
+}
+
+/// Connect and exchange `Hello`, so a version mismatch fails here with a clear
+/// error instead of on whatever request comes next. Only `hello` and spawning
+/// pay for it; updates and destroy are one round trip on a plain connection.
+async fn connect() -> Result<(impl AsyncRead + AsyncWrite + Unpin, BrokerInfo)> {
+    let mut pipe = connect_client().await?;
+    write_json(&mut pipe, &BrokerRequest::Hello { abi_version: HIDRA_FFI_ABI_VERSION }).await?;
+    match read_json_opt::<BrokerResponse, _>(&mut pipe).await? {
+        Some(BrokerResponse::Hello { abi_version, kinds, features }) => {
+            if abi_version != HIDRA_FFI_ABI_VERSION {
+                bail!("broker speaks ABI {abi_version}, this client {HIDRA_FFI_ABI_VERSION}");
+            }
+            Ok((pipe, BrokerInfo { abi_version, kinds, features }))
+        }
+        Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
+        other => bail!("unexpected response from broker: {:?}", other),
+    }
+}
+
+#[instrument(level = "debug")]
+pub async fn hello() -> Result<BrokerInfo> {
+    let (_, info) = connect().await?;
+    debug!(?info, "broker hello");
+    Ok(info)
+}
+
 #[instrument(level = "debug")]
 pub async fn ping() -> Result<()> {
     let mut pipe = connect_client().await?;
@@ -25,16 +63,43 @@ pub async fn ping() -> Result<()> {
     }
 }
 
-#[instrument(level = "info", fields(?kind))]
+/// Spawn with the kind's default features (`DeviceKind::capabilities`).
 pub async fn spawn(kind: DeviceKind) -> Result<GamepadHandle> {
-    let features = 0u32;
-    let mut pipe = connect_client().await?;
-    write_json(&mut pipe, &BrokerRequest::Create { kind, features }).await?;
+    spawn_with(kind, DeviceConfig::for_kind(kind)).await
+}
+
+/// Spawn with explicit features, value ranges and transport.
+pub async fn spawn_with(kind: DeviceKind, config: DeviceConfig) -> Result<GamepadHandle> {
+    spawn_processed(kind, config, PadPipeline::default()).await
+}
+
+/// Like `spawn_with`, with the broker running every state through `pipeline`.
+#[instrument(level = "info", fields(?kind, ?config, ?pipeline))]
+pub async fn spawn_processed(
+    kind: DeviceKind,
+    config: DeviceConfig,
+    pipeline: PadPipeline,
+) -> Result<GamepadHandle> {
+    let (mut pipe, _) = connect().await?;
+    let DeviceConfig { features, ranges, transport, generic, subtype, slot, identity } = config;
+    let req = BrokerRequest::Create {
+        kind,
+        features,
+        ranges,
+        transport,
+        pipeline,
+        generic,
+        subtype,
+        slot,
+        identity: Box::new(identity),
+    };
+    write_json(&mut pipe, &req).await?;
     match read_json_opt::<BrokerResponse, _>(&mut pipe).await? {
         Some(BrokerResponse::OkCreate { handle }) => {
             info!(handle, "spawned");
             Ok(GamepadHandle(handle))
         }
+        Some(BrokerResponse::Rejected { reason }) => Err(reason.into()),
         Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
         other => bail!("unexpected response from broker: {:?}", other),
     }
@@ -54,6 +119,28 @@ pub async fn update_state(h: GamepadHandle, s: PadState) -> Result<()> {
     }
 }
 
+/// Set every key down on a keyboard; keys not in `s` are released.
+#[instrument(level = "debug", fields(handle=h.0, state=?s))]
+pub async fn update_keyboard(h: GamepadHandle, s: KeyboardState) -> Result<()> {
+    request_ok(&BrokerRequest::UpdateKeyboard { handle: h.0, state: s }).await
+}
+
+/// Move a mouse by the deltas in `s`, with `s.buttons` held.
+#[instrument(level = "debug", fields(handle=h.0, state=?s))]
+pub async fn update_mouse(h: GamepadHandle, s: MouseState) -> Result<()> {
+    request_ok(&BrokerRequest::UpdateMouse { handle: h.0, state: s }).await
+}
+
+async fn request_ok(req: &BrokerRequest) -> Result<()> {
+    let mut pipe = connect_client().await?;
+    write_json(&mut pipe, req).await?;
+    match read_json_opt::<BrokerResponse, _>(&mut pipe).await? {
+        Some(BrokerResponse::Ok) => Ok(()),
+        Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
+        other => bail!("unexpected response: {:?}", other),
+    }
+}
+
 #[instrument(level = "info", fields(handle=h.0))]
 pub async fn destroy(h: GamepadHandle) -> Result<()> {
     let mut pipe = connect_client().await?;
@@ -67,3 +154,62 @@ pub async fn destroy(h: GamepadHandle) -> Result<()> {
         other => bail!("unexpected response from broker: {:?}", other),
     }
 }
+
+/// Output events the host sends to a device, from `subscribe`.
+pub struct OutputEvents {
+    pipe: BufReader<NamedPipeClient>,
+}
+
+impl OutputEvents {
+    /// The next event; `None` once the device is destroyed.
+    pub async fn next(&mut self) -> Result<Option<OutputEvent>> {
+        match read_json_line::<BrokerResponse, _>(&mut self.pipe).await? {
+            Some(BrokerResponse::Output { event }) => Ok(Some(event)),
+            None => Ok(None),
+            Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
+            other => bail!("unexpected response: {:?}", other),
+        }
+    }
+}
+
+/// Follow the rumble, lights and other output reports games send to `h`.
+/// Takes a connection of its own for as long as the stream lives.
+#[instrument(level = "info", fields(handle=h.0))]
+pub async fn subscribe(h: GamepadHandle) -> Result<OutputEvents> {
+    let mut pipe = BufReader::new(connect_client().await?);
+    write_json(&mut pipe, &BrokerRequest::Subscribe { handle: h.0 }).await?;
+    match read_json_line::<BrokerResponse, _>(&mut pipe).await? {
+        Some(BrokerResponse::Ok) => {
+            info!("subscribed");
+            Ok(OutputEvents { pipe })
+        }
+        Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
+        other => bail!("unexpected response from broker: {:?}", other),
+    }
+}
+
+/// Hand `report` to `h` as an output report from the host. Only the mock
+/// backend accepts this; it lets `subscribe` be tried without a game.
+#[instrument(level = "debug", fields(handle=h.0, ?report))]
+pub async fn inject_output(h: GamepadHandle, report: &[u8]) -> Result<()> {
+    request_ok(&BrokerRequest::InjectOutput { handle: h.0, report: report.to_vec() }).await
+}
+
+/// Feature report `id` of `h`, report ID first, as the host would read it.
+#[instrument(level = "debug", fields(handle=h.0, id))]
+pub async fn get_feature(h: GamepadHandle, id: u8) -> Result<Vec<u8>> {
+    let mut pipe = connect_client().await?;
+    write_json(&mut pipe, &BrokerRequest::GetFeature { handle: h.0, id }).await?;
+    match read_json_opt::<BrokerResponse, _>(&mut pipe).await? {
+        Some(BrokerResponse::Feature { report }) => Ok(report),
+        Some(BrokerResponse::Err { message }) => bail!("broker error: {message}"),
+        other => bail!("unexpected response: {:?}", other),
+    }
+}
+
+/// Hand `report` to `h` as a feature report the host set. Only the mock
+/// backend accepts this, e.g. to walk a wheel through effect allocation.
+#[instrument(level = "debug", fields(handle=h.0, ?report))]
+pub async fn set_feature(h: GamepadHandle, report: &[u8]) -> Result<()> {
+    request_ok(&BrokerRequest::SetFeature { handle: h.0, report: report.to_vec() }).await
+}
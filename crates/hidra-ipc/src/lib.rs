@@ -1,59 +1,120 @@
 #![deny(warnings)]
 
 use anyhow::{Context, Result};
+use hidra_protocol::{
+    DeviceIdentity, DeviceKind, Features, GenericSpec, InputRanges, KeyboardState, MouseState,
+    PadPipeline, PadState, Transport, XusbSubtype, output::OutputEvent,
+};
 use serde::{Deserialize, Serialize};
 use tokio::net::windows::named_pipe::ClientOptions;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
     net::windows::named_pipe::NamedPipeClient,
 };
 
 pub const PIPE_PATH: &str = r"\\.\pipe\hidra";
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct PadState {
-    pub buttons: u16,
-    pub lx: i16,
-    pub ly: i16,
-    pub rx: i16,
-    pub ry: i16,
-    pub lt: u16,
-    pub rt: u16,
-    //TODO: add touchpad, motion, battery, etc
-}
-
-impl TryFrom<PadState> for hidra_protocol::PadState {
-    type Error = anyhow::Error;
-
-    fn try_from(s: PadState) -> Result<Self> {
-        Ok(hidra_protocol::PadState {
-            buttons: s.buttons,
-            lx: s.lx,
-            ly: s.ly,
-            rx: s.rx,
-            ry: s.ry,
-            lt: s.lt,
-            rt: s.rt,
-        })
-    }
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "cmd", rename_all = "lowercase")]
 pub enum BrokerRequest {
     Ping,
-    Create { kind: hidra_protocol::DeviceKind, features: u32 },
-    Destroy { handle: u64 },
-    UpdateState { handle: u64, state: PadState },
+    /// Optional first message on a connection; the broker refuses mismatched ABI versions.
+    Hello {
+        abi_version: u32,
+    },
+    Create {
+        kind: DeviceKind,
+        /// Names or, as v1 clients send it, raw bits.
+        features: Features,
+        #[serde(default)]
+        ranges: InputRanges,
+        #[serde(default)]
+        transport: Transport,
+        /// Applied by the broker to every state sent for this device.
+        #[serde(default)]
+        pipeline: PadPipeline,
+        /// Layout of a `generic` pad; ignored for every other kind.
+        #[serde(default)]
+        generic: GenericSpec,
+        /// What an `x360` presents as; every other kind only accepts `gamepad`.
+        #[serde(default)]
+        subtype: XusbSubtype,
+        /// Picks the default identity, so the same slot is the same controller
+        /// to games across broker restarts.
+        #[serde(default)]
+        slot: u8,
+        /// Overrides of the default identity; fields left out keep it.
+        #[serde(default)]
+        identity: Box<DeviceIdentity>,
+    },
+    Destroy {
+        handle: u64,
+    },
+    UpdateState {
+        handle: u64,
+        state: PadState,
+    },
+    /// Every key down on a `keyboard`, e.g. `{"modifiers": ["left_shift"], "keys": ["a"]}`.
+    UpdateKeyboard {
+        handle: u64,
+        state: KeyboardState,
+    },
+    /// Buttons held on a `mouse` plus the motion since the last update.
+    UpdateMouse {
+        handle: u64,
+        state: MouseState,
+    },
+    /// Stream the output events (rumble, lights, ...) the host sends to a device:
+    /// `ok`, then one `output` per event until the device is destroyed. The
+    /// connection carries nothing else afterwards.
+    Subscribe {
+        handle: u64,
+    },
+    /// Deliver a raw output report as if the host had sent it; mock backend only.
+    InjectOutput {
+        handle: u64,
+        report: Vec<u8>,
+    },
+    /// Read feature report `id` as the host would; answered with `feature`.
+    GetFeature {
+        handle: u64,
+        id: u8,
+    },
+    /// Deliver a feature report as if the host had set it; mock backend only.
+    SetFeature {
+        handle: u64,
+        report: Vec<u8>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "lowercase")]
 pub enum BrokerResponse {
     Pong,
-    OkCreate { handle: u64 },
+    Hello {
+        abi_version: u32,
+        kinds: Vec<DeviceKind>,
+        features: Features,
+    },
+    OkCreate {
+        handle: u64,
+    },
+    /// Create refused before reaching the backend.
+    Rejected {
+        reason: hidra_protocol::CreateError,
+    },
     Ok,
-    Err { message: String },
+    /// One event on a `subscribe` connection.
+    Output {
+        event: OutputEvent,
+    },
+    /// Answer to `getfeature`, report ID first.
+    Feature {
+        report: Vec<u8>,
+    },
+    Err {
+        message: String,
+    },
 }
 
 // === Client helpers ===
@@ -69,9 +130,18 @@ where
     T: for<'de> serde::Deserialize<'de>,
     R: tokio::io::AsyncRead + Unpin,
 {
-    let mut br = BufReader::new(reader);
+    read_json_line(&mut BufReader::new(reader)).await
+}
+
+/// Like `read_json_opt`, but keeps whatever the reader buffered past the line,
+/// for connections that carry more than one frame per request.
+pub async fn read_json_line<T, R>(reader: &mut R) -> Result<Option<T>>
+where
+    T: for<'de> serde::Deserialize<'de>,
+    R: AsyncBufRead + Unpin,
+{
     let mut line = String::new();
-    let n = br.read_line(&mut line).await.context("read_line failed")?;
+    let n = reader.read_line(&mut line).await.context("read_line failed")?;
     if n == 0 {
         return Ok(None);
     }
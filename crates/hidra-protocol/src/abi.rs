@@ -0,0 +1,520 @@
+//! The kernel ABI described as data, and the C header generated from it.
+//! `drivers/HIDraBus/HIDraAbi.h` is the output of `c_header`; regenerate it with
+//! `hidra gen-header` after touching any `#[repr(C)]` type or IOCTL code.
+
+use crate::*;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use bitflags::Flags;
+use core::fmt::Write;
+use core::mem::offset_of;
+
+/// Path of the generated header, relative to the workspace root.
+pub const C_HEADER_PATH: &str = "drivers/HIDraBus/HIDraAbi.h";
+
+struct CField {
+    name: &'static str,
+    ty: &'static str,
+    len: Option<usize>,
+    offset: usize,
+    size: usize,
+    /// The Rust field's type, to check `ty` against.
+    rust: &'static str,
+}
+
+struct CStruct {
+    name: &'static str,
+    size: usize,
+    fields: Vec<CField>,
+}
+
+fn field_size<T, F>(_: fn(&T) -> &F) -> usize {
+    size_of::<F>()
+}
+
+fn field_type<T, F>(_: fn(&T) -> &F) -> &'static str {
+    core::any::type_name::<F>()
+}
+
+/// Size of one `ty`: a Windows base type or a struct described before it.
+fn c_type_size(ty: &str, earlier: &[CStruct]) -> Option<usize> {
+    Some(match ty {
+        "UINT8" => 1,
+        "UINT16" | "SHORT" | "WCHAR" => 2,
+        "UINT32" => 4,
+        "ULONGLONG" => 8,
+        _ => earlier.iter().find(|s| s.name == ty)?.size,
+    })
+}
+
+macro_rules! c_struct {
+    ($t:ty as $name:literal { $($f:ident: $ty:literal $([$n:literal])? => $c:literal,)* }) => {
+        CStruct {
+            name: $name,
+            size: size_of::<$t>(),
+            fields: vec![$(CField {
+                name: $c,
+                ty: $ty,
+                len: None $(.or(Some($n)))?,
+                offset: offset_of!($t, $f),
+                size: field_size(|s: &$t| &s.$f),
+                rust: field_type(|s: &$t| &s.$f),
+            },)*],
+        }
+    };
+}
+
+fn ioctls() -> [(&'static str, u32); 6] {
+    [
+        ("IOCTL_HIDRA_CREATE", IOCTL_HIDRA_CREATE),
+        ("IOCTL_HIDRA_UPDATE", IOCTL_HIDRA_UPDATE),
+        ("IOCTL_HIDRA_DESTROY", IOCTL_HIDRA_DESTROY),
+        ("IOCTL_HIDRA_GET_VERSION", IOCTL_HIDRA_GET_VERSION),
+        ("IOCTL_HIDRA_UPDATE_KEYBOARD", IOCTL_HIDRA_UPDATE_KEYBOARD),
+        ("IOCTL_HIDRA_UPDATE_MOUSE", IOCTL_HIDRA_UPDATE_MOUSE),
+    ]
+}
+
+/// Every struct that crosses the IOCTL boundary, dependencies first.
+fn structs() -> Vec<CStruct> {
+    vec![
+        c_struct!(StateHeader as "HIDRA_STATE_HEADER" {
+            size: "UINT16" => "Size",
+            version: "UINT16" => "Version",
+        }),
+        c_struct!(TouchPoint as "HIDRA_TOUCH_POINT" {
+            down: "UINT8" => "Down",
+            id: "UINT8" => "Id",
+            x: "UINT16" => "X",
+            y: "UINT16" => "Y",
+        }),
+        c_struct!(Motion as "HIDRA_MOTION" {
+            gyro: "SHORT"[3] => "Gyro",
+            accel: "SHORT"[3] => "Accel",
+        }),
+        c_struct!(Battery as "HIDRA_BATTERY" {
+            level: "UINT8" => "Level",
+            charge: "UINT8" => "Charge",
+        }),
+        c_struct!(PadState as "HIDRA_PAD_STATE" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            buttons: "UINT32" => "Buttons",
+            lx: "SHORT" => "Lx",
+            ly: "SHORT" => "Ly",
+            rx: "SHORT" => "Rx",
+            ry: "SHORT" => "Ry",
+            lt: "UINT16" => "Lt",
+            rt: "UINT16" => "Rt",
+            touch: "HIDRA_TOUCH_POINT"[2] => "Touch",
+            motion: "HIDRA_MOTION" => "Motion",
+            battery: "HIDRA_BATTERY" => "Battery",
+            reserved: "UINT8"[2] => "Reserved",
+        }),
+        c_struct!(PadStateV2 as "HIDRA_PAD_STATE_V2" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            buttons: "UINT16" => "Buttons",
+            ext_buttons: "UINT16" => "ExtButtons",
+            lx: "SHORT" => "Lx",
+            ly: "SHORT" => "Ly",
+            rx: "SHORT" => "Rx",
+            ry: "SHORT" => "Ry",
+            lt: "UINT16" => "Lt",
+            rt: "UINT16" => "Rt",
+            touch: "HIDRA_TOUCH_POINT"[2] => "Touch",
+            motion: "HIDRA_MOTION" => "Motion",
+            battery: "HIDRA_BATTERY" => "Battery",
+            reserved: "UINT8"[2] => "Reserved",
+        }),
+        c_struct!(PadStateV1 as "HIDRA_PAD_STATE_V1" {
+            buttons: "UINT16" => "Buttons",
+            lx: "SHORT" => "Lx",
+            ly: "SHORT" => "Ly",
+            rx: "SHORT" => "Rx",
+            ry: "SHORT" => "Ry",
+            lt: "UINT16" => "Lt",
+            rt: "UINT16" => "Rt",
+        }),
+        c_struct!(InputRanges as "HIDRA_INPUT_RANGES" {
+            trigger: "UINT8" => "TriggerBits",
+            stick: "UINT8" => "StickRange",
+        }),
+        c_struct!(VersionInfo as "HIDRA_VERSION_INFO" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            abi_version: "UINT32" => "AbiVersion",
+            kinds: "UINT32" => "Kinds",
+            features: "UINT32" => "Features",
+        }),
+        c_struct!(CreateIn as "HIDRA_CREATE_IN" {
+            kind: "UINT16" => "Kind",
+            reserved0: "UINT8"[2] => "Reserved0",
+            features: "UINT32" => "Features",
+            ranges: "HIDRA_INPUT_RANGES" => "Ranges",
+            transport: "UINT8" => "Transport",
+            subtype: "UINT8" => "Subtype",
+        }),
+        c_struct!(GenericSpec as "HIDRA_GENERIC_SPEC" {
+            vendor_id: "UINT16" => "VendorId",
+            product_id: "UINT16" => "ProductId",
+            axes: "UINT8" => "Axes",
+            axis_bits: "UINT8" => "AxisBits",
+            buttons: "UINT8" => "Buttons",
+            hats: "UINT8" => "Hats",
+            product: "WCHAR"[32] => "Product",
+        }),
+        c_struct!(CreateGenericIn as "HIDRA_CREATE_GENERIC_IN" {
+            create: "HIDRA_CREATE_IN" => "Create",
+            spec: "HIDRA_GENERIC_SPEC" => "Spec",
+        }),
+        c_struct!(DeviceIdentity as "HIDRA_DEVICE_IDENTITY" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            vendor_id: "UINT16" => "VendorId",
+            product_id: "UINT16" => "ProductId",
+            version: "UINT16" => "Version",
+            mac: "UINT8"[6] => "Mac",
+            manufacturer: "WCHAR"[32] => "Manufacturer",
+            product: "WCHAR"[32] => "Product",
+            serial: "WCHAR"[32] => "Serial",
+        }),
+        c_struct!(CreateOut as "HIDRA_CREATE_OUT" {
+            handle: "ULONGLONG" => "Handle",
+        }),
+        c_struct!(UpdateIn as "HIDRA_UPDATE_IN" {
+            handle: "ULONGLONG" => "Handle",
+            state: "HIDRA_PAD_STATE" => "State",
+        }),
+        c_struct!(UpdateInV1 as "HIDRA_UPDATE_IN_V1" {
+            handle: "ULONGLONG" => "Handle",
+            state: "HIDRA_PAD_STATE_V1" => "State",
+            reserved: "UINT8"[2] => "Reserved",
+        }),
+        c_struct!(DestroyIn as "HIDRA_DESTROY_IN" {
+            handle: "ULONGLONG" => "Handle",
+        }),
+        c_struct!(KeyboardState as "HIDRA_KEYBOARD_STATE" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            modifiers: "UINT8" => "Modifiers",
+            reserved: "UINT8"[3] => "Reserved",
+            keys: "UINT8"[32] => "Keys",
+        }),
+        c_struct!(MouseState as "HIDRA_MOUSE_STATE" {
+            header: "HIDRA_STATE_HEADER" => "Header",
+            buttons: "UINT8" => "Buttons",
+            reserved: "UINT8"[3] => "Reserved",
+            dx: "SHORT" => "Dx",
+            dy: "SHORT" => "Dy",
+            wheel: "SHORT" => "Wheel",
+            pan: "SHORT" => "Pan",
+        }),
+        c_struct!(UpdateKeyboardIn as "HIDRA_UPDATE_KEYBOARD_IN" {
+            handle: "ULONGLONG" => "Handle",
+            state: "HIDRA_KEYBOARD_STATE" => "State",
+        }),
+        c_struct!(UpdateMouseIn as "HIDRA_UPDATE_MOUSE_IN" {
+            handle: "ULONGLONG" => "Handle",
+            state: "HIDRA_MOUSE_STATE" => "State",
+        }),
+    ]
+}
+
+fn kind_name(k: DeviceKind) -> String {
+    k.name().to_ascii_uppercase()
+}
+
+/// C enums: (type name, [(constant, value)]).
+fn enums() -> Vec<(&'static str, Vec<(String, u32)>)> {
+    let kind = |k: DeviceKind| (format!("HIDRA_KIND_{}", kind_name(k)), k as u32);
+    let trigger = |t: TriggerRange| (format!("HIDRA_TRIGGER_BITS_{}", t as u8), t as u32);
+    let subtype = |s: XusbSubtype| {
+        (format!("HIDRA_XUSB_SUBTYPE_{}", s.name().to_ascii_uppercase()), s as u32)
+    };
+    vec![
+        ("HIDRA_DEVICE_KIND", DeviceKind::ALL.into_iter().map(kind).collect()),
+        (
+            "HIDRA_TRIGGER_RANGE",
+            [TriggerRange::Bits8, TriggerRange::Bits10, TriggerRange::Bits12, TriggerRange::Bits16]
+                .into_iter()
+                .map(trigger)
+                .collect(),
+        ),
+        (
+            "HIDRA_STICK_RANGE",
+            vec![
+                ("HIDRA_STICK_FULL".into(), StickRange::Full as u32),
+                ("HIDRA_STICK_HALF".into(), StickRange::Half as u32),
+            ],
+        ),
+        (
+            "HIDRA_TRANSPORT",
+            vec![
+                ("HIDRA_TRANSPORT_USB".into(), Transport::Usb as u32),
+                ("HIDRA_TRANSPORT_BLUETOOTH".into(), Transport::Bluetooth as u32),
+            ],
+        ),
+        ("HIDRA_XUSB_SUBTYPE", XusbSubtype::ALL.into_iter().map(subtype).collect()),
+        (
+            "HIDRA_CHARGE_STATE",
+            vec![
+                ("HIDRA_CHARGE_DISCHARGING".into(), ChargeState::Discharging as u32),
+                ("HIDRA_CHARGE_CHARGING".into(), ChargeState::Charging as u32),
+                ("HIDRA_CHARGE_FULL".into(), ChargeState::Full as u32),
+            ],
+        ),
+    ]
+}
+
+fn flags<F: Flags<Bits: Into<u32>>>(prefix: &str) -> Vec<(String, u32)> {
+    F::FLAGS.iter().map(|f| (format!("{prefix}_{}", f.name()), f.value().bits().into())).collect()
+}
+
+/// The HIDraBus ABI header. Every struct carries `C_ASSERT`s on its size and
+/// field offsets and sizes, so a C compiler that lays it out differently fails
+/// the driver build.
+pub fn c_header() -> String {
+    let mut h = String::new();
+    let w = &mut h;
+    let _ = writeln!(w, "// Generated from hidra-protocol (hidra_protocol::abi). DO NOT EDIT.");
+    let _ = writeln!(
+        w,
+        "// Regenerate with `hidra gen-header`; a hidra-protocol test fails when stale."
+    );
+    let _ = writeln!(w, "#pragma once");
+    let _ = writeln!(w);
+
+    let _ = writeln!(w, "#define HIDRA_FFI_ABI_VERSION {HIDRA_FFI_ABI_VERSION}");
+    let _ = writeln!(w, "#define HIDRA_PAD_STATE_VERSION {PAD_STATE_VERSION}");
+    let _ = writeln!(w, "#define HIDRA_PAD_STATE_V2_VERSION {PAD_STATE_V2_VERSION}");
+    let _ = writeln!(w, "#define HIDRA_VERSION_INFO_VERSION {VERSION_INFO_VERSION}");
+    let _ =
+        writeln!(w, "#define HIDRA_KEYBOARD_STATE_VERSION {}", keyboard::KEYBOARD_STATE_VERSION);
+    let _ = writeln!(w, "#define HIDRA_MOUSE_STATE_VERSION {}", mouse::MOUSE_STATE_VERSION);
+    let _ = writeln!(w, "#define HIDRA_IDENTITY_VERSION {}", identity::IDENTITY_VERSION);
+    let _ = writeln!(w);
+    let _ =
+        writeln!(w, "// CTL_CODE layout: (DeviceType<<16) | (Access<<14) | (Function<<2) | Method");
+    let _ = writeln!(w, "#define HIDRA_DEVICE_TYPE 0x{HIDRA_DEVICE_TYPE:04X}");
+    let _ = writeln!(w, "#define HIDRA_IOCTL_BASE 0x{HIDRA_IOCTL_BASE:03X}");
+    for (name, code) in ioctls() {
+        let _ = writeln!(w, "#define {name} 0x{code:08X}");
+    }
+
+    for (name, variants) in enums() {
+        let _ = writeln!(w);
+        let _ = writeln!(w, "typedef enum _{name}\n{{");
+        for (v, value) in variants {
+            let _ = writeln!(w, "    {v} = 0x{value:04X},");
+        }
+        let _ = writeln!(w, "}} {name};");
+    }
+
+    let kind_bits =
+        DeviceKind::ALL.map(|k| (format!("HIDRA_KIND_BIT_{}", kind_name(k)), k.bit())).to_vec();
+    let kind_features = DeviceKind::ALL
+        .map(|k| {
+            (format!("HIDRA_KIND_FEATURES_{}", kind_name(k)), k.capabilities().features.bits())
+        })
+        .to_vec();
+    for values in [
+        kind_bits,
+        flags::<Features>("HIDRA_FEATURE"),
+        kind_features,
+        flags::<Buttons>("HIDRA_BUTTON"),
+        flags::<Modifiers>("HIDRA_MODIFIER"),
+        flags::<keyboard::KeyboardLeds>("HIDRA_KEYBOARD_LED"),
+        flags::<MouseButtons>("HIDRA_MOUSE_BUTTON"),
+    ] {
+        let _ = writeln!(w);
+        for (name, bits) in values {
+            let _ = writeln!(w, "#define {name} 0x{bits:08X}");
+        }
+    }
+
+    let _ = writeln!(w);
+    let hat = GenericSpec { hats: 1, ..Default::default() };
+    for (name, value) in [
+        ("MAX_AXES", generic::GENERIC_MAX_AXES),
+        ("MIN_AXIS_BITS", generic::GENERIC_MIN_AXIS_BITS),
+        ("MAX_AXIS_BITS", generic::GENERIC_MAX_AXIS_BITS),
+        ("MAX_HATS", generic::GENERIC_MAX_HATS),
+        ("MAX_BUTTONS", GenericSpec { hats: 0, ..hat }.max_buttons()),
+        ("MAX_BUTTONS_WITH_HAT", hat.max_buttons()),
+    ] {
+        let _ = writeln!(w, "#define HIDRA_GENERIC_{name} {value}");
+    }
+
+    for s in structs() {
+        let _ = writeln!(w);
+        let _ = writeln!(w, "typedef struct _{}\n{{", s.name);
+        for f in &s.fields {
+            let _ = match f.len {
+                Some(n) => writeln!(w, "    {} {}[{n}];", f.ty, f.name),
+                None => writeln!(w, "    {} {};", f.ty, f.name),
+            };
+        }
+        let _ = writeln!(w, "}} {0}, *P{0};", s.name);
+        let _ = writeln!(w, "C_ASSERT(sizeof({}) == {});", s.name, s.size);
+        for f in &s.fields {
+            let _ = writeln!(w, "C_ASSERT(FIELD_OFFSET({}, {}) == {});", s.name, f.name, f.offset);
+            let _ = writeln!(w, "C_ASSERT(RTL_FIELD_SIZE({}, {}) == {});", s.name, f.name, f.size);
+        }
+    }
+
+    let g = HIDRA_INTERFACE_ID;
+    let d4 = (g as u64).to_be_bytes().map(|b| format!("0x{b:02X}")).join(", ");
+    let _ = writeln!(w);
+    let _ = writeln!(
+        w,
+        "DEFINE_GUID(GUID_DEVINTERFACE_HIDRA,\n    0x{:08X}, 0x{:04X}, 0x{:04X}, {d4});",
+        (g >> 96) as u32,
+        (g >> 80) as u16,
+        (g >> 64) as u16,
+    );
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_in_header_is_current() {
+        let on_disk = include_str!("../../../drivers/HIDraBus/HIDraAbi.h").replace("\r\n", "\n");
+        assert!(on_disk == c_header(), "{C_HEADER_PATH} is stale, run `hidra gen-header`");
+    }
+
+    /// Every byte of every struct is described by exactly one field (or trailing
+    /// padding), so a Rust field missing from the table can't go unnoticed.
+    #[test]
+    fn fields_cover_structs() {
+        for s in structs() {
+            let mut end = 0;
+            for f in &s.fields {
+                assert!(f.offset >= end, "{}.{} overlaps", s.name, f.name);
+                end = f.offset + f.size;
+            }
+            let padding = s.size - s.fields.iter().map(|f| f.size).sum::<usize>();
+            assert!(padding < 8, "{} has {padding} undescribed bytes", s.name);
+        }
+    }
+
+    /// Each field's C type has the Rust field's size and signedness, so a
+    /// hand-typed `ty` can't drift from the struct it describes.
+    #[test]
+    fn c_types_match_rust_fields() {
+        let structs = structs();
+        for (i, s) in structs.iter().enumerate() {
+            for f in &s.fields {
+                let size = c_type_size(f.ty, &structs[..i])
+                    .unwrap_or_else(|| panic!("{}.{}: unknown C type {}", s.name, f.name, f.ty));
+                let len = f.len.unwrap_or(1);
+                assert_eq!(size * len, f.size, "{}.{}: {} is {}", s.name, f.name, f.ty, f.rust);
+                let elem = f.rust.trim_start_matches('[').split(';').next().unwrap();
+                let signed = matches!(elem, "i8" | "i16" | "i32" | "i64");
+                assert_eq!(
+                    signed,
+                    f.ty == "SHORT",
+                    "{}.{}: {} is {}",
+                    s.name,
+                    f.name,
+                    f.ty,
+                    f.rust
+                );
+            }
+        }
+    }
+
+    /// The wire layout the shipped driver was built against. Changing any of these
+    /// needs a new IOCTL or struct version and an ABI bump, not just a
+    /// regenerated header.
+    #[test]
+    fn layout_is_pinned() {
+        assert_eq!(size_of::<CreateIn>(), 12);
+        assert_eq!(offset_of!(CreateIn, features), 4);
+        assert_eq!(offset_of!(CreateIn, ranges), 8);
+        assert_eq!(offset_of!(CreateIn, transport), 10);
+        assert_eq!(offset_of!(CreateIn, subtype), 11);
+        assert_eq!(size_of::<UpdateIn>(), 56);
+        assert_eq!(offset_of!(UpdateIn, state), 8);
+        assert_eq!(size_of::<UpdateInV1>(), 24);
+        assert_eq!(offset_of!(PadState, touch), 20);
+        assert_eq!(offset_of!(PadState, motion), 32);
+        assert_eq!(offset_of!(PadState, battery), 44);
+        assert_eq!(offset_of!(PadStateV2, ext_buttons), 6);
+        assert_eq!(offset_of!(PadStateV2, touch), 20);
+        assert_eq!(IOCTL_HIDRA_CREATE, 0x0022_A000);
+        assert_eq!(IOCTL_HIDRA_UPDATE, 0x0022_A004);
+        assert_eq!(IOCTL_HIDRA_DESTROY, 0x0022_A008);
+        assert_eq!(IOCTL_HIDRA_GET_VERSION, 0x0022_200C);
+        assert_eq!(size_of::<VersionInfo>(), 16);
+        assert_eq!(size_of::<CreateGenericIn>(), 84);
+        assert_eq!(offset_of!(CreateGenericIn, spec), 12);
+        assert_eq!(offset_of!(GenericSpec, product), 8);
+        assert_eq!(IOCTL_HIDRA_UPDATE_KEYBOARD, 0x0022_A010);
+        assert_eq!(IOCTL_HIDRA_UPDATE_MOUSE, 0x0022_A014);
+        assert_eq!(size_of::<UpdateKeyboardIn>(), 48);
+        assert_eq!(offset_of!(KeyboardState, keys), 8);
+        assert_eq!(size_of::<UpdateMouseIn>(), 24);
+        assert_eq!(offset_of!(MouseState, dx), 8);
+        assert_eq!(size_of::<DeviceIdentity>(), 208);
+        assert_eq!(offset_of!(DeviceIdentity, mac), 10);
+        assert_eq!(offset_of!(DeviceIdentity, manufacturer), 16);
+    }
+
+    /// Any change to a struct layout or IOCTL code changes this fingerprint. When
+    /// it does, bump `HIDRA_FFI_ABI_VERSION` and pin the new pair.
+    #[test]
+    fn abi_version_tracks_layout() {
+        let mut text = String::new();
+        for (name, code) in ioctls() {
+            let _ = writeln!(text, "{name} {code:08X}");
+        }
+        for s in structs() {
+            let _ = writeln!(text, "{} {}", s.name, s.size);
+            for f in &s.fields {
+                let _ = writeln!(text, " {} {} {:?} {} {}", f.name, f.ty, f.len, f.offset, f.size);
+            }
+        }
+        // FNV-1a
+        let hash = text.bytes().fold(0xCBF2_9CE4_8422_2325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+        assert_eq!((HIDRA_FFI_ABI_VERSION, hash), (2, 0xFED6_740B_3B41_AA33));
+    }
+
+    #[test]
+    fn discriminants_are_pinned() {
+        let kinds: Vec<_> = DeviceKind::ALL.iter().map(|k| *k as u16).collect();
+        assert_eq!(
+            kinds,
+            [
+                0x0366, 0x05C4, 0x0CE6, 0x0B13, 0x2009, 0x4844, 0x5748, 0x0DF2, 0x0B00, 0x4B42,
+                0x4D53
+            ]
+        );
+        let bits: Vec<_> = DeviceKind::ALL.iter().map(|k| k.bit()).collect();
+        assert_eq!(bits, [1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024]);
+        assert_eq!(TriggerRange::Bits12 as u8, 12);
+        assert_eq!(StickRange::Half as u8, 1);
+        assert_eq!(ChargeState::Full as u8, 2);
+        assert!(c_header().contains("HIDRA_KIND_DS4 = 0x05C4,"));
+        assert!(c_header().contains("HIDRA_KIND_XBOX_ONE = 0x0B13,"));
+    }
+
+    #[test]
+    fn version_info_check() {
+        let info = VersionInfo::new(&[DeviceKind::DS4, DeviceKind::DS5], Features::RUMBLE);
+        assert_eq!(info.check(), Ok(()));
+        assert!(!info.supports(DeviceKind::X360));
+        assert_eq!(info.supported_kinds().collect::<Vec<_>>().len(), 2);
+        let old = VersionInfo { abi_version: 0, ..info };
+        assert_eq!(old.check(), Err(Error::AbiVersion { expected: HIDRA_FFI_ABI_VERSION, got: 0 }));
+    }
+
+    #[test]
+    fn guid_matches_interface_id() {
+        assert!(c_header().contains(
+            "0x468ADE0B, 0x0432, 0x4680, 0xB5, 0x7B, 0x18, 0x50, 0xB3, 0x3C, 0x7D, 0x9B);"
+        ));
+    }
+}
@@ -0,0 +1,515 @@
+//! Canonical button model and its wire layouts.
+//! The low 16 bits of `Buttons` deliberately match XUSB so v1 states convert for free.
+
+bitflags::bitflags! {
+    /// Every button HIDra knows about, independent of the device kind.
+    /// PlayStation names: A = cross, B = circle, X = square, Y = triangle,
+    /// BACK = share/create, START = options, GUIDE = PS.
+    /// Serialized like `Features`: a list of lowercase names, or a bare number.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct Buttons: u32 {
+        const DPAD_UP        = 1 << 0;
+        const DPAD_DOWN      = 1 << 1;
+        const DPAD_LEFT      = 1 << 2;
+        const DPAD_RIGHT     = 1 << 3;
+        const START          = 1 << 4;
+        const BACK           = 1 << 5;
+        const LEFT_THUMB     = 1 << 6;
+        const RIGHT_THUMB    = 1 << 7;
+        const LEFT_SHOULDER  = 1 << 8;
+        const RIGHT_SHOULDER = 1 << 9;
+        const GUIDE          = 1 << 10;
+        const A              = 1 << 12;
+        const B              = 1 << 13;
+        const X              = 1 << 14;
+        const Y              = 1 << 15;
+        const TOUCHPAD       = 1 << 16;
+        const MUTE           = 1 << 17;
+        /// Back paddles, Elite numbering: P1/P2 right upper/lower, P3/P4 left upper/lower.
+        const PADDLE1        = 1 << 18;
+        const PADDLE2        = 1 << 19;
+        const PADDLE3        = 1 << 20;
+        const PADDLE4        = 1 << 21;
+        /// Xbox Series share, Switch capture.
+        const SHARE          = 1 << 22;
+        /// DualSense Edge function buttons under the sticks.
+        const FN_LEFT        = 1 << 23;
+        const FN_RIGHT       = 1 << 24;
+
+        const DPAD = Self::DPAD_UP.bits()
+            | Self::DPAD_DOWN.bits()
+            | Self::DPAD_LEFT.bits()
+            | Self::DPAD_RIGHT.bits();
+    }
+}
+
+/// XUSB `wButtons` bit per button.
+pub const XUSB_LAYOUT: &[(Buttons, u16)] = &[
+    (Buttons::DPAD_UP, 0x0001),
+    (Buttons::DPAD_DOWN, 0x0002),
+    (Buttons::DPAD_LEFT, 0x0004),
+    (Buttons::DPAD_RIGHT, 0x0008),
+    (Buttons::START, 0x0010),
+    (Buttons::BACK, 0x0020),
+    (Buttons::LEFT_THUMB, 0x0040),
+    (Buttons::RIGHT_THUMB, 0x0080),
+    (Buttons::LEFT_SHOULDER, 0x0100),
+    (Buttons::RIGHT_SHOULDER, 0x0200),
+    (Buttons::GUIDE, 0x0400),
+    (Buttons::A, 0x1000),
+    (Buttons::B, 0x2000),
+    (Buttons::X, 0x4000),
+    (Buttons::Y, 0x8000),
+];
+
+/// DS4 button block (input report bytes 5..8): (button, byte, bit).
+/// The d-pad is a hat in the low nibble of byte 0; the digital L2/R2 bits
+/// (byte 1, 0x04/0x08) and the frame counter belong to the packer.
+pub const DS4_LAYOUT: &[(Buttons, usize, u8)] = &[
+    (Buttons::X, 0, 0x10),
+    (Buttons::A, 0, 0x20),
+    (Buttons::B, 0, 0x40),
+    (Buttons::Y, 0, 0x80),
+    (Buttons::LEFT_SHOULDER, 1, 0x01),
+    (Buttons::RIGHT_SHOULDER, 1, 0x02),
+    (Buttons::BACK, 1, 0x10),
+    (Buttons::START, 1, 0x20),
+    (Buttons::LEFT_THUMB, 1, 0x40),
+    (Buttons::RIGHT_THUMB, 1, 0x80),
+    (Buttons::GUIDE, 2, 0x01),
+    (Buttons::TOUCHPAD, 2, 0x02),
+];
+
+/// DualSense button block (input report bytes 8..12), same conventions as `DS4_LAYOUT`.
+pub const DS5_LAYOUT: &[(Buttons, usize, u8)] = &[
+    (Buttons::X, 0, 0x10),
+    (Buttons::A, 0, 0x20),
+    (Buttons::B, 0, 0x40),
+    (Buttons::Y, 0, 0x80),
+    (Buttons::LEFT_SHOULDER, 1, 0x01),
+    (Buttons::RIGHT_SHOULDER, 1, 0x02),
+    (Buttons::BACK, 1, 0x10),
+    (Buttons::START, 1, 0x20),
+    (Buttons::LEFT_THUMB, 1, 0x40),
+    (Buttons::RIGHT_THUMB, 1, 0x80),
+    (Buttons::GUIDE, 2, 0x01),
+    (Buttons::TOUCHPAD, 2, 0x02),
+    (Buttons::MUTE, 2, 0x04),
+];
+
+/// DualSense Edge buttons on top of `DS5_LAYOUT`: the Fn buttons and the two
+/// back buttons, which take the upper paddle numbers.
+pub const DS5_EDGE_EXTRAS: &[(Buttons, usize, u8)] = &[
+    (Buttons::FN_LEFT, 2, 0x10),
+    (Buttons::FN_RIGHT, 2, 0x20),
+    (Buttons::PADDLE3, 2, 0x40),
+    (Buttons::PADDLE1, 2, 0x80),
+];
+
+/// Xbox One button block (input report bytes 13..17), same conventions as `DS4_LAYOUT`
+/// except that byte 0 holds the hat alone, counted from 1 (see `to_xbox_one`).
+pub const XBOX_ONE_LAYOUT: &[(Buttons, usize, u8)] = &[
+    (Buttons::A, 1, 0x01),
+    (Buttons::B, 1, 0x02),
+    (Buttons::X, 1, 0x08),
+    (Buttons::Y, 1, 0x10),
+    (Buttons::LEFT_SHOULDER, 1, 0x40),
+    (Buttons::RIGHT_SHOULDER, 1, 0x80),
+    (Buttons::BACK, 2, 0x04),
+    (Buttons::START, 2, 0x08),
+    (Buttons::GUIDE, 2, 0x10),
+    (Buttons::LEFT_THUMB, 2, 0x20),
+    (Buttons::RIGHT_THUMB, 2, 0x40),
+    (Buttons::SHARE, 3, 0x01),
+];
+
+/// Xbox Elite Series 2 buttons on top of `XBOX_ONE_LAYOUT`: the four paddles after share.
+pub const XBOX_ELITE_EXTRAS: &[(Buttons, usize, u8)] = &[
+    (Buttons::PADDLE1, 3, 0x02),
+    (Buttons::PADDLE2, 3, 0x04),
+    (Buttons::PADDLE3, 3, 0x08),
+    (Buttons::PADDLE4, 3, 0x10),
+];
+
+/// Switch Pro button bytes (input report bytes 3..6): right, shared, left.
+/// Face buttons go by position, so Nintendo B is `A` and Nintendo A is `B`.
+/// The d-pad is four plain bits, and ZR/ZL (byte 0/2, 0x80) belong to the packer.
+pub const SWITCH_PRO_LAYOUT: &[(Buttons, usize, u8)] = &[
+    (Buttons::X, 0, 0x01),
+    (Buttons::Y, 0, 0x02),
+    (Buttons::A, 0, 0x04),
+    (Buttons::B, 0, 0x08),
+    (Buttons::RIGHT_SHOULDER, 0, 0x40),
+    (Buttons::BACK, 1, 0x01),
+    (Buttons::START, 1, 0x02),
+    (Buttons::RIGHT_THUMB, 1, 0x04),
+    (Buttons::LEFT_THUMB, 1, 0x08),
+    (Buttons::GUIDE, 1, 0x10),
+    (Buttons::SHARE, 1, 0x20),
+    (Buttons::DPAD_DOWN, 2, 0x01),
+    (Buttons::DPAD_UP, 2, 0x02),
+    (Buttons::DPAD_RIGHT, 2, 0x04),
+    (Buttons::DPAD_LEFT, 2, 0x08),
+    (Buttons::LEFT_SHOULDER, 2, 0x40),
+];
+
+/// Racing wheel button block (input report bytes 9..11), same conventions as
+/// `DS4_LAYOUT`. The shoulder buttons are the paddle shifters, so Button 1 is upshift.
+pub const WHEEL_LAYOUT: &[(Buttons, usize, u8)] = &[
+    (Buttons::RIGHT_SHOULDER, 0, 0x10),
+    (Buttons::LEFT_SHOULDER, 0, 0x20),
+    (Buttons::A, 0, 0x40),
+    (Buttons::B, 0, 0x80),
+    (Buttons::X, 1, 0x01),
+    (Buttons::Y, 1, 0x02),
+    (Buttons::BACK, 1, 0x04),
+    (Buttons::START, 1, 0x08),
+    (Buttons::LEFT_THUMB, 1, 0x10),
+    (Buttons::RIGHT_THUMB, 1, 0x20),
+    (Buttons::GUIDE, 1, 0x40),
+];
+
+/// Generic pad button numbering: entry `i` is HID Button `i + 1`. The d-pad
+/// comes after the face, shoulder and paddle buttons so a pad with a hat can
+/// stop before it; the Fn buttons were added later and follow it.
+pub const GENERIC_ORDER: &[Buttons] = &[
+    Buttons::A,
+    Buttons::B,
+    Buttons::X,
+    Buttons::Y,
+    Buttons::LEFT_SHOULDER,
+    Buttons::RIGHT_SHOULDER,
+    Buttons::BACK,
+    Buttons::START,
+    Buttons::LEFT_THUMB,
+    Buttons::RIGHT_THUMB,
+    Buttons::GUIDE,
+    Buttons::TOUCHPAD,
+    Buttons::MUTE,
+    Buttons::SHARE,
+    Buttons::PADDLE1,
+    Buttons::PADDLE2,
+    Buttons::PADDLE3,
+    Buttons::PADDLE4,
+    Buttons::DPAD_UP,
+    Buttons::DPAD_DOWN,
+    Buttons::DPAD_LEFT,
+    Buttons::DPAD_RIGHT,
+    Buttons::FN_LEFT,
+    Buttons::FN_RIGHT,
+];
+
+/// Hat switch value when no direction is held.
+pub const HAT_NEUTRAL: u8 = 8;
+
+impl Buttons {
+    /// Buttons that XUSB can carry; the rest are dropped.
+    pub fn to_xusb(self) -> u16 {
+        XUSB_LAYOUT.iter().filter(|(b, _)| self.contains(*b)).fold(0, |w, (_, m)| w | m)
+    }
+
+    pub fn from_xusb(w: u16) -> Self {
+        XUSB_LAYOUT.iter().filter(|(_, m)| w & m != 0).fold(Self::empty(), |s, (b, _)| s | *b)
+    }
+
+    pub fn to_ds4(self) -> [u8; 3] {
+        self.to_block(DS4_LAYOUT)
+    }
+
+    pub fn from_ds4(block: [u8; 3]) -> Self {
+        Self::from_block(&block, DS4_LAYOUT)
+    }
+
+    pub fn to_ds5(self) -> [u8; 4] {
+        self.to_block(DS5_LAYOUT)
+    }
+
+    pub fn from_ds5(block: [u8; 4]) -> Self {
+        Self::from_block(&block, DS5_LAYOUT)
+    }
+
+    pub fn to_ds5_edge(self) -> [u8; 4] {
+        self.to_block(DS5_LAYOUT.iter().chain(DS5_EDGE_EXTRAS))
+    }
+
+    pub fn from_ds5_edge(block: [u8; 4]) -> Self {
+        Self::from_block(&block, DS5_LAYOUT.iter().chain(DS5_EDGE_EXTRAS))
+    }
+
+    /// The hat is 1 = N .. 8 = NW with 0 as neutral, unlike the DS4/DS5 0-based hat.
+    pub fn to_xbox_one(self) -> [u8; 4] {
+        self.to_xbox_block(XBOX_ONE_LAYOUT)
+    }
+
+    pub fn from_xbox_one(block: [u8; 4]) -> Self {
+        Self::from_xbox_block(&block, XBOX_ONE_LAYOUT)
+    }
+
+    pub fn to_xbox_elite(self) -> [u8; 4] {
+        self.to_xbox_block(XBOX_ONE_LAYOUT.iter().chain(XBOX_ELITE_EXTRAS))
+    }
+
+    pub fn from_xbox_elite(block: [u8; 4]) -> Self {
+        Self::from_xbox_block(&block, XBOX_ONE_LAYOUT.iter().chain(XBOX_ELITE_EXTRAS))
+    }
+
+    pub fn to_switch_pro(self) -> [u8; 3] {
+        self.to_layout(SWITCH_PRO_LAYOUT)
+    }
+
+    pub fn from_switch_pro(block: [u8; 3]) -> Self {
+        Self::from_layout(&block, SWITCH_PRO_LAYOUT)
+    }
+
+    pub fn to_wheel(self) -> [u8; 2] {
+        self.to_block(WHEEL_LAYOUT)
+    }
+
+    pub fn from_wheel(block: [u8; 2]) -> Self {
+        Self::from_block(&block, WHEEL_LAYOUT)
+    }
+
+    /// The first `count` buttons of `GENERIC_ORDER`, bit `i` for entry `i`.
+    pub fn to_generic(self, count: u8) -> u32 {
+        GENERIC_ORDER
+            .iter()
+            .take(count as usize)
+            .enumerate()
+            .filter(|(_, b)| self.contains(**b))
+            .fold(0, |w, (i, _)| w | 1 << i)
+    }
+
+    pub fn from_generic(bits: u32, count: u8) -> Self {
+        GENERIC_ORDER
+            .iter()
+            .take(count as usize)
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .fold(Self::empty(), |s, (_, b)| s | *b)
+    }
+
+    /// D-pad as a HID hat switch (0 = N, clockwise, 8 = neutral).
+    /// Opposing directions cancel out.
+    pub fn hat(self) -> u8 {
+        let v = self.contains(Self::DPAD_UP) as i8 - self.contains(Self::DPAD_DOWN) as i8;
+        let h = self.contains(Self::DPAD_RIGHT) as i8 - self.contains(Self::DPAD_LEFT) as i8;
+        match (v, h) {
+            (1, 0) => 0,
+            (1, 1) => 1,
+            (0, 1) => 2,
+            (-1, 1) => 3,
+            (-1, 0) => 4,
+            (-1, -1) => 5,
+            (0, -1) => 6,
+            (1, -1) => 7,
+            _ => HAT_NEUTRAL,
+        }
+    }
+
+    /// Inverse of `hat`; out-of-range values read as neutral.
+    pub fn from_hat(hat: u8) -> Self {
+        match hat {
+            0 => Self::DPAD_UP,
+            1 => Self::DPAD_UP | Self::DPAD_RIGHT,
+            2 => Self::DPAD_RIGHT,
+            3 => Self::DPAD_DOWN | Self::DPAD_RIGHT,
+            4 => Self::DPAD_DOWN,
+            5 => Self::DPAD_DOWN | Self::DPAD_LEFT,
+            6 => Self::DPAD_LEFT,
+            7 => Self::DPAD_UP | Self::DPAD_LEFT,
+            _ => Self::empty(),
+        }
+    }
+
+    /// `layout` bits plus the hat in the low nibble of byte 0.
+    fn to_block<'a, const N: usize>(
+        self,
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> [u8; N] {
+        let mut b = self.to_layout(layout);
+        b[0] |= self.hat();
+        b
+    }
+
+    fn from_block<'a>(
+        block: &[u8],
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> Self {
+        Self::from_layout(block, layout) | Self::from_hat(block[0] & 0x0F)
+    }
+
+    /// `layout` bits plus the 1-based Xbox hat alone in byte 0.
+    fn to_xbox_block<'a>(
+        self,
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> [u8; 4] {
+        let mut b: [u8; 4] = self.to_layout(layout);
+        b[0] = (self.hat() + 1) % 9;
+        b
+    }
+
+    fn from_xbox_block<'a>(
+        block: &[u8],
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> Self {
+        let hat = (block[0] & 0x0F).wrapping_sub(1);
+        Self::from_layout(block, layout) | Self::from_hat(hat)
+    }
+
+    fn to_layout<'a, const N: usize>(
+        self,
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> [u8; N] {
+        let mut b = [0u8; N];
+        for (btn, i, m) in layout {
+            if self.contains(*btn) {
+                b[*i] |= m;
+            }
+        }
+        b
+    }
+
+    fn from_layout<'a>(
+        block: &[u8],
+        layout: impl IntoIterator<Item = &'a (Buttons, usize, u8)>,
+    ) -> Self {
+        layout
+            .into_iter()
+            .filter(|(_, i, m)| block[*i] & m != 0)
+            .fold(Self::empty(), |s, (b, _, _)| s | *b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every subset of the layout's buttons, with every d-pad state.
+    fn combos(layout: &[(Buttons, usize, u8)]) -> Vec<Buttons> {
+        let mut out = Vec::new();
+        for mask in 0u32..(1 << layout.len()) {
+            let b = (0..layout.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .fold(Buttons::empty(), |a, i| a | layout[i].0);
+            out.extend((0..=HAT_NEUTRAL).map(|hat| b | Buttons::from_hat(hat)));
+        }
+        out
+    }
+
+    #[test]
+    fn xusb_is_identity_on_low_bits() {
+        for (b, m) in XUSB_LAYOUT {
+            assert_eq!(b.bits(), *m as u32);
+        }
+        let all = Buttons::all();
+        assert_eq!(Buttons::from_xusb(all.to_xusb()), all & Buttons::from_bits_truncate(0xFFFF));
+    }
+
+    #[test]
+    fn ds4_round_trip() {
+        for b in combos(DS4_LAYOUT) {
+            assert_eq!(Buttons::from_ds4(b.to_ds4()), b);
+        }
+        assert_eq!(Buttons::empty().to_ds4(), [0x08, 0x00, 0x00]);
+        assert_eq!((Buttons::A | Buttons::DPAD_RIGHT).to_ds4(), [0x22, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn ds5_round_trip() {
+        for b in combos(DS5_LAYOUT) {
+            assert_eq!(Buttons::from_ds5(b.to_ds5()), b);
+        }
+        assert_eq!((Buttons::MUTE | Buttons::GUIDE).to_ds5(), [0x08, 0x00, 0x05, 0x00]);
+    }
+
+    #[test]
+    fn ds5_edge_round_trip() {
+        for b in combos(&[DS5_LAYOUT, DS5_EDGE_EXTRAS].concat()) {
+            assert_eq!(Buttons::from_ds5_edge(b.to_ds5_edge()), b);
+        }
+        let extras = Buttons::FN_LEFT | Buttons::FN_RIGHT | Buttons::PADDLE1 | Buttons::PADDLE3;
+        assert_eq!(extras.to_ds5_edge(), [0x08, 0x00, 0xF0, 0x00]);
+        assert_eq!(extras.to_ds5(), [0x08, 0x00, 0x00, 0x00]);
+        assert_eq!((Buttons::PADDLE2 | Buttons::PADDLE4).to_ds5_edge(), [0x08, 0, 0, 0]);
+    }
+
+    #[test]
+    fn xbox_one_round_trip() {
+        for b in combos(XBOX_ONE_LAYOUT) {
+            assert_eq!(Buttons::from_xbox_one(b.to_xbox_one()), b);
+        }
+        assert_eq!(Buttons::empty().to_xbox_one(), [0, 0, 0, 0]);
+        assert_eq!((Buttons::A | Buttons::DPAD_UP).to_xbox_one(), [1, 0x01, 0, 0]);
+        assert_eq!((Buttons::SHARE | Buttons::GUIDE).to_xbox_one(), [0, 0, 0x10, 0x01]);
+    }
+
+    #[test]
+    fn xbox_elite_round_trip() {
+        for b in combos(&[XBOX_ONE_LAYOUT, XBOX_ELITE_EXTRAS].concat()) {
+            assert_eq!(Buttons::from_xbox_elite(b.to_xbox_elite()), b);
+        }
+        let paddles = Buttons::PADDLE1 | Buttons::PADDLE2 | Buttons::PADDLE3 | Buttons::PADDLE4;
+        assert_eq!((paddles | Buttons::SHARE).to_xbox_elite(), [0, 0, 0, 0x1F]);
+        assert_eq!(paddles.to_xbox_one(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn wheel_round_trip() {
+        for b in combos(WHEEL_LAYOUT) {
+            assert_eq!(Buttons::from_wheel(b.to_wheel()), b);
+        }
+        assert_eq!(Buttons::empty().to_wheel(), [HAT_NEUTRAL, 0]);
+        assert_eq!((Buttons::RIGHT_SHOULDER | Buttons::GUIDE).to_wheel(), [0x18, 0x40]);
+    }
+
+    #[test]
+    fn switch_pro_round_trip() {
+        // the d-pad is in the layout itself, so every combination survives
+        for mask in 0u32..(1 << SWITCH_PRO_LAYOUT.len()) {
+            let b = (0..SWITCH_PRO_LAYOUT.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .fold(Buttons::empty(), |a, i| a | SWITCH_PRO_LAYOUT[i].0);
+            assert_eq!(Buttons::from_switch_pro(b.to_switch_pro()), b);
+        }
+        assert_eq!((Buttons::A | Buttons::DPAD_UP).to_switch_pro(), [0x04, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn hat_round_trip_and_conflicts() {
+        for hat in 0..=8 {
+            assert_eq!(Buttons::from_hat(hat).hat(), hat);
+        }
+        assert_eq!((Buttons::DPAD_LEFT | Buttons::DPAD_RIGHT).hat(), HAT_NEUTRAL);
+        assert_eq!(Buttons::DPAD.hat(), HAT_NEUTRAL);
+    }
+
+    #[test]
+    fn generic_order() {
+        let all = GENERIC_ORDER.iter().fold(Buttons::empty(), |a, b| a | *b);
+        assert_eq!(all.bits().count_ones() as usize, GENERIC_ORDER.len());
+        assert_eq!(Buttons::all().difference(all), Buttons::empty());
+        let n = GENERIC_ORDER.len() as u8;
+        assert_eq!(Buttons::all().to_generic(n), (1 << n) - 1);
+        assert_eq!(Buttons::from_generic(u32::MAX, n), Buttons::all());
+        assert_eq!((Buttons::A | Buttons::PADDLE1).to_generic(4), 0x1);
+        assert_eq!(Buttons::from_generic(0b101, 2), Buttons::A);
+    }
+
+    #[test]
+    fn layouts_have_no_collisions() {
+        for layout in [
+            DS4_LAYOUT,
+            &[DS5_LAYOUT, DS5_EDGE_EXTRAS].concat(),
+            &[XBOX_ONE_LAYOUT, XBOX_ELITE_EXTRAS].concat(),
+            SWITCH_PRO_LAYOUT,
+            WHEEL_LAYOUT,
+        ] {
+            for (i, a) in layout.iter().enumerate() {
+                for b in &layout[i + 1..] {
+                    assert!(a.0 != b.0 && (a.1, a.2) != (b.1, b.2), "{a:?} / {b:?}");
+                }
+            }
+        }
+    }
+}
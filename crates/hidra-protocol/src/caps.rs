@@ -0,0 +1,202 @@
+//! What each `DeviceKind` can do, and validation of create requests against it.
+
+use crate::generic::GenericField;
+use crate::process::PipelineAxis;
+use crate::{DeviceConfig, DeviceKind, Features, Transport, XusbSubtype};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Capabilities {
+    /// Every feature the kind can emulate.
+    pub features: Features,
+    /// Features a device gets when the client doesn't ask for specific ones.
+    pub defaults: Features,
+    pub bluetooth: bool,
+    /// XInput subtypes the kind can present as; `Gamepad` alone for all but X360.
+    pub subtypes: &'static [XusbSubtype],
+}
+
+const GAMEPAD_ONLY: &[XusbSubtype] = &[XusbSubtype::Gamepad];
+
+/// Everything a DualShock/DualSense does; the rest of `Features` is keyboard-only.
+const PAD_FEATURES: Features =
+    Features::RUMBLE.union(Features::TOUCH).union(Features::GYRO).union(Features::LED);
+
+impl DeviceKind {
+    pub const fn capabilities(self) -> Capabilities {
+        match self {
+            DeviceKind::X360 => Capabilities {
+                features: Features::RUMBLE.union(Features::LED),
+                defaults: Features::RUMBLE,
+                bluetooth: false,
+                subtypes: &XusbSubtype::ALL,
+            },
+            DeviceKind::XboxOne | DeviceKind::XboxElite => Capabilities {
+                features: Features::RUMBLE,
+                defaults: Features::RUMBLE,
+                bluetooth: false,
+                subtypes: GAMEPAD_ONLY,
+            },
+            DeviceKind::SwitchPro => Capabilities {
+                features: Features::RUMBLE.union(Features::GYRO).union(Features::LED),
+                defaults: Features::RUMBLE.union(Features::GYRO),
+                bluetooth: false,
+                subtypes: GAMEPAD_ONLY,
+            },
+            DeviceKind::Generic | DeviceKind::Wheel => Capabilities {
+                features: Features::empty(),
+                defaults: Features::empty(),
+                bluetooth: false,
+                subtypes: GAMEPAD_ONLY,
+            },
+            DeviceKind::DS4 | DeviceKind::DS5 | DeviceKind::DS5Edge => Capabilities {
+                features: PAD_FEATURES,
+                defaults: PAD_FEATURES,
+                bluetooth: true,
+                subtypes: GAMEPAD_ONLY,
+            },
+            DeviceKind::Keyboard => Capabilities {
+                features: Features::LED.union(Features::NKRO),
+                defaults: Features::LED,
+                bluetooth: false,
+                subtypes: GAMEPAD_ONLY,
+            },
+            DeviceKind::Mouse => Capabilities {
+                features: Features::empty(),
+                defaults: Features::empty(),
+                bluetooth: false,
+                subtypes: GAMEPAD_ONLY,
+            },
+        }
+    }
+}
+
+/// Why a create request was refused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "error", rename_all = "snake_case"))]
+pub enum CreateError {
+    #[error("unknown feature bits {bits:#x}")]
+    UnknownFeatures { bits: u32 },
+    #[error("{kind:?} does not support features {bits:#x}")]
+    UnsupportedFeatures { kind: DeviceKind, bits: u32 },
+    #[error("{kind:?} has no {transport:?} variant")]
+    UnsupportedTransport { kind: DeviceKind, transport: Transport },
+    #[error("{kind:?} has no {subtype:?} subtype")]
+    UnsupportedSubtype { kind: DeviceKind, subtype: XusbSubtype },
+    #[error("generic pad {field:?} {value} is out of range")]
+    InvalidGenericSpec { field: GenericField, value: u8 },
+    #[error("{axis:?} pipeline stage {stage} has out-of-range parameters")]
+    InvalidPipeline { axis: PipelineAxis, stage: u32 },
+}
+
+impl Capabilities {
+    /// Check raw `features` bits and `transport` for a device of `kind`.
+    pub fn validate(
+        &self,
+        kind: DeviceKind,
+        features: u32,
+        transport: Transport,
+    ) -> Result<Features, CreateError> {
+        let unknown = features & !Features::all().bits();
+        if unknown != 0 {
+            return Err(CreateError::UnknownFeatures { bits: unknown });
+        }
+        let unsupported = features & !self.features.bits();
+        if unsupported != 0 {
+            return Err(CreateError::UnsupportedFeatures { kind, bits: unsupported });
+        }
+        if transport == Transport::Bluetooth && !self.bluetooth {
+            return Err(CreateError::UnsupportedTransport { kind, transport });
+        }
+        Ok(Features::from_bits_retain(features))
+    }
+
+    pub fn validate_subtype(
+        &self,
+        kind: DeviceKind,
+        subtype: XusbSubtype,
+    ) -> Result<(), CreateError> {
+        if !self.subtypes.contains(&subtype) {
+            return Err(CreateError::UnsupportedSubtype { kind, subtype });
+        }
+        Ok(())
+    }
+}
+
+impl DeviceConfig {
+    /// The kind's default features, default ranges, USB.
+    pub fn for_kind(kind: DeviceKind) -> Self {
+        Self { features: kind.capabilities().defaults, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_supported() {
+        for kind in DeviceKind::ALL {
+            let caps = kind.capabilities();
+            assert!(caps.features.contains(caps.defaults), "{kind:?}");
+            let cfg = DeviceConfig::for_kind(kind);
+            assert_eq!(caps.validate(kind, cfg.features.bits(), cfg.transport), Ok(cfg.features));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_unsupported() {
+        let x360 = DeviceKind::X360.capabilities();
+        assert_eq!(
+            x360.validate(DeviceKind::X360, 0x100 | Features::RUMBLE.bits(), Transport::Usb),
+            Err(CreateError::UnknownFeatures { bits: 0x100 })
+        );
+        assert_eq!(
+            x360.validate(DeviceKind::X360, Features::all().bits(), Transport::Usb),
+            Err(CreateError::UnsupportedFeatures {
+                kind: DeviceKind::X360,
+                bits: (Features::TOUCH | Features::GYRO | Features::NKRO).bits()
+            })
+        );
+        assert_eq!(
+            x360.validate(DeviceKind::X360, 0, Transport::Bluetooth),
+            Err(CreateError::UnsupportedTransport {
+                kind: DeviceKind::X360,
+                transport: Transport::Bluetooth
+            })
+        );
+        let ds5 = DeviceKind::DS5.capabilities();
+        assert_eq!(
+            ds5.validate(DeviceKind::DS5, Features::GYRO.bits(), Transport::Bluetooth),
+            Ok(Features::GYRO)
+        );
+        assert_eq!(
+            ds5.validate(DeviceKind::DS5, Features::NKRO.bits(), Transport::Usb),
+            Err(CreateError::UnsupportedFeatures { kind: DeviceKind::DS5, bits: 0x10 })
+        );
+        let kbd = DeviceKind::Keyboard.capabilities();
+        assert_eq!(
+            kbd.validate(DeviceKind::Keyboard, Features::NKRO.bits(), Transport::Usb),
+            Ok(Features::NKRO)
+        );
+    }
+
+    #[test]
+    fn subtypes() {
+        let x360 = DeviceKind::X360.capabilities();
+        for subtype in XusbSubtype::ALL {
+            assert_eq!(x360.validate_subtype(DeviceKind::X360, subtype), Ok(()));
+        }
+        let ds4 = DeviceKind::DS4.capabilities();
+        assert_eq!(ds4.validate_subtype(DeviceKind::DS4, XusbSubtype::Gamepad), Ok(()));
+        assert_eq!(
+            ds4.validate_subtype(DeviceKind::DS4, XusbSubtype::Guitar),
+            Err(CreateError::UnsupportedSubtype {
+                kind: DeviceKind::DS4,
+                subtype: XusbSubtype::Guitar
+            })
+        );
+    }
+}
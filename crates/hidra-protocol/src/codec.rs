@@ -0,0 +1,370 @@
+//! Little-endian encoding of the IOCTL structs to and from byte slices.
+//! Each layout is checked at compile time to be padding-free and to match the
+//! `#[repr(C)]` offsets, so the bytes are exactly what `HIDraAbi.h` describes.
+
+use crate::error::{Error, Result};
+use crate::*;
+use core::mem::offset_of;
+
+/// A struct that crosses the IOCTL boundary.
+pub trait Wire: Sized {
+    /// Encoded size; always `size_of::<Self>()`.
+    const LEN: usize;
+
+    /// Write `self` to the start of `out`, returning the number of bytes written.
+    fn encode(&self, out: &mut [u8]) -> Result<usize>;
+
+    /// Read from the start of `b`. Trailing bytes are ignored, so a longer
+    /// struct from a newer peer still decodes.
+    fn decode(b: &[u8]) -> Result<Self>;
+}
+
+/// One field. `put`/`take` get slices of at least `LEN` bytes.
+trait Field: Sized {
+    const LEN: usize;
+    fn put(&self, b: &mut [u8]);
+    fn take(b: &[u8]) -> Result<Self>;
+}
+
+macro_rules! le_field {
+    ($($t:ty),*) => {$(
+        impl Field for $t {
+            const LEN: usize = size_of::<$t>();
+            fn put(&self, b: &mut [u8]) {
+                b[..Self::LEN].copy_from_slice(&self.to_le_bytes());
+            }
+            fn take(b: &[u8]) -> Result<Self> {
+                let mut a = [0u8; size_of::<$t>()];
+                a.copy_from_slice(&b[..Self::LEN]);
+                Ok(<$t>::from_le_bytes(a))
+            }
+        }
+    )*};
+}
+
+le_field!(u8, u16, i16, u32, u64);
+
+impl<T: Field + Copy + Default, const N: usize> Field for [T; N] {
+    const LEN: usize = T::LEN * N;
+    fn put(&self, b: &mut [u8]) {
+        for (v, chunk) in self.iter().zip(b.chunks_exact_mut(T::LEN)) {
+            v.put(chunk);
+        }
+    }
+    fn take(b: &[u8]) -> Result<Self> {
+        let mut a = [T::default(); N];
+        for (v, chunk) in a.iter_mut().zip(b.chunks_exact(T::LEN)) {
+            *v = T::take(chunk)?;
+        }
+        Ok(a)
+    }
+}
+
+/// A `#[repr(uN)]` enum, decoded through `$from` and rejected as `Error::Value` if unknown.
+macro_rules! enum_field {
+    ($($t:ty: $repr:ty, $field:literal, $from:expr;)*) => {$(
+        impl Field for $t {
+            const LEN: usize = size_of::<$repr>();
+            fn put(&self, b: &mut [u8]) {
+                (*self as $repr).put(b);
+            }
+            fn take(b: &[u8]) -> Result<Self> {
+                let v = <$repr>::take(b)?;
+                $from(v).ok_or(Error::Value { field: $field, value: v as u32 })
+            }
+        }
+    )*};
+}
+
+enum_field! {
+    DeviceKind: u16, "device kind", DeviceKind::from_id;
+    Transport: u8, "transport", |v| match v {
+        0 => Some(Transport::Usb),
+        1 => Some(Transport::Bluetooth),
+        _ => None,
+    };
+    TriggerRange: u8, "trigger range", |v| TriggerRange::try_from(v).ok();
+    XusbSubtype: u8, "xusb subtype", XusbSubtype::from_id;
+    StickRange: u8, "stick range", |v| match v {
+        0 => Some(StickRange::Full),
+        1 => Some(StickRange::Half),
+        _ => None,
+    };
+}
+
+/// A bitflags set, carried as its raw bits; unknown bits are kept.
+macro_rules! flags_field {
+    ($($t:ty: $repr:ty,)*) => {$(
+        impl Field for $t {
+            const LEN: usize = size_of::<$repr>();
+            fn put(&self, b: &mut [u8]) {
+                self.bits().put(b);
+            }
+            fn take(b: &[u8]) -> Result<Self> {
+                Ok(<$t>::from_bits_retain(<$repr>::take(b)?))
+            }
+        }
+    )*};
+}
+
+flags_field! {
+    Buttons: u32,
+    Modifiers: u8,
+    MouseButtons: u8,
+}
+
+/// Fields in declaration order. The const block fails the build if a field is
+/// listed out of order or the struct has padding anywhere.
+macro_rules! wire_struct {
+    ($($t:ident { $($f:ident: $ty:ty,)* })*) => {$(
+        const _: () = {
+            let mut at = 0;
+            $(
+                assert!(offset_of!($t, $f) == at, concat!(stringify!($t), ".", stringify!($f)));
+                at += <$ty as Field>::LEN;
+            )*
+            assert!(at == size_of::<$t>(), concat!(stringify!($t), " has tail padding"));
+        };
+
+        impl Field for $t {
+            const LEN: usize = size_of::<$t>();
+            fn put(&self, b: &mut [u8]) {
+                let mut at = 0;
+                $(
+                    self.$f.put(&mut b[at..]);
+                    at += <$ty as Field>::LEN;
+                )*
+            }
+            fn take(b: &[u8]) -> Result<Self> {
+                let mut at = 0;
+                Ok(Self {$(
+                    $f: {
+                        let v = <$ty as Field>::take(&b[at..])?;
+                        at += <$ty as Field>::LEN;
+                        v
+                    },
+                )*})
+            }
+        }
+
+        impl Wire for $t {
+            const LEN: usize = size_of::<$t>();
+            fn encode(&self, out: &mut [u8]) -> Result<usize> {
+                check_len(<Self as Wire>::LEN, out.len())?;
+                Field::put(self, out);
+                Ok(<Self as Wire>::LEN)
+            }
+            fn decode(b: &[u8]) -> Result<Self> {
+                check_len(<Self as Wire>::LEN, b.len())?;
+                <Self as Field>::take(b)
+            }
+        }
+    )*};
+}
+
+fn check_len(expected: usize, got: usize) -> Result<()> {
+    if got < expected {
+        return Err(Error::Length { expected, got });
+    }
+    Ok(())
+}
+
+wire_struct! {
+    StateHeader { size: u16, version: u16, }
+    TouchPoint { down: u8, id: u8, x: u16, y: u16, }
+    Motion { gyro: [i16; 3], accel: [i16; 3], }
+    Battery { level: u8, charge: u8, }
+    PadState {
+        header: StateHeader,
+        buttons: Buttons,
+        lx: i16,
+        ly: i16,
+        rx: i16,
+        ry: i16,
+        lt: u16,
+        rt: u16,
+        touch: [TouchPoint; 2],
+        motion: Motion,
+        battery: Battery,
+        reserved: [u8; 2],
+    }
+    PadStateV2 {
+        header: StateHeader,
+        buttons: u16,
+        ext_buttons: u16,
+        lx: i16,
+        ly: i16,
+        rx: i16,
+        ry: i16,
+        lt: u16,
+        rt: u16,
+        touch: [TouchPoint; 2],
+        motion: Motion,
+        battery: Battery,
+        reserved: [u8; 2],
+    }
+    PadStateV1 { buttons: u16, lx: i16, ly: i16, rx: i16, ry: i16, lt: u16, rt: u16, }
+    InputRanges { trigger: TriggerRange, stick: StickRange, }
+    VersionInfo { header: StateHeader, abi_version: u32, kinds: u32, features: u32, }
+    CreateIn {
+        kind: DeviceKind,
+        reserved0: [u8; 2],
+        features: u32,
+        ranges: InputRanges,
+        transport: Transport,
+        subtype: XusbSubtype,
+    }
+    GenericSpec {
+        vendor_id: u16,
+        product_id: u16,
+        axes: u8,
+        axis_bits: u8,
+        buttons: u8,
+        hats: u8,
+        product: [u16; generic::GENERIC_PRODUCT_LEN],
+    }
+    CreateGenericIn { create: CreateIn, spec: GenericSpec, }
+    DeviceIdentity {
+        header: StateHeader,
+        vendor_id: u16,
+        product_id: u16,
+        version: u16,
+        mac: [u8; 6],
+        manufacturer: [u16; identity::IDENTITY_STRING_LEN],
+        product: [u16; identity::IDENTITY_STRING_LEN],
+        serial: [u16; identity::IDENTITY_STRING_LEN],
+    }
+    CreateOut { handle: u64, }
+    DestroyIn { handle: u64, }
+    UpdateIn { handle: u64, state: PadState, }
+    UpdateInV1 { handle: u64, state: PadStateV1, reserved: [u8; 2], }
+    KeyboardState { header: StateHeader, modifiers: Modifiers, reserved: [u8; 3], keys: [u8; 32], }
+    MouseState {
+        header: StateHeader,
+        buttons: MouseButtons,
+        reserved: [u8; 3],
+        dx: i16,
+        dy: i16,
+        wheel: i16,
+        pan: i16,
+    }
+    UpdateKeyboardIn { handle: u64, state: KeyboardState, }
+    UpdateMouseIn { handle: u64, state: MouseState, }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Wire + Eq + core::fmt::Debug>(v: T) -> Vec<u8> {
+        let mut b = vec![0u8; T::LEN];
+        assert_eq!(v.encode(&mut b), Ok(T::LEN));
+        assert_eq!(T::decode(&b), Ok(v));
+        b
+    }
+
+    #[test]
+    fn create_in_bytes() {
+        let cfg = DeviceConfig {
+            features: Features::RUMBLE | Features::GYRO,
+            ranges: InputRanges { trigger: TriggerRange::Bits10, stick: StickRange::Half },
+            transport: Transport::Bluetooth,
+            ..Default::default()
+        };
+        let b = round_trip(CreateIn::new(DeviceKind::DS4, &cfg));
+        assert_eq!(b, [0xC4, 0x05, 0, 0, 5, 0, 0, 0, 10, 1, 1, 1]);
+        let mut old = b.clone();
+        old[11] = 0;
+        assert_eq!(CreateIn::decode(&old).map(|c| c.subtype), Ok(XusbSubtype::Gamepad));
+        old[11] = 2;
+        assert_eq!(CreateIn::decode(&old), Err(Error::Value { field: "xusb subtype", value: 2 }));
+        let guitar = DeviceConfig { subtype: XusbSubtype::Guitar, ..Default::default() };
+        assert_eq!(round_trip(CreateIn::new(DeviceKind::X360, &guitar))[11], 0x06);
+
+        let generic = GenericSpec { vendor_id: 0x1234, axes: 2, ..Default::default() };
+        let cfg = DeviceConfig { generic: generic.with_product("Hi"), ..Default::default() };
+        let b = round_trip(CreateGenericIn::new(&cfg));
+        assert_eq!(b.len(), 84);
+        assert_eq!(b[..2], [0x44, 0x48]);
+        assert_eq!(b[12..20], [0x34, 0x12, 0, 0, 2, 16, 16, 1]);
+        assert_eq!(b[20..26], [b'H', 0, b'i', 0, 0, 0]);
+    }
+
+    #[test]
+    fn update_in_bytes() {
+        let state = PadState { buttons: Buttons::A, lx: -2, rt: 0x1234, ..Default::default() };
+        let b = round_trip(UpdateIn { handle: 0x0102_0304_0506_0708, state });
+        assert_eq!(b.len(), 56);
+        assert_eq!(b[..8], [8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(b[8..12], [48, 0, 3, 0]);
+        assert_eq!(b[12..16], Buttons::A.bits().to_le_bytes());
+        assert_eq!(b[16..18], [0xFE, 0xFF]);
+        assert_eq!(b[26..28], [0x34, 0x12]);
+        assert_eq!(b[52..54], [100, 2]);
+
+        let v2 = PadStateV2 {
+            buttons: 0x1000,
+            ext_buttons: PadStateV2::EXT_MUTE,
+            lx: -2,
+            ..Default::default()
+        };
+        let b = round_trip(v2);
+        assert_eq!(b[..10], [48, 0, 2, 0, 0x00, 0x10, 0x02, 0x00, 0xFE, 0xFF]);
+        let s = PadState::from(v2);
+        assert_eq!((s.buttons, s.lx), (Buttons::A | Buttons::MUTE, -2));
+
+        let v1 = PadStateV1 { buttons: 0x1000, lx: 1, ..Default::default() };
+        let b = round_trip(UpdateInV1 { handle: 9, state: v1, reserved: [0; 2] });
+        assert_eq!(b[8..12], [0x00, 0x10, 1, 0]);
+    }
+
+    #[test]
+    fn keyboard_and_mouse_bytes() {
+        let state = KeyboardState::with_keys(&[0x04, 0xE2]);
+        let b = round_trip(UpdateKeyboardIn { handle: 3, state });
+        assert_eq!(b.len(), 48);
+        assert_eq!(b[8..14], [40, 0, 1, 0, 0x04, 0]);
+        assert_eq!(b[16], 0x10);
+
+        let state =
+            MouseState { buttons: MouseButtons::RIGHT, dx: -1, pan: 2, ..Default::default() };
+        let b = round_trip(UpdateMouseIn { handle: 4, state });
+        assert_eq!(b.len(), 24);
+        assert_eq!(b[8..18], [16, 0, 1, 0, 0x02, 0, 0, 0, 0xFF, 0xFF]);
+        assert_eq!(b[22..24], [2, 0]);
+    }
+
+    #[test]
+    fn small_structs_round_trip() {
+        round_trip(CreateOut { handle: u64::MAX });
+        round_trip(DestroyIn { handle: 42 });
+        let b = round_trip(VersionInfo::new(&DeviceKind::ALL, Features::all()));
+        assert_eq!(b[..4], [16, 0, 1, 0]);
+    }
+
+    #[test]
+    fn length_is_validated() {
+        let mut short = [0u8; 11];
+        assert_eq!(
+            CreateIn::new(DeviceKind::X360, &DeviceConfig::default()).encode(&mut short),
+            Err(Error::Length { expected: 12, got: 11 })
+        );
+        assert_eq!(CreateOut::decode(&[0; 7]), Err(Error::Length { expected: 8, got: 7 }));
+        // longer input from a newer peer is fine
+        assert_eq!(CreateOut::decode(&[1, 0, 0, 0, 0, 0, 0, 0, 0xFF]), Ok(CreateOut { handle: 1 }));
+    }
+
+    #[test]
+    fn unknown_enum_values_are_rejected() {
+        let mut b = [0u8; 12];
+        CreateIn::new(DeviceKind::DS5, &DeviceConfig::default()).encode(&mut b).unwrap();
+        b[0] = 0x01;
+        assert!(matches!(CreateIn::decode(&b), Err(Error::Value { field: "device kind", .. })));
+        CreateIn::new(DeviceKind::DS5, &DeviceConfig::default()).encode(&mut b).unwrap();
+        b[8] = 9;
+        assert_eq!(CreateIn::decode(&b), Err(Error::Value { field: "trigger range", value: 9 }));
+        b[8] = 8;
+        b[10] = 2;
+        assert_eq!(CreateIn::decode(&b), Err(Error::Value { field: "transport", value: 2 }));
+    }
+}
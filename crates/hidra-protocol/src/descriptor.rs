@@ -0,0 +1,1238 @@
+//! HID report descriptors: a small item builder plus the canned descriptor per `DeviceKind`.
+//! The canned descriptors describe exactly the bytes produced by the packers in `report`.
+
+use crate::generic::GenericSpec;
+use crate::transport::BT_REPORT_LEN;
+use crate::{DeviceConfig, DeviceKind, Features, Transport, XusbSubtype, feature};
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UsagePage {
+    GenericDesktop,
+    Simulation,
+    Keyboard,
+    Led,
+    Button,
+    Ordinal,
+    Consumer,
+    PhysicalInput,
+    Vendor(u16),
+}
+
+impl UsagePage {
+    pub fn id(self) -> u16 {
+        match self {
+            UsagePage::GenericDesktop => 0x01,
+            UsagePage::Simulation => 0x02,
+            UsagePage::Keyboard => 0x07,
+            UsagePage::Led => 0x08,
+            UsagePage::Button => 0x09,
+            UsagePage::Ordinal => 0x0A,
+            UsagePage::Consumer => 0x0C,
+            UsagePage::PhysicalInput => 0x0F,
+            UsagePage::Vendor(id) => id,
+        }
+    }
+}
+
+/// Generic Desktop usages.
+pub mod usage {
+    pub const POINTER: u16 = 0x01;
+    pub const MOUSE: u16 = 0x02;
+    pub const JOYSTICK: u16 = 0x04;
+    pub const GAMEPAD: u16 = 0x05;
+    pub const KEYBOARD: u16 = 0x06;
+    pub const X: u16 = 0x30;
+    pub const Y: u16 = 0x31;
+    pub const Z: u16 = 0x32;
+    pub const RX: u16 = 0x33;
+    pub const RY: u16 = 0x34;
+    pub const RZ: u16 = 0x35;
+    pub const WHEEL: u16 = 0x38;
+    pub const HAT_SWITCH: u16 = 0x39;
+}
+
+/// Simulation Controls usages.
+pub mod simulation {
+    pub const ACCELERATOR: u16 = 0xC4;
+    pub const BRAKE: u16 = 0xC5;
+    pub const CLUTCH: u16 = 0xC6;
+}
+
+/// Physical Input Device (force feedback) usages.
+pub mod physical {
+    pub const SET_EFFECT_REPORT: u16 = 0x21;
+    pub const EFFECT_BLOCK_INDEX: u16 = 0x22;
+    pub const PARAMETER_BLOCK_OFFSET: u16 = 0x23;
+    pub const EFFECT_TYPE: u16 = 0x25;
+    pub const ET_CONSTANT_FORCE: u16 = 0x26;
+    pub const BYTE_COUNT: u16 = 0x3B;
+    pub const ET_SPRING: u16 = 0x40;
+    pub const ET_DAMPER: u16 = 0x41;
+    pub const ET_INERTIA: u16 = 0x42;
+    pub const ET_FRICTION: u16 = 0x43;
+    pub const DURATION: u16 = 0x50;
+    pub const SAMPLE_PERIOD: u16 = 0x51;
+    pub const GAIN: u16 = 0x52;
+    pub const TRIGGER_BUTTON: u16 = 0x53;
+    pub const TRIGGER_REPEAT_INTERVAL: u16 = 0x54;
+    pub const AXES_ENABLE: u16 = 0x55;
+    pub const DIRECTION_ENABLE: u16 = 0x56;
+    pub const DIRECTION: u16 = 0x57;
+    pub const SET_CONDITION_REPORT: u16 = 0x5F;
+    pub const CP_OFFSET: u16 = 0x60;
+    pub const POSITIVE_COEFFICIENT: u16 = 0x61;
+    pub const NEGATIVE_COEFFICIENT: u16 = 0x62;
+    pub const POSITIVE_SATURATION: u16 = 0x63;
+    pub const NEGATIVE_SATURATION: u16 = 0x64;
+    pub const DEAD_BAND: u16 = 0x65;
+    pub const MAGNITUDE: u16 = 0x70;
+    pub const SET_CONSTANT_FORCE_REPORT: u16 = 0x73;
+    pub const EFFECT_OPERATION_REPORT: u16 = 0x77;
+    pub const EFFECT_OPERATION: u16 = 0x78;
+    pub const OP_EFFECT_START: u16 = 0x79;
+    pub const OP_EFFECT_START_SOLO: u16 = 0x7A;
+    pub const OP_EFFECT_STOP: u16 = 0x7B;
+    pub const LOOP_COUNT: u16 = 0x7C;
+    pub const DEVICE_GAIN_REPORT: u16 = 0x7D;
+    pub const DEVICE_GAIN: u16 = 0x7E;
+    pub const POOL_REPORT: u16 = 0x7F;
+    pub const RAM_POOL_SIZE: u16 = 0x80;
+    pub const SIMULTANEOUS_EFFECTS_MAX: u16 = 0x83;
+    pub const BLOCK_LOAD_REPORT: u16 = 0x89;
+    pub const BLOCK_LOAD_STATUS: u16 = 0x8B;
+    pub const BLOCK_LOAD_SUCCESS: u16 = 0x8C;
+    pub const BLOCK_LOAD_FULL: u16 = 0x8D;
+    pub const BLOCK_LOAD_ERROR: u16 = 0x8E;
+    pub const BLOCK_FREE_REPORT: u16 = 0x90;
+    pub const DEVICE_CONTROL_REPORT: u16 = 0x95;
+    pub const DC_ENABLE_ACTUATORS: u16 = 0x97;
+    pub const DC_DISABLE_ACTUATORS: u16 = 0x98;
+    pub const DC_STOP_ALL_EFFECTS: u16 = 0x99;
+    pub const DC_DEVICE_RESET: u16 = 0x9A;
+    pub const DC_DEVICE_PAUSE: u16 = 0x9B;
+    pub const DC_DEVICE_CONTINUE: u16 = 0x9C;
+    pub const START_DELAY: u16 = 0xA7;
+    pub const DEVICE_MANAGED_POOL: u16 = 0xA9;
+    pub const SHARED_PARAMETER_BLOCKS: u16 = 0xAA;
+    pub const CREATE_NEW_EFFECT_REPORT: u16 = 0xAB;
+    pub const RAM_POOL_AVAILABLE: u16 = 0xAC;
+}
+
+/// Consumer usages.
+pub mod consumer {
+    pub const RECORD: u16 = 0xB2;
+    pub const AC_PAN: u16 = 0x238;
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Collection {
+    Physical = 0x00,
+    Application = 0x01,
+    Logical = 0x02,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+bitflags::bitflags! {
+    /// Data bits of Input/Output/Feature main items.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub struct ItemFlags: u16 {
+        const CONSTANT       = 1 << 0;
+        const VARIABLE       = 1 << 1;
+        const RELATIVE       = 1 << 2;
+        const WRAP           = 1 << 3;
+        const NON_LINEAR     = 1 << 4;
+        const NO_PREFERRED   = 1 << 5;
+        const NULL_STATE     = 1 << 6;
+        const VOLATILE       = 1 << 7;
+        const BUFFERED_BYTES = 1 << 8;
+    }
+}
+
+impl ItemFlags {
+    /// Data, Variable, Absolute.
+    pub const DATA_VAR_ABS: Self = Self::VARIABLE;
+    /// Constant padding.
+    pub const PADDING: Self = Self::CONSTANT.union(Self::VARIABLE);
+}
+
+// Item tags, already shifted with their type bits (short items).
+const MAIN_INPUT: u8 = 0x80;
+const MAIN_OUTPUT: u8 = 0x90;
+const MAIN_COLLECTION: u8 = 0xA0;
+const MAIN_FEATURE: u8 = 0xB0;
+const MAIN_END_COLLECTION: u8 = 0xC0;
+const GLOBAL_USAGE_PAGE: u8 = 0x04;
+const GLOBAL_LOGICAL_MIN: u8 = 0x14;
+const GLOBAL_LOGICAL_MAX: u8 = 0x24;
+const GLOBAL_PHYSICAL_MIN: u8 = 0x34;
+const GLOBAL_PHYSICAL_MAX: u8 = 0x44;
+const GLOBAL_UNIT_EXPONENT: u8 = 0x54;
+const GLOBAL_UNIT: u8 = 0x64;
+const GLOBAL_REPORT_SIZE: u8 = 0x74;
+const GLOBAL_REPORT_ID: u8 = 0x84;
+const GLOBAL_REPORT_COUNT: u8 = 0x94;
+const LOCAL_USAGE: u8 = 0x08;
+const LOCAL_USAGE_MIN: u8 = 0x18;
+const LOCAL_USAGE_MAX: u8 = 0x28;
+
+/// Serialized HID report descriptor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportDescriptor(pub Vec<u8>);
+
+impl ReportDescriptor {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Length in bytes of report `id` of the given type, including the ID byte.
+    /// Use `id = 0` for descriptors without report IDs. `None` if no such report.
+    pub fn report_len(&self, ty: ReportType, id: u8) -> Option<usize> {
+        let (mut size, mut count, mut cur_id) = (0u32, 0u32, 0u8);
+        let mut bits = None;
+        let mut i = 0;
+        let b = &self.0;
+        while i < b.len() {
+            let prefix = b[i];
+            if prefix == 0xFE {
+                // long item: size byte, tag byte, data
+                i += 3 + *b.get(i + 1)? as usize;
+                continue;
+            }
+            let len = [0, 1, 2, 4][(prefix & 0x03) as usize];
+            let data = b.get(i + 1..i + 1 + len)?;
+            let value = data.iter().rev().fold(0u32, |v, &x| (v << 8) | x as u32);
+            match prefix & 0xFC {
+                GLOBAL_REPORT_SIZE => size = value,
+                GLOBAL_REPORT_COUNT => count = value,
+                GLOBAL_REPORT_ID => cur_id = value as u8,
+                tag @ (MAIN_INPUT | MAIN_OUTPUT | MAIN_FEATURE) => {
+                    let item_ty = match tag {
+                        MAIN_INPUT => ReportType::Input,
+                        MAIN_OUTPUT => ReportType::Output,
+                        _ => ReportType::Feature,
+                    };
+                    if item_ty == ty && cur_id == id {
+                        bits = Some(bits.unwrap_or(0) + size * count);
+                    }
+                }
+                _ => {}
+            }
+            i += 1 + len;
+        }
+        bits.map(|bits| bits.div_ceil(8) as usize + (id != 0) as usize)
+    }
+}
+
+/// Builds a descriptor item by item. Values are emitted in the shortest encoding.
+#[derive(Clone, Debug, Default)]
+pub struct DescriptorBuilder {
+    bytes: Vec<u8>,
+}
+
+impl DescriptorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(self) -> ReportDescriptor {
+        ReportDescriptor(self.bytes)
+    }
+
+    fn item_unsigned(mut self, tag: u8, v: u32) -> Self {
+        let data = v.to_le_bytes();
+        let len = match v {
+            0..=0xFF => 1,
+            0x100..=0xFFFF => 2,
+            _ => 4,
+        };
+        self.bytes.push(tag | if len == 4 { 3 } else { len as u8 });
+        self.bytes.extend_from_slice(&data[..len]);
+        self
+    }
+
+    fn item_signed(mut self, tag: u8, v: i32) -> Self {
+        let data = v.to_le_bytes();
+        let len = match v {
+            -0x80..=0x7F => 1,
+            -0x8000..=0x7FFF => 2,
+            _ => 4,
+        };
+        self.bytes.push(tag | if len == 4 { 3 } else { len as u8 });
+        self.bytes.extend_from_slice(&data[..len]);
+        self
+    }
+
+    fn item_main(self, tag: u8, flags: ItemFlags) -> Self {
+        self.item_unsigned(tag, flags.bits() as u32)
+    }
+
+    pub fn usage_page(self, page: UsagePage) -> Self {
+        self.item_unsigned(GLOBAL_USAGE_PAGE, page.id() as u32)
+    }
+
+    pub fn usage(self, usage: u16) -> Self {
+        self.item_unsigned(LOCAL_USAGE, usage as u32)
+    }
+
+    pub fn usage_range(self, min: u16, max: u16) -> Self {
+        self.item_unsigned(LOCAL_USAGE_MIN, min as u32).item_unsigned(LOCAL_USAGE_MAX, max as u32)
+    }
+
+    pub fn logical_range(self, min: i32, max: i32) -> Self {
+        self.item_signed(GLOBAL_LOGICAL_MIN, min).item_signed(GLOBAL_LOGICAL_MAX, max)
+    }
+
+    pub fn physical_range(self, min: i32, max: i32) -> Self {
+        self.item_signed(GLOBAL_PHYSICAL_MIN, min).item_signed(GLOBAL_PHYSICAL_MAX, max)
+    }
+
+    pub fn unit(self, unit: u32) -> Self {
+        self.item_unsigned(GLOBAL_UNIT, unit)
+    }
+
+    pub fn unit_exponent(self, exp: u8) -> Self {
+        self.item_unsigned(GLOBAL_UNIT_EXPONENT, exp as u32)
+    }
+
+    pub fn report_id(self, id: u8) -> Self {
+        self.item_unsigned(GLOBAL_REPORT_ID, id as u32)
+    }
+
+    pub fn report_size(self, bits: u32) -> Self {
+        self.item_unsigned(GLOBAL_REPORT_SIZE, bits)
+    }
+
+    pub fn report_count(self, count: u32) -> Self {
+        self.item_unsigned(GLOBAL_REPORT_COUNT, count)
+    }
+
+    pub fn input(self, flags: ItemFlags) -> Self {
+        self.item_main(MAIN_INPUT, flags)
+    }
+
+    pub fn output(self, flags: ItemFlags) -> Self {
+        self.item_main(MAIN_OUTPUT, flags)
+    }
+
+    pub fn feature(self, flags: ItemFlags) -> Self {
+        self.item_main(MAIN_FEATURE, flags)
+    }
+
+    /// Opens a collection, lets `f` fill it, then closes it.
+    pub fn collection(self, kind: Collection, f: impl FnOnce(Self) -> Self) -> Self {
+        let mut b = f(self.item_unsigned(MAIN_COLLECTION, kind as u32));
+        b.bytes.push(MAIN_END_COLLECTION);
+        b
+    }
+
+    /// Constant padding of `bits` bits.
+    pub fn padding(self, bits: u32) -> Self {
+        self.report_size(1).report_count(bits).input(ItemFlags::PADDING)
+    }
+
+    /// `count` 1-bit buttons, usages `Button 1..=count`.
+    pub fn buttons(self, count: u16) -> Self {
+        self.buttons_from(1, count)
+    }
+
+    /// `count` 1-bit buttons, usages `Button first..first + count`.
+    pub fn buttons_from(self, first: u16, count: u16) -> Self {
+        self.usage_page(UsagePage::Button)
+            .usage_range(first, first + count - 1)
+            .logical_range(0, 1)
+            .report_size(1)
+            .report_count(count as u32)
+            .input(ItemFlags::DATA_VAR_ABS)
+    }
+
+    /// A 4-bit hat switch, 0..7 clockwise from north, out-of-range = centered.
+    pub fn hat(self) -> Self {
+        self.hat_from(0)
+    }
+
+    /// Like `hat`, with north at `north` instead of 0.
+    pub fn hat_from(self, north: i32) -> Self {
+        self.usage_page(UsagePage::GenericDesktop)
+            .usage(usage::HAT_SWITCH)
+            .logical_range(north, north + 7)
+            .physical_range(0, 315)
+            .unit(0x14) // English rotation, degrees
+            .report_size(4)
+            .report_count(1)
+            .input(ItemFlags::DATA_VAR_ABS | ItemFlags::NULL_STATE)
+            .physical_range(0, 0)
+            .unit(0)
+    }
+
+    /// Opaque vendor-defined bytes.
+    pub fn vendor_bytes(self, ty: ReportType, usage: u16, count: u32) -> Self {
+        let b = self
+            .usage_page(UsagePage::Vendor(0xFF00))
+            .usage(usage)
+            .logical_range(0, 255)
+            .report_size(8)
+            .report_count(count);
+        match ty {
+            ReportType::Input => b.input(ItemFlags::DATA_VAR_ABS),
+            ReportType::Output => b.output(ItemFlags::DATA_VAR_ABS),
+            ReportType::Feature => b.feature(ItemFlags::DATA_VAR_ABS),
+        }
+    }
+}
+
+/// Canned descriptor for a device kind connected over `transport`.
+pub fn for_device(kind: DeviceKind, transport: Transport) -> ReportDescriptor {
+    match (kind, transport) {
+        (DeviceKind::DS4, Transport::Bluetooth) => ds4_bt(),
+        (DeviceKind::DS5 | DeviceKind::DS5Edge, Transport::Bluetooth) => ds5_bt(),
+        _ => for_kind(kind),
+    }
+}
+
+/// Descriptor of a device created as `kind` with `cfg`.
+pub fn for_config(kind: DeviceKind, cfg: &DeviceConfig) -> ReportDescriptor {
+    match kind {
+        DeviceKind::X360 => x360_subtype(cfg.subtype),
+        DeviceKind::Generic => generic(&cfg.generic),
+        DeviceKind::Keyboard => keyboard_with(cfg.features),
+        _ => for_device(kind, cfg.transport),
+    }
+}
+
+/// Canned USB descriptor for a device kind.
+pub fn for_kind(kind: DeviceKind) -> ReportDescriptor {
+    match kind {
+        DeviceKind::X360 => x360(),
+        DeviceKind::DS4 => ds4(),
+        DeviceKind::DS5 => ds5(),
+        DeviceKind::XboxOne => xbox_one(),
+        DeviceKind::SwitchPro => switch_pro(),
+        DeviceKind::Generic => generic(&GenericSpec::default()),
+        DeviceKind::Wheel => wheel(),
+        DeviceKind::DS5Edge => ds5_edge(),
+        DeviceKind::XboxElite => xbox_elite(),
+        DeviceKind::Keyboard => keyboard(),
+        DeviceKind::Mouse => mouse(),
+    }
+}
+
+/// XUSB over HID, no report ID; mirrors `X360Report`.
+pub fn x360() -> ReportDescriptor {
+    x360_subtype(XusbSubtype::Gamepad)
+}
+
+/// `x360` as `subtype` presents it: the same 14 bytes, with the fields the
+/// subtype leaves zero declared as padding (see `XusbSubtype::shape`).
+pub fn x360_subtype(subtype: XusbSubtype) -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            let b = b.buttons(16).usage_page(UsagePage::GenericDesktop);
+            let b = match subtype {
+                XusbSubtype::Gamepad => b
+                    .usage(usage::Z)
+                    .usage(usage::RZ)
+                    .logical_range(0, 255)
+                    .report_size(8)
+                    .report_count(2)
+                    .input(ItemFlags::DATA_VAR_ABS)
+                    .usage(usage::X)
+                    .usage(usage::Y)
+                    .usage(usage::RX)
+                    .usage(usage::RY)
+                    .logical_range(i16::MIN as i32, i16::MAX as i32)
+                    .report_size(16)
+                    .report_count(4)
+                    .input(ItemFlags::DATA_VAR_ABS),
+                XusbSubtype::ArcadeStick => b
+                    .usage(usage::Z)
+                    .usage(usage::RZ)
+                    .logical_range(0, 255)
+                    .report_size(8)
+                    .report_count(2)
+                    .input(ItemFlags::DATA_VAR_ABS)
+                    .padding(64),
+                XusbSubtype::DancePad => b.padding(80),
+                // pickup selector, then whammy and tilt
+                XusbSubtype::Guitar => b
+                    .usage(usage::Z)
+                    .logical_range(0, 255)
+                    .report_size(8)
+                    .report_count(1)
+                    .input(ItemFlags::DATA_VAR_ABS)
+                    .padding(40)
+                    .usage(usage::RX)
+                    .usage(usage::RY)
+                    .logical_range(i16::MIN as i32, i16::MAX as i32)
+                    .report_size(16)
+                    .report_count(2)
+                    .input(ItemFlags::DATA_VAR_ABS),
+                // red, yellow, blue, green velocities
+                XusbSubtype::DrumKit => b
+                    .padding(16)
+                    .usage(usage::X)
+                    .usage(usage::Y)
+                    .usage(usage::RX)
+                    .usage(usage::RY)
+                    .logical_range(0, i16::MAX as i32)
+                    .report_size(16)
+                    .report_count(4)
+                    .input(ItemFlags::DATA_VAR_ABS),
+            };
+            b.padding(16)
+        })
+        .build()
+}
+
+/// DualShock 4 over USB; input report 0x01 mirrors `DS4Report`, output report 0x05
+/// carries rumble and lightbar, feature reports are those in `feature`.
+pub fn ds4() -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            b.report_id(0x01)
+                .usage(usage::X)
+                .usage(usage::Y)
+                .usage(usage::Z)
+                .usage(usage::RZ)
+                .logical_range(0, 255)
+                .report_size(8)
+                .report_count(4)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .hat()
+                .buttons(14)
+                // frame counter
+                .usage_page(UsagePage::Vendor(0xFF00))
+                .usage(0x20)
+                .report_size(6)
+                .report_count(1)
+                .logical_range(0, 63)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .usage_page(UsagePage::GenericDesktop)
+                .usage(usage::RX)
+                .usage(usage::RY)
+                .logical_range(0, 255)
+                .report_size(8)
+                .report_count(2)
+                .input(ItemFlags::DATA_VAR_ABS)
+                // timestamp, motion, status and touch
+                .vendor_bytes(ReportType::Input, 0x21, 54)
+                .report_id(0x05)
+                .vendor_bytes(ReportType::Output, 0x22, 31)
+                .report_id(feature::DS4_CALIBRATION_ID)
+                .vendor_bytes(ReportType::Feature, 0x24, feature::DS4_CALIBRATION_LEN as u32 - 1)
+                .report_id(feature::DS4_PAIRING_ID)
+                .vendor_bytes(ReportType::Feature, 0x25, feature::DS4_PAIRING_LEN as u32 - 1)
+                .report_id(feature::DS4_FIRMWARE_ID)
+                .vendor_bytes(ReportType::Feature, 0x26, feature::DS4_FIRMWARE_LEN as u32 - 1)
+        })
+        .build()
+}
+
+/// DualSense over USB; input report 0x01 mirrors `DS5Report`, output report 0x02
+/// carries rumble, lights and adaptive triggers, feature reports are those in `feature`.
+pub fn ds5() -> ReportDescriptor {
+    dualsense(false)
+}
+
+/// DualSense Edge over USB: `ds5` with the Fn and back buttons as Buttons 16..19.
+pub fn ds5_edge() -> ReportDescriptor {
+    dualsense(true)
+}
+
+fn dualsense(edge: bool) -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            let b = b
+                .report_id(0x01)
+                .usage(usage::X)
+                .usage(usage::Y)
+                .usage(usage::Z)
+                .usage(usage::RZ)
+                .usage(usage::RX)
+                .usage(usage::RY)
+                .logical_range(0, 255)
+                .report_size(8)
+                .report_count(6)
+                .input(ItemFlags::DATA_VAR_ABS)
+                // sequence number
+                .vendor_bytes(ReportType::Input, 0x20, 1)
+                .hat()
+                .buttons(15);
+            let b = if edge { b.padding(1).buttons_from(16, 4) } else { b };
+            b.usage_page(UsagePage::Vendor(0xFF00))
+                .usage(0x21)
+                .logical_range(0, 1)
+                .report_size(1)
+                .report_count(if edge { 8 } else { 13 })
+                .input(ItemFlags::DATA_VAR_ABS)
+                // motion, timestamp, touch and status
+                .vendor_bytes(ReportType::Input, 0x22, 52)
+                .report_id(0x02)
+                .vendor_bytes(ReportType::Output, 0x23, 47)
+                .report_id(feature::DS5_CALIBRATION_ID)
+                .vendor_bytes(ReportType::Feature, 0x24, feature::DS5_CALIBRATION_LEN as u32 - 1)
+                .report_id(feature::DS5_PAIRING_ID)
+                .vendor_bytes(ReportType::Feature, 0x25, feature::DS5_PAIRING_LEN as u32 - 1)
+                .report_id(feature::DS5_FIRMWARE_ID)
+                .vendor_bytes(ReportType::Feature, 0x26, feature::DS5_FIRMWARE_LEN as u32 - 1)
+        })
+        .build()
+}
+
+/// Xbox One/Series pad as it enumerates over Bluetooth; input report 0x01 mirrors
+/// `XboxOneReport`, output report 0x03 drives the four rumble motors.
+pub fn xbox_one() -> ReportDescriptor {
+    xbox(false)
+}
+
+/// Xbox Elite Series 2: `xbox_one` with paddles P1..P4 as Buttons 16..19.
+pub fn xbox_elite() -> ReportDescriptor {
+    xbox(true)
+}
+
+fn xbox(elite: bool) -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            let b = b
+                .report_id(0x01)
+                .usage(usage::X)
+                .usage(usage::Y)
+                .usage(usage::Z)
+                .usage(usage::RZ)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .report_count(4)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .usage_page(UsagePage::Simulation)
+                .usage(simulation::BRAKE)
+                .logical_range(0, 1023)
+                .report_size(10)
+                .report_count(1)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .padding(6)
+                .usage(simulation::ACCELERATOR)
+                .report_size(10)
+                .report_count(1)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .padding(6)
+                .hat_from(1)
+                .padding(4)
+                .buttons(15)
+                .padding(1)
+                .usage_page(UsagePage::Consumer)
+                .usage(consumer::RECORD)
+                .logical_range(0, 1)
+                .report_size(1)
+                .report_count(1)
+                .input(ItemFlags::DATA_VAR_ABS);
+            let b = if elite { b.buttons_from(16, 4).padding(3) } else { b.padding(7) };
+            b.report_id(0x03).vendor_bytes(ReportType::Output, 0x22, 8)
+        })
+        .build()
+}
+
+/// Switch Pro Controller: every report is an opaque 63-byte blob, as on the real pad.
+/// Inputs are 0x30 (`SwitchProReport`) and the 0x21/0x81 replies, outputs the
+/// 0x01/0x10/0x80 requests handled by `switch_pro::Session`.
+pub fn switch_pro() -> ReportDescriptor {
+    use crate::switch_pro::{
+        OUTPUT_RUMBLE_ID, OUTPUT_SUBCOMMAND_ID, OUTPUT_USB_ID, REPLY_ID, USB_REPLY_ID,
+    };
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            let inputs = [crate::report::SwitchProReport::ID, REPLY_ID, USB_REPLY_ID];
+            let b = inputs
+                .into_iter()
+                .fold(b, |b, id| b.report_id(id).vendor_bytes(ReportType::Input, 0x21, 63));
+            [OUTPUT_SUBCOMMAND_ID, OUTPUT_RUMBLE_ID, OUTPUT_USB_ID]
+                .into_iter()
+                .fold(b, |b, id| b.report_id(id).vendor_bytes(ReportType::Output, 0x22, 63))
+        })
+        .build()
+}
+
+/// Generic pad built from `spec`, no report ID; mirrors `GenericReport`.
+pub fn generic(spec: &GenericSpec) -> ReportDescriptor {
+    const AXES: [u16; 6] = [usage::X, usage::Y, usage::RX, usage::RY, usage::Z, usage::RZ];
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |mut b| {
+            if spec.axes > 0 {
+                b = AXES[..spec.axes as usize]
+                    .iter()
+                    .fold(b, |b, u| b.usage(*u))
+                    .logical_range(0, (1 << spec.axis_bits) - 1)
+                    .report_size(spec.axis_bits as u32)
+                    .report_count(spec.axes as u32)
+                    .input(ItemFlags::DATA_VAR_ABS);
+            }
+            if spec.hats > 0 {
+                b = b.hat();
+            }
+            if spec.buttons > 0 {
+                b = b.buttons(spec.buttons as u16);
+            }
+            match spec.report_bits() % 8 {
+                0 => b,
+                used => b.padding(8 - used as u32),
+            }
+        })
+        .build()
+}
+
+/// Racing wheel; input report 0x01 mirrors `WheelReport`, and the PID reports
+/// are those `pid` decodes and builds.
+pub fn wheel() -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::JOYSTICK)
+        .collection(Collection::Application, |b| {
+            let b = b
+                .report_id(crate::report::WheelReport::ID)
+                .usage(usage::X)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .report_count(1)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .usage_page(UsagePage::Simulation)
+                .usage(simulation::ACCELERATOR)
+                .usage(simulation::BRAKE)
+                .usage(simulation::CLUTCH)
+                .report_count(3)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .hat()
+                .buttons(11)
+                .padding(1);
+            pid_reports(b)
+        })
+        .build()
+}
+
+/// The force feedback half of `wheel`: one logical collection per PID report.
+fn pid_reports(b: DescriptorBuilder) -> DescriptorBuilder {
+    use crate::pid;
+    use physical::*;
+    const EFFECT_TYPES: [u16; 5] =
+        [ET_CONSTANT_FORCE, ET_SPRING, ET_DAMPER, ET_INERTIA, ET_FRICTION];
+    // one 8-bit array field selecting among `usages`, numbered from 1
+    let array = |b: DescriptorBuilder, usages: &[u16], ty: ReportType| {
+        let b = usages
+            .iter()
+            .fold(b, |b, u| b.usage(*u))
+            .logical_range(1, usages.len() as i32)
+            .report_size(8)
+            .report_count(1);
+        match ty {
+            ReportType::Output => b.output(ItemFlags::empty()),
+            _ => b.feature(ItemFlags::empty()),
+        }
+    };
+    let index = |b: DescriptorBuilder| {
+        b.usage(EFFECT_BLOCK_INDEX)
+            .logical_range(1, pid::MAX_EFFECTS as i32)
+            .report_size(8)
+            .report_count(1)
+    };
+    let force = pid::FORCE_MAX as i32;
+    let out = ItemFlags::DATA_VAR_ABS;
+
+    b.usage_page(UsagePage::PhysicalInput)
+        .usage(SET_EFFECT_REPORT)
+        .collection(Collection::Logical, |b| {
+            index(b.report_id(pid::SET_EFFECT_ID))
+                .output(out)
+                .usage(EFFECT_TYPE)
+                .collection(Collection::Logical, |b| array(b, &EFFECT_TYPES, ReportType::Output))
+                .usage(DURATION)
+                .usage(TRIGGER_REPEAT_INTERVAL)
+                .usage(SAMPLE_PERIOD)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .report_count(3)
+                .output(out)
+                .usage(GAIN)
+                .logical_range(0, 255)
+                .report_size(8)
+                .report_count(1)
+                .output(out)
+                .usage(TRIGGER_BUTTON)
+                .logical_range(0, 11)
+                .output(out)
+                .usage(AXES_ENABLE)
+                .collection(Collection::Logical, |b| {
+                    b.usage_page(UsagePage::GenericDesktop)
+                        .usage(usage::X)
+                        .logical_range(0, 1)
+                        .report_size(1)
+                        .report_count(1)
+                        .output(out)
+                })
+                .usage_page(UsagePage::PhysicalInput)
+                .usage(DIRECTION_ENABLE)
+                .output(out)
+                .report_count(6)
+                .output(ItemFlags::PADDING)
+                .usage(DIRECTION)
+                .collection(Collection::Logical, |b| {
+                    b.usage_page(UsagePage::Ordinal)
+                        .usage(1)
+                        .logical_range(0, 35999)
+                        .report_size(16)
+                        .report_count(1)
+                        .output(out)
+                })
+                .usage_page(UsagePage::PhysicalInput)
+                .usage(START_DELAY)
+                .logical_range(0, 65535)
+                .output(out)
+        })
+        .usage(SET_CONDITION_REPORT)
+        .collection(Collection::Logical, |b| {
+            index(b.report_id(pid::SET_CONDITION_ID))
+                .output(out)
+                .usage(PARAMETER_BLOCK_OFFSET)
+                .logical_range(0, 1)
+                .output(out)
+                .usage(CP_OFFSET)
+                .usage(POSITIVE_COEFFICIENT)
+                .usage(NEGATIVE_COEFFICIENT)
+                .logical_range(-force, force)
+                .report_size(16)
+                .report_count(3)
+                .output(out)
+                .usage(POSITIVE_SATURATION)
+                .usage(NEGATIVE_SATURATION)
+                .usage(DEAD_BAND)
+                .logical_range(0, force)
+                .output(out)
+        })
+        .usage(SET_CONSTANT_FORCE_REPORT)
+        .collection(Collection::Logical, |b| {
+            index(b.report_id(pid::SET_CONSTANT_FORCE_ID))
+                .output(out)
+                .usage(MAGNITUDE)
+                .logical_range(-force, force)
+                .report_size(16)
+                .output(out)
+        })
+        .usage(EFFECT_OPERATION_REPORT)
+        .collection(Collection::Logical, |b| {
+            index(b.report_id(pid::EFFECT_OPERATION_ID))
+                .output(out)
+                .usage(EFFECT_OPERATION)
+                .collection(Collection::Logical, |b| {
+                    array(
+                        b,
+                        &[OP_EFFECT_START, OP_EFFECT_START_SOLO, OP_EFFECT_STOP],
+                        ReportType::Output,
+                    )
+                })
+                .usage(LOOP_COUNT)
+                .logical_range(0, 255)
+                .output(out)
+        })
+        .usage(BLOCK_FREE_REPORT)
+        .collection(Collection::Logical, |b| index(b.report_id(pid::BLOCK_FREE_ID)).output(out))
+        .usage(DEVICE_CONTROL_REPORT)
+        .collection(Collection::Logical, |b| {
+            array(
+                b.report_id(pid::DEVICE_CONTROL_ID),
+                &[
+                    DC_ENABLE_ACTUATORS,
+                    DC_DISABLE_ACTUATORS,
+                    DC_STOP_ALL_EFFECTS,
+                    DC_DEVICE_RESET,
+                    DC_DEVICE_PAUSE,
+                    DC_DEVICE_CONTINUE,
+                ],
+                ReportType::Output,
+            )
+        })
+        .usage(DEVICE_GAIN_REPORT)
+        .collection(Collection::Logical, |b| {
+            b.report_id(pid::DEVICE_GAIN_ID)
+                .usage(DEVICE_GAIN)
+                .logical_range(0, 255)
+                .report_size(8)
+                .report_count(1)
+                .output(out)
+        })
+        .usage(CREATE_NEW_EFFECT_REPORT)
+        .collection(Collection::Logical, |b| {
+            b.report_id(pid::CREATE_NEW_EFFECT_ID)
+                .usage(EFFECT_TYPE)
+                .collection(Collection::Logical, |b| array(b, &EFFECT_TYPES, ReportType::Feature))
+                .usage(BYTE_COUNT)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .feature(out)
+        })
+        .usage(BLOCK_LOAD_REPORT)
+        .collection(Collection::Logical, |b| {
+            index(b.report_id(pid::BLOCK_LOAD_ID))
+                .feature(out)
+                .usage(BLOCK_LOAD_STATUS)
+                .collection(Collection::Logical, |b| {
+                    array(
+                        b,
+                        &[BLOCK_LOAD_SUCCESS, BLOCK_LOAD_FULL, BLOCK_LOAD_ERROR],
+                        ReportType::Feature,
+                    )
+                })
+                .usage(RAM_POOL_AVAILABLE)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .feature(out)
+        })
+        .usage(POOL_REPORT)
+        .collection(Collection::Logical, |b| {
+            b.report_id(pid::POOL_ID)
+                .usage(RAM_POOL_SIZE)
+                .logical_range(0, 65535)
+                .report_size(16)
+                .report_count(1)
+                .feature(out)
+                .usage(SIMULTANEOUS_EFFECTS_MAX)
+                .logical_range(0, 255)
+                .report_size(8)
+                .feature(out)
+                .usage(DEVICE_MANAGED_POOL)
+                .usage(SHARED_PARAMETER_BLOCKS)
+                .logical_range(0, 1)
+                .report_size(1)
+                .report_count(2)
+                .feature(out)
+                .report_count(6)
+                .feature(ItemFlags::PADDING)
+        })
+}
+
+/// Boot protocol keyboard with LEDs, no report ID; mirrors `KeyboardReport`.
+pub fn keyboard() -> ReportDescriptor {
+    keyboard_with(DeviceKind::Keyboard.capabilities().defaults)
+}
+
+/// Keyboard as created with `features`: `NKRO` swaps the six key slots for the
+/// `KeyboardNkroReport` bitmap, `LED` adds the one-byte LED output report.
+pub fn keyboard_with(features: Features) -> ReportDescriptor {
+    use crate::keyboard::{KEY_MAX, MODIFIER_MIN};
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::KEYBOARD)
+        .collection(Collection::Application, |b| {
+            let b = b
+                .usage_page(UsagePage::Keyboard)
+                .usage_range(MODIFIER_MIN as u16, MODIFIER_MIN as u16 + 7)
+                .logical_range(0, 1)
+                .report_size(1)
+                .report_count(8)
+                .input(ItemFlags::DATA_VAR_ABS)
+                .padding(8);
+            let b = if features.contains(Features::LED) {
+                b.usage_page(UsagePage::Led)
+                    .usage_range(1, 5)
+                    .report_count(5)
+                    .output(ItemFlags::DATA_VAR_ABS)
+                    .report_count(3)
+                    .output(ItemFlags::PADDING)
+            } else {
+                b
+            };
+            let b = b.usage_page(UsagePage::Keyboard).usage_range(0, KEY_MAX as u16);
+            if features.contains(Features::NKRO) {
+                b.logical_range(0, 1)
+                    .report_size(1)
+                    .report_count(KEY_MAX as u32 + 1)
+                    .input(ItemFlags::DATA_VAR_ABS)
+            } else {
+                // array of usages, 0 = no key
+                b.logical_range(0, KEY_MAX as i32)
+                    .report_size(8)
+                    .report_count(crate::report::KeyboardReport::ROLLOVER as u32)
+                    .input(ItemFlags::empty())
+            }
+        })
+        .build()
+}
+
+/// Five-button wheel mouse with horizontal pan, no report ID; mirrors
+/// `MouseReport`, whose first three bytes are the boot protocol report.
+pub fn mouse() -> ReportDescriptor {
+    let rel = ItemFlags::DATA_VAR_ABS | ItemFlags::RELATIVE;
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::MOUSE)
+        .collection(Collection::Application, |b| {
+            b.usage(usage::POINTER).collection(Collection::Physical, |b| {
+                b.buttons(5)
+                    .padding(3)
+                    .usage_page(UsagePage::GenericDesktop)
+                    .usage(usage::X)
+                    .usage(usage::Y)
+                    .usage(usage::WHEEL)
+                    .logical_range(-127, 127)
+                    .report_size(8)
+                    .report_count(3)
+                    .input(rel)
+                    .usage_page(UsagePage::Consumer)
+                    .usage(consumer::AC_PAN)
+                    .report_count(1)
+                    .input(rel)
+            })
+        })
+        .build()
+}
+
+/// DualShock 4 over Bluetooth: input and output 0x11 are opaque CRC-sealed blobs,
+/// as on the real pad.
+pub fn ds4_bt() -> ReportDescriptor {
+    bt_blobs(0x11)
+}
+
+/// DualSense over Bluetooth: input and output 0x31, see `ds4_bt`.
+pub fn ds5_bt() -> ReportDescriptor {
+    bt_blobs(0x31)
+}
+
+fn bt_blobs(id: u8) -> ReportDescriptor {
+    DescriptorBuilder::new()
+        .usage_page(UsagePage::GenericDesktop)
+        .usage(usage::GAMEPAD)
+        .collection(Collection::Application, |b| {
+            b.report_id(id)
+                .vendor_bytes(ReportType::Input, 0x21, BT_REPORT_LEN as u32 - 1)
+                .vendor_bytes(ReportType::Output, 0x22, BT_REPORT_LEN as u32 - 1)
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PadState;
+    use crate::report::{DS4Report, DS5Report, SwitchProReport, X360Report, XboxOneReport};
+
+    #[test]
+    fn items_use_shortest_encoding() {
+        let d = DescriptorBuilder::new()
+            .usage_page(UsagePage::Vendor(0xFF00))
+            .logical_range(0, 255)
+            .logical_range(-1, 1)
+            .report_count(70000)
+            .build();
+        assert_eq!(
+            d.as_bytes(),
+            &[
+                0x06, 0x00, 0xFF, // usage page (2 bytes)
+                0x15, 0x00, 0x26, 0xFF, 0x00, // 255 needs 2 bytes signed
+                0x15, 0xFF, 0x25, 0x01, // -1..1
+                0x97, 0x70, 0x11, 0x01, 0x00, // 4-byte count
+            ]
+        );
+    }
+
+    #[test]
+    fn collections_are_closed() {
+        let d = DescriptorBuilder::new()
+            .usage_page(UsagePage::GenericDesktop)
+            .usage(usage::GAMEPAD)
+            .collection(Collection::Application, |b| b.collection(Collection::Physical, |b| b))
+            .build();
+        assert_eq!(d.as_bytes(), &[0x05, 0x01, 0x09, 0x05, 0xA1, 0x01, 0xA1, 0x00, 0xC0, 0xC0]);
+    }
+
+    #[test]
+    fn canned_input_sizes_match_packers() {
+        let s = PadState::default();
+        assert_eq!(
+            x360().report_len(ReportType::Input, 0),
+            Some(X360Report::from(&s).as_bytes().len())
+        );
+        assert_eq!(
+            ds4().report_len(ReportType::Input, DS4Report::ID),
+            Some(DS4Report::from(&s).as_bytes().len())
+        );
+        assert_eq!(
+            ds5().report_len(ReportType::Input, DS5Report::ID),
+            Some(DS5Report::from(&s).as_bytes().len())
+        );
+        assert_eq!(
+            ds5_edge().report_len(ReportType::Input, DS5Report::ID),
+            Some(DS5Report::pack_edge(&s, &Default::default(), &Default::default()).0.len())
+        );
+        assert_eq!(
+            xbox_one().report_len(ReportType::Input, XboxOneReport::ID),
+            Some(XboxOneReport::from(&s).as_bytes().len())
+        );
+        assert_eq!(
+            xbox_elite().report_len(ReportType::Input, XboxOneReport::ID),
+            Some(XboxOneReport::pack_elite(&s, &Default::default()).0.len())
+        );
+        assert_eq!(
+            switch_pro().report_len(ReportType::Input, SwitchProReport::ID),
+            Some(SwitchProReport::from(&s).as_bytes().len())
+        );
+        assert_eq!(switch_pro().report_len(ReportType::Input, 0x21), Some(64));
+    }
+
+    #[test]
+    fn generic_sizes_match_packer() {
+        use crate::DeviceConfig;
+        use crate::report::GenericReport;
+        let specs = [
+            GenericSpec::default(),
+            GenericSpec { axes: 0, buttons: 1, hats: 0, ..Default::default() },
+            GenericSpec { axes: 3, axis_bits: 10, buttons: 5, ..Default::default() },
+            GenericSpec { axes: 6, axis_bits: 12, buttons: 22, hats: 0, ..Default::default() },
+        ];
+        for spec in specs {
+            let cfg = DeviceConfig { generic: spec, ..Default::default() };
+            let rpt = GenericReport::pack(&PadState::default(), &cfg);
+            assert_eq!(
+                generic(&spec).report_len(ReportType::Input, 0),
+                Some(rpt.as_bytes().len()),
+                "{spec:?}"
+            );
+        }
+        assert_eq!(generic(&GenericSpec::default()).report_len(ReportType::Output, 0), None);
+    }
+
+    #[test]
+    fn canned_output_sizes() {
+        assert_eq!(ds4().report_len(ReportType::Output, 0x05), Some(32));
+        assert_eq!(ds5().report_len(ReportType::Output, 0x02), Some(48));
+        assert_eq!(
+            xbox_one().report_len(ReportType::Output, crate::output::XBOX_ONE_OUTPUT_ID),
+            Some(crate::output::XBOX_ONE_OUTPUT_LEN)
+        );
+        assert_eq!(x360().report_len(ReportType::Output, 0), None);
+    }
+
+    #[test]
+    fn wheel_sizes_match_pid() {
+        use crate::pid::*;
+        use crate::report::WheelReport;
+        let d = wheel();
+        assert_eq!(
+            d.report_len(ReportType::Input, WheelReport::ID),
+            Some(WheelReport::from(&PadState::default()).as_bytes().len())
+        );
+        for (id, len) in [
+            (SET_EFFECT_ID, SET_EFFECT_LEN),
+            (SET_CONDITION_ID, SET_CONDITION_LEN),
+            (SET_CONSTANT_FORCE_ID, SET_CONSTANT_FORCE_LEN),
+            (EFFECT_OPERATION_ID, EFFECT_OPERATION_LEN),
+            (BLOCK_FREE_ID, BLOCK_FREE_LEN),
+            (DEVICE_CONTROL_ID, DEVICE_CONTROL_LEN),
+            (DEVICE_GAIN_ID, DEVICE_GAIN_LEN),
+        ] {
+            assert_eq!(d.report_len(ReportType::Output, id), Some(len), "output {id:#04x}");
+        }
+        for (id, len) in [
+            (CREATE_NEW_EFFECT_ID, CREATE_NEW_EFFECT_LEN),
+            (BLOCK_LOAD_ID, BLOCK_LOAD_LEN),
+            (POOL_ID, POOL_LEN),
+        ] {
+            assert_eq!(d.report_len(ReportType::Feature, id), Some(len), "feature {id:#04x}");
+        }
+    }
+
+    #[test]
+    fn bluetooth_sizes_match_packers() {
+        use crate::report::{DS4BtReport, DS5BtReport};
+        let s = PadState::default();
+        let (clock, cfg) = (Default::default(), Default::default());
+        let d = for_device(DeviceKind::DS4, Transport::Bluetooth);
+        assert_eq!(
+            d.report_len(ReportType::Input, DS4BtReport::ID),
+            Some(DS4BtReport::pack(&s, &clock, &cfg).as_bytes().len())
+        );
+        assert_eq!(d.report_len(ReportType::Output, DS4BtReport::ID), Some(BT_REPORT_LEN));
+        let d = for_device(DeviceKind::DS5, Transport::Bluetooth);
+        assert_eq!(
+            d.report_len(ReportType::Input, DS5BtReport::ID),
+            Some(DS5BtReport::pack(&s, &clock, &cfg).as_bytes().len())
+        );
+        assert_eq!(for_device(DeviceKind::X360, Transport::Bluetooth), x360());
+    }
+
+    #[test]
+    fn for_kind_dispatch() {
+        assert_eq!(for_kind(DeviceKind::DS4), ds4());
+        assert_eq!(for_kind(DeviceKind::DS5), ds5());
+        assert_eq!(for_kind(DeviceKind::X360), x360());
+        assert_eq!(for_kind(DeviceKind::XboxOne), xbox_one());
+        assert_eq!(for_kind(DeviceKind::SwitchPro), switch_pro());
+        assert_eq!(for_kind(DeviceKind::Generic), generic(&GenericSpec::default()));
+        assert_eq!(for_kind(DeviceKind::Wheel), wheel());
+        assert_eq!(for_kind(DeviceKind::DS5Edge), ds5_edge());
+        assert_eq!(for_kind(DeviceKind::XboxElite), xbox_elite());
+        assert_eq!(for_kind(DeviceKind::Keyboard), keyboard());
+        assert_eq!(for_kind(DeviceKind::Mouse), mouse());
+        assert_eq!(for_device(DeviceKind::DS5Edge, Transport::Bluetooth), ds5_bt());
+    }
+
+    #[test]
+    fn keyboard_and_mouse_sizes_match_packers() {
+        use crate::report::{KeyboardNkroReport, KeyboardReport, MouseReport};
+        use crate::{KeyboardState, MouseState};
+        let k = KeyboardState::default();
+        let d = keyboard();
+        assert_eq!(d.report_len(ReportType::Input, 0), Some(KeyboardReport::pack(&k).0.len()));
+        assert_eq!(d.report_len(ReportType::Output, 0), Some(1));
+        // boot layout: modifiers, reserved, LEDs, six-key array
+        assert_eq!(
+            d.as_bytes(),
+            &[
+                0x05, 0x01, 0x09, 0x06, 0xA1, 0x01, 0x05, 0x07, 0x19, 0xE0, 0x29, 0xE7, 0x15, 0x00,
+                0x25, 0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0x75, 0x01, 0x95, 0x08, 0x81, 0x03,
+                0x05, 0x08, 0x19, 0x01, 0x29, 0x05, 0x95, 0x05, 0x91, 0x02, 0x95, 0x03, 0x91, 0x03,
+                0x05, 0x07, 0x19, 0x00, 0x29, 0xDF, 0x15, 0x00, 0x26, 0xDF, 0x00, 0x75, 0x08, 0x95,
+                0x06, 0x81, 0x00, 0xC0,
+            ][..]
+        );
+
+        let cfg = DeviceConfig { features: Features::NKRO, ..Default::default() };
+        let d = for_config(DeviceKind::Keyboard, &cfg);
+        assert_eq!(d, keyboard_with(Features::NKRO));
+        assert_eq!(d.report_len(ReportType::Input, 0), Some(KeyboardNkroReport::pack(&k).0.len()));
+        assert_eq!(d.report_len(ReportType::Output, 0), None);
+
+        let mut m = MouseState::default();
+        let d = mouse();
+        assert_eq!(d.report_len(ReportType::Input, 0), Some(MouseReport::take(&mut m).0.len()));
+        assert_eq!(d.report_len(ReportType::Output, 0), None);
+        assert_eq!(&d.as_bytes()[..8], &[0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0x09, 0x01]);
+    }
+
+    #[test]
+    fn x360_subtypes_keep_report_size() {
+        for subtype in XusbSubtype::ALL {
+            let cfg = DeviceConfig { subtype, ..Default::default() };
+            let d = for_config(DeviceKind::X360, &cfg);
+            assert_eq!(d, x360_subtype(subtype));
+            assert_eq!(d.report_len(ReportType::Input, 0), Some(14), "{subtype:?}");
+        }
+        let cfg = DeviceConfig { transport: Transport::Bluetooth, ..Default::default() };
+        assert_eq!(for_config(DeviceKind::DS4, &cfg), ds4_bt());
+    }
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error)]
+pub enum Error {
+    #[error("bad report length: expected {expected} bytes, got {got}")]
+    Length { expected: usize, got: usize },
+    #[error("unexpected report id {0:#04x}")]
+    ReportId(u8),
+    #[error("ABI version mismatch: expected {expected}, got {got}")]
+    AbiVersion { expected: u32, got: u32 },
+    #[error("bad CRC32: expected {expected:#010x}, got {got:#010x}")]
+    Crc { expected: u32, got: u32 },
+    #[error("invalid {field}: {value:#x}")]
+    Value { field: &'static str, value: u32 },
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
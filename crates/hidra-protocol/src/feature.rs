@@ -0,0 +1,274 @@
+//! Feature reports games and Steam read before trusting a DualShock/DualSense:
+//! IMU calibration, pairing info (MAC) and firmware info. USB layouts.
+
+use crate::{DeviceIdentity, DeviceKind};
+use alloc::vec::Vec;
+
+pub const DS4_CALIBRATION_ID: u8 = 0x02;
+pub const DS4_CALIBRATION_LEN: usize = 37;
+pub const DS4_PAIRING_ID: u8 = 0x12;
+pub const DS4_PAIRING_LEN: usize = 16;
+pub const DS4_FIRMWARE_ID: u8 = 0xA3;
+pub const DS4_FIRMWARE_LEN: usize = 49;
+
+pub const DS5_CALIBRATION_ID: u8 = 0x05;
+pub const DS5_CALIBRATION_LEN: usize = 41;
+pub const DS5_PAIRING_ID: u8 = 0x09;
+pub const DS5_PAIRING_LEN: usize = 20;
+pub const DS5_FIRMWARE_ID: u8 = 0x20;
+pub const DS5_FIRMWARE_LEN: usize = 64;
+
+/// Gyro/accelerometer calibration, in the raw units the pad reports.
+/// Drivers derive scale from `(speed_plus + speed_minus) / (plus - minus)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ImuCalibration {
+    /// Pitch, yaw, roll.
+    pub gyro_bias: [i16; 3],
+    pub gyro_plus: [i16; 3],
+    pub gyro_minus: [i16; 3],
+    pub gyro_speed_plus: i16,
+    pub gyro_speed_minus: i16,
+    /// X, Y, Z.
+    pub accel_plus: [i16; 3],
+    pub accel_minus: [i16; 3],
+}
+
+impl Default for ImuCalibration {
+    /// Values close to a factory pad: ~16.4 LSB per deg/s and 8192 LSB per g.
+    fn default() -> Self {
+        Self {
+            gyro_bias: [0; 3],
+            gyro_plus: [8856; 3],
+            gyro_minus: [-8856; 3],
+            gyro_speed_plus: 540,
+            gyro_speed_minus: 540,
+            accel_plus: [8192; 3],
+            accel_minus: [-8192; 3],
+        }
+    }
+}
+
+/// What the firmware info report claims. DS4 only carries the low 16 bits
+/// of `hw_version`/`fw_version` and has no `update_version`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FirmwareInfo {
+    /// `__DATE__` style, e.g. "Sep 21 2018".
+    pub date: [u8; 11],
+    /// `__TIME__` style, e.g. "04:50:51".
+    pub time: [u8; 8],
+    pub hw_version: u32,
+    pub fw_version: u32,
+    pub update_version: u16,
+}
+
+impl FirmwareInfo {
+    pub const DS4: Self = Self {
+        date: *b"Sep 21 2018",
+        time: *b"04:50:51",
+        hw_version: 0x0100,
+        fw_version: 0x0801,
+        update_version: 0,
+    };
+
+    pub const DS5: Self = Self {
+        date: *b"Jun 10 2021",
+        time: *b"10:17:23",
+        hw_version: 0x0000_0613,
+        fw_version: 0x0110_002A,
+        update_version: 0x0324,
+    };
+}
+
+/// Everything the feature reports of one pad are built from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeatureConfig {
+    /// Bluetooth address in display order (`AA:BB:..` is `[0xAA, 0xBB, ..]`).
+    pub mac: [u8; 6],
+    /// Address of the console/PC the pad claims to be paired with.
+    pub host_mac: [u8; 6],
+    pub calibration: ImuCalibration,
+    pub firmware: FirmwareInfo,
+}
+
+impl FeatureConfig {
+    /// The default identity's MAC and the kind's stock firmware.
+    pub fn for_kind(kind: DeviceKind) -> Self {
+        Self::for_identity(kind, &DeviceIdentity::for_kind(kind, 0))
+    }
+
+    /// The MAC from `identity` and the kind's stock firmware.
+    pub fn for_identity(kind: DeviceKind, identity: &DeviceIdentity) -> Self {
+        Self {
+            mac: identity.mac,
+            host_mac: [0; 6],
+            calibration: ImuCalibration::default(),
+            firmware: match kind {
+                DeviceKind::DS5 | DeviceKind::DS5Edge => FirmwareInfo::DS5,
+                _ => FirmwareInfo::DS4,
+            },
+        }
+    }
+}
+
+/// Feature report `id` for `kind`, or `None` if that pad has no such report.
+pub fn get_feature(kind: DeviceKind, id: u8, cfg: &FeatureConfig) -> Option<Vec<u8>> {
+    let r = match (kind, id) {
+        (DeviceKind::DS4, DS4_CALIBRATION_ID) => ds4_calibration(&cfg.calibration).to_vec(),
+        (DeviceKind::DS4, DS4_PAIRING_ID) => ds4_pairing(cfg.mac, cfg.host_mac).to_vec(),
+        (DeviceKind::DS4, DS4_FIRMWARE_ID) => ds4_firmware(&cfg.firmware).to_vec(),
+        (DeviceKind::DS5 | DeviceKind::DS5Edge, id) => match id {
+            DS5_CALIBRATION_ID => ds5_calibration(&cfg.calibration).to_vec(),
+            DS5_PAIRING_ID => ds5_pairing(cfg.mac).to_vec(),
+            DS5_FIRMWARE_ID => ds5_firmware(&cfg.firmware).to_vec(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(r)
+}
+
+pub fn ds4_calibration(c: &ImuCalibration) -> [u8; DS4_CALIBRATION_LEN] {
+    let mut r = [0u8; DS4_CALIBRATION_LEN];
+    r[0] = DS4_CALIBRATION_ID;
+    pack_calibration(&mut r[1..35], c);
+    r
+}
+
+/// Device and host addresses are stored byte-reversed. Bytes 7..10 are the
+/// class-of-device bytes real pads return.
+pub fn ds4_pairing(mac: [u8; 6], host_mac: [u8; 6]) -> [u8; DS4_PAIRING_LEN] {
+    let mut r = [0u8; DS4_PAIRING_LEN];
+    r[0] = DS4_PAIRING_ID;
+    r[1..7].copy_from_slice(&reversed(mac));
+    r[7..10].copy_from_slice(&[0x08, 0x25, 0x00]);
+    r[10..16].copy_from_slice(&reversed(host_mac));
+    r
+}
+
+pub fn ds4_firmware(f: &FirmwareInfo) -> [u8; DS4_FIRMWARE_LEN] {
+    let mut r = [0u8; DS4_FIRMWARE_LEN];
+    r[0] = DS4_FIRMWARE_ID;
+    r[1..12].copy_from_slice(&f.date);
+    r[17..25].copy_from_slice(&f.time);
+    r[35..37].copy_from_slice(&(f.hw_version as u16).to_le_bytes());
+    r[41..43].copy_from_slice(&(f.fw_version as u16).to_le_bytes());
+    r
+}
+
+pub fn ds5_calibration(c: &ImuCalibration) -> [u8; DS5_CALIBRATION_LEN] {
+    let mut r = [0u8; DS5_CALIBRATION_LEN];
+    r[0] = DS5_CALIBRATION_ID;
+    pack_calibration(&mut r[1..35], c);
+    r
+}
+
+pub fn ds5_pairing(mac: [u8; 6]) -> [u8; DS5_PAIRING_LEN] {
+    let mut r = [0u8; DS5_PAIRING_LEN];
+    r[0] = DS5_PAIRING_ID;
+    r[1..7].copy_from_slice(&reversed(mac));
+    r
+}
+
+pub fn ds5_firmware(f: &FirmwareInfo) -> [u8; DS5_FIRMWARE_LEN] {
+    let mut r = [0u8; DS5_FIRMWARE_LEN];
+    r[0] = DS5_FIRMWARE_ID;
+    r[1..12].copy_from_slice(&f.date);
+    r[12..20].copy_from_slice(&f.time);
+    r[24..28].copy_from_slice(&f.hw_version.to_le_bytes());
+    r[28..32].copy_from_slice(&f.fw_version.to_le_bytes());
+    r[44..46].copy_from_slice(&f.update_version.to_le_bytes());
+    r
+}
+
+/// The USB calibration block shared by DS4 0x02 and DS5 0x05: biases, then
+/// plus/minus per gyro axis, gyro speeds, then plus/minus per accel axis.
+fn pack_calibration(b: &mut [u8], c: &ImuCalibration) {
+    let mut words = Vec::with_capacity(17);
+    words.extend(c.gyro_bias);
+    for i in 0..3 {
+        words.extend([c.gyro_plus[i], c.gyro_minus[i]]);
+    }
+    words.extend([c.gyro_speed_plus, c.gyro_speed_minus]);
+    for i in 0..3 {
+        words.extend([c.accel_plus[i], c.accel_minus[i]]);
+    }
+    for (chunk, w) in b.chunks_exact_mut(2).zip(words) {
+        chunk.copy_from_slice(&w.to_le_bytes());
+    }
+}
+
+fn reversed(mut mac: [u8; 6]) -> [u8; 6] {
+    mac.reverse();
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::{self, ReportType};
+
+    fn le16(b: &[u8], at: usize) -> i16 {
+        i16::from_le_bytes([b[at], b[at + 1]])
+    }
+
+    #[test]
+    fn calibration_layout() {
+        let c = ImuCalibration {
+            gyro_bias: [1, 2, 3],
+            gyro_plus: [10, 20, 30],
+            gyro_minus: [-10, -20, -30],
+            gyro_speed_plus: 540,
+            gyro_speed_minus: 541,
+            accel_plus: [100, 200, 300],
+            accel_minus: [-100, -200, -300],
+        };
+        let r = ds4_calibration(&c);
+        let want = [1, 2, 3, 10, -10, 20, -20, 30, -30, 540, 541, 100, -100, 200, -200, 300, -300];
+        for (i, w) in want.iter().enumerate() {
+            assert_eq!(le16(&r, 1 + 2 * i), *w, "word {i}");
+        }
+        assert_eq!(&ds5_calibration(&c)[1..35], &r[1..35]);
+        assert_eq!(r[35..], [0, 0]);
+    }
+
+    #[test]
+    fn pairing_reverses_macs() {
+        let r = ds4_pairing([1, 2, 3, 4, 5, 6], [0xA, 0xB, 0xC, 0xD, 0xE, 0xF]);
+        assert_eq!(r, [0x12, 6, 5, 4, 3, 2, 1, 0x08, 0x25, 0x00, 0xF, 0xE, 0xD, 0xC, 0xB, 0xA]);
+        assert_eq!(ds5_pairing([1, 2, 3, 4, 5, 6])[..7], [0x09, 6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn firmware_versions() {
+        let r = ds4_firmware(&FirmwareInfo::DS4);
+        assert_eq!(&r[1..12], b"Sep 21 2018");
+        assert_eq!(u16::from_le_bytes([r[41], r[42]]), 0x0801);
+        let r = ds5_firmware(&FirmwareInfo::DS5);
+        assert_eq!(&r[12..20], b"10:17:23");
+        assert_eq!(u32::from_le_bytes(r[28..32].try_into().unwrap()), 0x0110_002A);
+        assert_eq!(u16::from_le_bytes([r[44], r[45]]), 0x0324);
+    }
+
+    #[test]
+    fn descriptors_declare_every_feature_report() {
+        for kind in [DeviceKind::DS4, DeviceKind::DS5, DeviceKind::DS5Edge] {
+            let cfg = FeatureConfig::for_kind(kind);
+            let d = descriptor::for_kind(kind);
+            let mut n = 0;
+            for id in 1..=u8::MAX {
+                let len = d.report_len(ReportType::Feature, id);
+                assert_eq!(get_feature(kind, id, &cfg).map(|r| r.len()), len, "{kind:?} {id:#x}");
+                n += len.is_some() as usize;
+            }
+            assert_eq!(n, 3);
+        }
+        assert_eq!(
+            get_feature(
+                DeviceKind::X360,
+                DS4_CALIBRATION_ID,
+                &FeatureConfig::for_kind(DeviceKind::X360)
+            ),
+            None
+        );
+    }
+}
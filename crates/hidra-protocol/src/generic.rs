@@ -0,0 +1,190 @@
+//! Spec of a `DeviceKind::Generic` pad: how many axes, buttons and hats it has,
+//! and what it calls itself. Its descriptor (`descriptor::generic`) and reports
+//! (`report::GenericReport`) are generated from this.
+
+use crate::buttons::GENERIC_ORDER;
+use crate::{Buttons, CreateError};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Axes come from, in order: LX, LY, RX, RY, LT, RT.
+pub const GENERIC_MAX_AXES: u8 = 6;
+pub const GENERIC_MIN_AXIS_BITS: u8 = 8;
+pub const GENERIC_MAX_AXIS_BITS: u8 = 16;
+/// The d-pad is the only hat source.
+pub const GENERIC_MAX_HATS: u8 = 1;
+/// UTF-16 code units in `GenericSpec::product`, including the terminating NUL.
+pub const GENERIC_PRODUCT_LEN: usize = 32;
+
+/// Trailer of `IOCTL_HIDRA_CREATE` for generic pads, see `CreateGenericIn`.
+/// Serialized with `product` as a plain string; missing fields take their defaults.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GenericSpec {
+    /// 0 leaves the choice to the driver.
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub axes: u8,
+    /// Resolution of every axis.
+    pub axis_bits: u8,
+    /// Buttons in `GENERIC_ORDER`, so Button 1 is `A`.
+    pub buttons: u8,
+    pub hats: u8,
+    /// NUL-terminated UTF-16; empty leaves the choice to the driver.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::utf16"))]
+    pub product: [u16; GENERIC_PRODUCT_LEN],
+}
+
+impl Default for GenericSpec {
+    /// Both sticks, both triggers, 16-bit, the d-pad as a hat and 16 buttons.
+    fn default() -> Self {
+        Self {
+            vendor_id: 0,
+            product_id: 0,
+            axes: GENERIC_MAX_AXES,
+            axis_bits: 16,
+            buttons: 16,
+            hats: 1,
+            product: [0; GENERIC_PRODUCT_LEN],
+        }
+    }
+}
+
+/// Which `GenericSpec` field a create request got wrong.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GenericField {
+    Axes,
+    AxisBits,
+    Buttons,
+    Hats,
+}
+
+impl GenericField {
+    pub fn name(self) -> &'static str {
+        match self {
+            GenericField::Axes => "generic axes",
+            GenericField::AxisBits => "generic axis bits",
+            GenericField::Buttons => "generic buttons",
+            GenericField::Hats => "generic hats",
+        }
+    }
+}
+
+impl GenericSpec {
+    /// With `product` set from `name`, cut to fit.
+    pub fn with_product(mut self, name: &str) -> Self {
+        self.product = [0; GENERIC_PRODUCT_LEN];
+        for (d, c) in self.product[..GENERIC_PRODUCT_LEN - 1].iter_mut().zip(name.encode_utf16()) {
+            *d = c;
+        }
+        self
+    }
+
+    /// The product string up to its NUL.
+    pub fn product_units(&self) -> &[u16] {
+        let end = self.product.iter().position(|c| *c == 0).unwrap_or(GENERIC_PRODUCT_LEN);
+        &self.product[..end]
+    }
+
+    /// Most buttons a pad with this many hats can have: the d-pad directions
+    /// are buttons only while there is no hat.
+    pub fn max_buttons(&self) -> u8 {
+        let end = match self.hats {
+            0 => GENERIC_ORDER.len(),
+            _ => GENERIC_ORDER.iter().position(|b| Buttons::DPAD.contains(*b)).unwrap_or(0),
+        };
+        end as u8
+    }
+
+    pub fn validate(&self) -> Result<(), CreateError> {
+        let bad = |field, value| Err(CreateError::InvalidGenericSpec { field, value });
+        if self.axes > GENERIC_MAX_AXES {
+            return bad(GenericField::Axes, self.axes);
+        }
+        if !(GENERIC_MIN_AXIS_BITS..=GENERIC_MAX_AXIS_BITS).contains(&self.axis_bits) {
+            return bad(GenericField::AxisBits, self.axis_bits);
+        }
+        if self.hats > GENERIC_MAX_HATS {
+            return bad(GenericField::Hats, self.hats);
+        }
+        if self.buttons > self.max_buttons() {
+            return bad(GenericField::Buttons, self.buttons);
+        }
+        // a pad with nothing to report would have an empty input report;
+        // buttons are what every layout can add
+        if self.report_bits() == 0 {
+            return bad(GenericField::Buttons, 0);
+        }
+        Ok(())
+    }
+
+    /// Input report size in bits: axes, then the hat nibble, then buttons.
+    pub fn report_bits(&self) -> usize {
+        self.axes as usize * self.axis_bits as usize
+            + self.hats as usize * 4
+            + self.buttons as usize
+    }
+
+    /// Input report size in bytes, padded to a whole byte.
+    pub fn report_len(&self) -> usize {
+        self.report_bits().div_ceil(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits() {
+        let spec = GenericSpec::default();
+        assert_eq!(spec.validate(), Ok(()));
+        assert_eq!(spec.report_len(), 15);
+        assert_eq!(spec.max_buttons(), 18);
+        assert_eq!(GenericSpec { hats: 0, ..spec }.max_buttons(), 24);
+
+        let bad = |spec: GenericSpec| match spec.validate() {
+            Err(CreateError::InvalidGenericSpec { field, .. }) => field,
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(bad(GenericSpec { axes: 7, ..spec }), GenericField::Axes);
+        assert_eq!(bad(GenericSpec { axis_bits: 7, ..spec }), GenericField::AxisBits);
+        assert_eq!(bad(GenericSpec { axis_bits: 17, ..spec }), GenericField::AxisBits);
+        assert_eq!(bad(GenericSpec { hats: 2, ..spec }), GenericField::Hats);
+        assert_eq!(bad(GenericSpec { buttons: 19, ..spec }), GenericField::Buttons);
+        assert_eq!(GenericSpec { buttons: 22, hats: 0, ..spec }.validate(), Ok(()));
+        assert_eq!(GenericSpec { buttons: 24, hats: 0, ..spec }.validate(), Ok(()));
+        let empty = GenericSpec { axes: 0, buttons: 0, hats: 0, ..spec };
+        assert_eq!(bad(empty), GenericField::Buttons);
+        assert_eq!(GenericSpec { hats: 1, ..empty }.validate(), Ok(()));
+    }
+
+    #[test]
+    fn product_string() {
+        let spec = GenericSpec::default().with_product("Flight Stick");
+        assert_eq!(String::from_utf16(spec.product_units()).unwrap(), "Flight Stick");
+        let long = GenericSpec::default().with_product(&"x".repeat(40));
+        assert_eq!(long.product_units().len(), GENERIC_PRODUCT_LEN - 1);
+        assert_eq!(long.product[GENERIC_PRODUCT_LEN - 1], 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_form() {
+        let spec: GenericSpec =
+            serde_json::from_str(r#"{"axes": 3, "product": "Throttle", "vendor_id": 4660}"#)
+                .unwrap();
+        assert_eq!(
+            spec,
+            GenericSpec { axes: 3, vendor_id: 0x1234, ..Default::default() }
+                .with_product("Throttle")
+        );
+        let json = serde_json::to_value(spec).unwrap();
+        assert_eq!(json["product"], "Throttle");
+        assert_eq!(json["axis_bits"], 16);
+    }
+}
@@ -0,0 +1,252 @@
+//! What a device tells the host about itself: USB IDs, strings and, for pads
+//! that pair over Bluetooth, a MAC. Defaults are derived from the kind and a
+//! slot number, so the same slot looks like the same controller every time.
+
+use crate::{DeviceConfig, DeviceKind, StateHeader};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub const IDENTITY_VERSION: u16 = 1;
+/// UTF-16 code units in each identity string, including the terminating NUL.
+pub const IDENTITY_STRING_LEN: usize = 32;
+
+/// pid.codes, the vendor ID shared by open-source hardware; used by kinds
+/// that don't imitate a real product.
+pub const HIDRA_VENDOR_ID: u16 = 0x1209;
+
+const _: [(); 208] = [(); size_of::<DeviceIdentity>()];
+
+/// Device identity, ABI v1. Trails the `IOCTL_HIDRA_CREATE` input, see `CreateIn`.
+/// A zero ID, an empty string or an all-zero MAC leaves that field to the
+/// defaults (`with_defaults`). Serialized with the strings as plain strings
+/// and the MAC as `aa:bb:cc:dd:ee:ff`; missing fields stay blank.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DeviceIdentity {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub header: StateHeader,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// `bcdDevice`.
+    pub version: u16,
+    /// Bluetooth address in display order, as in `FeatureConfig::mac`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::mac"))]
+    pub mac: [u8; 6],
+    /// NUL-terminated UTF-16, like the three strings below.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::utf16"))]
+    pub manufacturer: [u16; IDENTITY_STRING_LEN],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::utf16"))]
+    pub product: [u16; IDENTITY_STRING_LEN],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impl::utf16"))]
+    pub serial: [u16; IDENTITY_STRING_LEN],
+}
+
+impl Default for DeviceIdentity {
+    /// Every field blank.
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            vendor_id: 0,
+            product_id: 0,
+            version: 0,
+            mac: [0; 6],
+            manufacturer: [0; IDENTITY_STRING_LEN],
+            product: [0; IDENTITY_STRING_LEN],
+            serial: [0; IDENTITY_STRING_LEN],
+        }
+    }
+}
+
+impl DeviceIdentity {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<DeviceIdentity>() as u16, version: IDENTITY_VERSION };
+
+    /// The identity of pad number `slot` of `kind`. Real products keep their
+    /// vendor, product ID and strings; the MAC is locally administered,
+    /// `02:48:49` then the kind ID and the slot, and the serial is the MAC in hex.
+    pub fn for_kind(kind: DeviceKind, slot: u8) -> Self {
+        let (vendor_id, product_id, version, manufacturer, product) = match kind {
+            DeviceKind::X360 => (0x045E, 0x028E, 0x0114, "Microsoft", "Controller"),
+            DeviceKind::DS4 => (0x054C, 0x05C4, 0x0100, SONY, "Wireless Controller"),
+            DeviceKind::DS5 => (0x054C, 0x0CE6, 0x0100, SONY, "DualSense Wireless Controller"),
+            DeviceKind::DS5Edge => {
+                (0x054C, 0x0DF2, 0x0100, SONY, "DualSense Edge Wireless Controller")
+            }
+            DeviceKind::XboxOne => {
+                (0x045E, 0x0B13, 0x0509, "Microsoft", "Xbox Wireless Controller")
+            }
+            DeviceKind::XboxElite => {
+                (0x045E, 0x0B00, 0x0511, "Microsoft", "Xbox Elite Wireless Controller")
+            }
+            DeviceKind::SwitchPro => {
+                (0x057E, 0x2009, 0x0210, "Nintendo Co., Ltd.", "Pro Controller")
+            }
+            DeviceKind::Wheel => (HIDRA_VENDOR_ID, kind as u16, 0x0100, "HIDra", "HIDra Wheel"),
+            DeviceKind::Generic => (HIDRA_VENDOR_ID, kind as u16, 0x0100, "HIDra", "HIDra Gamepad"),
+            DeviceKind::Keyboard => {
+                (HIDRA_VENDOR_ID, kind as u16, 0x0100, "HIDra", "HIDra Keyboard")
+            }
+            DeviceKind::Mouse => (HIDRA_VENDOR_ID, kind as u16, 0x0100, "HIDra", "HIDra Mouse"),
+        };
+        let [hi, lo] = (kind as u16).to_be_bytes();
+        let mac = [0x02, 0x48, 0x49, hi, lo, slot];
+        let mut serial = [0; IDENTITY_STRING_LEN];
+        for (i, b) in mac.iter().enumerate() {
+            serial[2 * i] = hex(b >> 4);
+            serial[2 * i + 1] = hex(b & 0xF);
+        }
+        Self {
+            header: Self::HEADER,
+            vendor_id,
+            product_id,
+            version,
+            mac,
+            manufacturer: to_utf16(manufacturer),
+            product: to_utf16(product),
+            serial,
+        }
+    }
+
+    /// `self` with every blank field taken from `defaults`.
+    pub fn with_defaults(&self, defaults: &Self) -> Self {
+        let pick = |v: u16, d: u16| if v == 0 { d } else { v };
+        let text = |v: &[u16; IDENTITY_STRING_LEN], d| if v[0] == 0 { d } else { *v };
+        Self {
+            header: Self::HEADER,
+            vendor_id: pick(self.vendor_id, defaults.vendor_id),
+            product_id: pick(self.product_id, defaults.product_id),
+            version: pick(self.version, defaults.version),
+            mac: if self.mac == [0; 6] { defaults.mac } else { self.mac },
+            manufacturer: text(&self.manufacturer, defaults.manufacturer),
+            product: text(&self.product, defaults.product),
+            serial: text(&self.serial, defaults.serial),
+        }
+    }
+
+    pub fn with_manufacturer(mut self, s: &str) -> Self {
+        self.manufacturer = to_utf16(s);
+        self
+    }
+
+    pub fn with_product(mut self, s: &str) -> Self {
+        self.product = to_utf16(s);
+        self
+    }
+
+    pub fn with_serial(mut self, s: &str) -> Self {
+        self.serial = to_utf16(s);
+        self
+    }
+
+    /// Each string up to its NUL.
+    pub fn manufacturer_units(&self) -> &[u16] {
+        units(&self.manufacturer)
+    }
+
+    pub fn product_units(&self) -> &[u16] {
+        units(&self.product)
+    }
+
+    pub fn serial_units(&self) -> &[u16] {
+        units(&self.serial)
+    }
+}
+
+impl DeviceConfig {
+    /// Fill the blanks of `identity`: from `generic` for generic pads, then
+    /// from `DeviceIdentity::for_kind(kind, slot)`.
+    pub fn resolve_identity(&mut self, kind: DeviceKind) {
+        let mut defaults = DeviceIdentity::for_kind(kind, self.slot);
+        if kind == DeviceKind::Generic {
+            let spec = DeviceIdentity {
+                vendor_id: self.generic.vendor_id,
+                product_id: self.generic.product_id,
+                product: self.generic.product,
+                ..Default::default()
+            };
+            defaults = spec.with_defaults(&defaults);
+        }
+        self.identity = self.identity.with_defaults(&defaults);
+    }
+}
+
+const SONY: &str = "Sony Interactive Entertainment";
+
+fn hex(nibble: u8) -> u16 {
+    b"0123456789abcdef"[nibble as usize] as u16
+}
+
+/// `s` as NUL-terminated UTF-16, cut to fit.
+fn to_utf16(s: &str) -> [u16; IDENTITY_STRING_LEN] {
+    let mut out = [0; IDENTITY_STRING_LEN];
+    for (d, c) in out[..IDENTITY_STRING_LEN - 1].iter_mut().zip(s.encode_utf16()) {
+        *d = c;
+    }
+    out
+}
+
+fn units(s: &[u16; IDENTITY_STRING_LEN]) -> &[u16] {
+    &s[..s.iter().position(|c| *c == 0).unwrap_or(IDENTITY_STRING_LEN)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenericSpec;
+
+    fn text(units: &[u16]) -> String {
+        String::from_utf16(units).unwrap()
+    }
+
+    #[test]
+    fn defaults_are_stable_per_kind_and_slot() {
+        let a = DeviceIdentity::for_kind(DeviceKind::DS4, 0);
+        assert_eq!(a, DeviceIdentity::for_kind(DeviceKind::DS4, 0));
+        assert_eq!((a.vendor_id, a.product_id), (0x054C, 0x05C4));
+        assert_eq!(a.mac, [0x02, 0x48, 0x49, 0x05, 0xC4, 0x00]);
+        assert_eq!(text(a.serial_units()), "02484905c400");
+        assert_eq!(text(a.product_units()), "Wireless Controller");
+
+        let b = DeviceIdentity::for_kind(DeviceKind::DS4, 1);
+        assert_ne!(a.mac, b.mac);
+        assert_ne!(a.serial, b.serial);
+        assert_ne!(a.mac, DeviceIdentity::for_kind(DeviceKind::DS5, 0).mac);
+        let wheel = DeviceIdentity::for_kind(DeviceKind::Wheel, 0);
+        assert_eq!((wheel.vendor_id, wheel.product_id), (HIDRA_VENDOR_ID, 0x5748));
+        for kind in DeviceKind::ALL {
+            let id = DeviceIdentity::for_kind(kind, 3);
+            assert!(id.vendor_id != 0 && id.product_id != 0, "{kind:?}");
+            assert!(!id.manufacturer_units().is_empty() && !id.product_units().is_empty());
+            assert_eq!(id.with_defaults(&DeviceIdentity::default()), id);
+        }
+    }
+
+    #[test]
+    fn blanks_take_defaults() {
+        let mine = DeviceIdentity { product_id: 0x09CC, ..Default::default() }.with_serial("S-1");
+        let id = mine.with_defaults(&DeviceIdentity::for_kind(DeviceKind::DS4, 2));
+        assert_eq!((id.vendor_id, id.product_id), (0x054C, 0x09CC));
+        assert_eq!(text(id.serial_units()), "S-1");
+        assert_eq!(id.mac[5], 2);
+        let long = DeviceIdentity::default().with_product(&"x".repeat(40));
+        assert_eq!(long.product_units().len(), IDENTITY_STRING_LEN - 1);
+    }
+
+    #[test]
+    fn generic_spec_overrides_kind_defaults() {
+        let mut cfg = DeviceConfig {
+            generic: GenericSpec { vendor_id: 0x1234, ..Default::default() }.with_product("Stick"),
+            slot: 1,
+            ..Default::default()
+        };
+        cfg.identity.product_id = 0x5678;
+        cfg.resolve_identity(DeviceKind::Generic);
+        let id = cfg.identity;
+        assert_eq!((id.vendor_id, id.product_id), (0x1234, 0x5678));
+        assert_eq!(text(id.product_units()), "Stick");
+        assert_eq!(text(id.manufacturer_units()), "HIDra");
+        assert_eq!(id.mac, DeviceIdentity::for_kind(DeviceKind::Generic, 1).mac);
+    }
+}
@@ -0,0 +1,290 @@
+//! Virtual keyboard state: pressed keys as HID Keyboard/Keypad page usages,
+//! plus the modifier byte and the LEDs the host drives.
+
+use crate::StateHeader;
+
+bitflags::bitflags! {
+    /// The boot report's modifier byte, usages `0xE0..=0xE7` in order.
+    /// Serialized like `Buttons`.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct Modifiers: u8 {
+        const LEFT_CTRL   = 1 << 0;
+        const LEFT_SHIFT  = 1 << 1;
+        const LEFT_ALT    = 1 << 2;
+        const LEFT_GUI    = 1 << 3;
+        const RIGHT_CTRL  = 1 << 4;
+        const RIGHT_SHIFT = 1 << 5;
+        const RIGHT_ALT   = 1 << 6;
+        const RIGHT_GUI   = 1 << 7;
+    }
+}
+
+bitflags::bitflags! {
+    /// Keyboard LED output report, LED page usages 1..=5.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct KeyboardLeds: u8 {
+        const NUM_LOCK    = 1 << 0;
+        const CAPS_LOCK   = 1 << 1;
+        const SCROLL_LOCK = 1 << 2;
+        const COMPOSE     = 1 << 3;
+        const KANA        = 1 << 4;
+    }
+}
+
+/// First and last usage a report can carry as a key; below are error codes,
+/// above are the modifiers.
+pub const KEY_MIN: u8 = 0x04;
+pub const KEY_MAX: u8 = 0xDF;
+/// Usage of `Modifiers::LEFT_CTRL`; the other modifiers follow bit by bit.
+pub const MODIFIER_MIN: u8 = 0xE0;
+/// Reported in every key slot of a boot report when more keys are down than fit.
+pub const ERROR_ROLL_OVER: u8 = 0x01;
+
+/// Names of usages `KEY_MIN..` in order, as in the HID usage tables with the
+/// `Keyboard`/`Keypad` prefix dropped (`kp_` for the keypad).
+const KEY_NAMES: [&str; 98] = [
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "0",
+    "enter",
+    "escape",
+    "backspace",
+    "tab",
+    "space",
+    "minus",
+    "equal",
+    "left_bracket",
+    "right_bracket",
+    "backslash",
+    "non_us_hash",
+    "semicolon",
+    "quote",
+    "grave",
+    "comma",
+    "period",
+    "slash",
+    "caps_lock",
+    "f1",
+    "f2",
+    "f3",
+    "f4",
+    "f5",
+    "f6",
+    "f7",
+    "f8",
+    "f9",
+    "f10",
+    "f11",
+    "f12",
+    "print_screen",
+    "scroll_lock",
+    "pause",
+    "insert",
+    "home",
+    "page_up",
+    "delete",
+    "end",
+    "page_down",
+    "right",
+    "left",
+    "down",
+    "up",
+    "num_lock",
+    "kp_slash",
+    "kp_asterisk",
+    "kp_minus",
+    "kp_plus",
+    "kp_enter",
+    "kp_1",
+    "kp_2",
+    "kp_3",
+    "kp_4",
+    "kp_5",
+    "kp_6",
+    "kp_7",
+    "kp_8",
+    "kp_9",
+    "kp_0",
+    "kp_period",
+    "non_us_backslash",
+    "application",
+];
+
+/// Names of usages `MODIFIER_MIN..`, matching the `Modifiers` flags.
+const MODIFIER_NAMES: [&str; 8] = [
+    "left_ctrl",
+    "left_shift",
+    "left_alt",
+    "left_gui",
+    "right_ctrl",
+    "right_shift",
+    "right_alt",
+    "right_gui",
+];
+
+/// Name of a key or modifier usage, e.g. `a`, `enter`, `left_shift`.
+pub fn key_name(usage: u8) -> Option<&'static str> {
+    match usage.checked_sub(MODIFIER_MIN) {
+        Some(i) => MODIFIER_NAMES.get(i as usize).copied(),
+        None => KEY_NAMES.get(usage.checked_sub(KEY_MIN)? as usize).copied(),
+    }
+}
+
+/// Usage of a key or modifier by name, any case; the inverse of `key_name`.
+pub fn key_from_name(name: &str) -> Option<u8> {
+    let find = |names: &[&str]| names.iter().position(|n| n.eq_ignore_ascii_case(name));
+    find(&KEY_NAMES)
+        .map(|i| KEY_MIN + i as u8)
+        .or_else(|| find(&MODIFIER_NAMES).map(|i| MODIFIER_MIN + i as u8))
+}
+
+/// The modifier flag a usage stands for, if it is one.
+fn modifier(usage: u8) -> Option<Modifiers> {
+    let i = usage.checked_sub(MODIFIER_MIN).filter(|i| *i < 8)?;
+    Some(Modifiers::from_bits_retain(1 << i))
+}
+
+pub const KEYBOARD_STATE_VERSION: u16 = 1;
+
+const _: [(); 40] = [(); size_of::<KeyboardState>()];
+
+/// Keyboard state, ABI v1. Every key that is down, not just the ones a report
+/// has room for; the packers pick what fits.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeyboardState {
+    pub header: StateHeader,
+    pub modifiers: Modifiers,
+    pub reserved: [u8; 3],
+    /// Bit `u % 8` of byte `u / 8` is set while usage `u` is down. Usages
+    /// outside `KEY_MIN..=KEY_MAX` are ignored.
+    pub keys: [u8; 32],
+}
+
+impl KeyboardState {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<KeyboardState>() as u16, version: KEYBOARD_STATE_VERSION };
+
+    /// State with `usages` down; modifier usages set `modifiers`.
+    pub fn with_keys(usages: &[u8]) -> Self {
+        let mut s = Self::default();
+        usages.iter().for_each(|u| s.press(*u));
+        s
+    }
+
+    pub fn press(&mut self, usage: u8) {
+        self.set(usage, true);
+    }
+
+    pub fn release(&mut self, usage: u8) {
+        self.set(usage, false);
+    }
+
+    pub fn set(&mut self, usage: u8, down: bool) {
+        if let Some(m) = modifier(usage) {
+            self.modifiers.set(m, down);
+            return;
+        }
+        let (byte, bit) = ((usage / 8) as usize, 1 << (usage % 8));
+        if down {
+            self.keys[byte] |= bit;
+        } else {
+            self.keys[byte] &= !bit;
+        }
+    }
+
+    pub fn is_down(&self, usage: u8) -> bool {
+        if let Some(m) = modifier(usage) {
+            return self.modifiers.contains(m);
+        }
+        self.keys[(usage / 8) as usize] & (1 << (usage % 8)) != 0
+    }
+
+    /// Keys that are down, lowest usage first, modifiers excluded.
+    pub fn pressed(&self) -> impl Iterator<Item = u8> + '_ {
+        (KEY_MIN..=KEY_MAX).filter(|u| self.is_down(*u))
+    }
+}
+
+impl Default for KeyboardState {
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            modifiers: Modifiers::empty(),
+            reserved: [0; 3],
+            keys: [0; 32],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(key_name(0x04), Some("a"));
+        assert_eq!(key_name(0x27), Some("0"));
+        assert_eq!(key_name(0x3A), Some("f1"));
+        assert_eq!(key_name(0x65), Some("application"));
+        assert_eq!(key_name(0x66), None);
+        assert_eq!(key_name(ERROR_ROLL_OVER), None);
+        assert_eq!(key_name(0xE1), Some("left_shift"));
+        assert_eq!(key_from_name("Enter"), Some(0x28));
+        assert_eq!(key_from_name("right_gui"), Some(0xE7));
+        assert_eq!(key_from_name("hyper"), None);
+        for u in (KEY_MIN..=0x65).chain(MODIFIER_MIN..=0xE7) {
+            assert_eq!(key_from_name(key_name(u).unwrap()), Some(u));
+        }
+    }
+
+    #[test]
+    fn press_and_release() {
+        let mut s = KeyboardState::with_keys(&[0x04, 0xE1, 0x28, 0xDF, 0xFF]);
+        assert_eq!(s.modifiers, Modifiers::LEFT_SHIFT);
+        assert!(s.is_down(0xFF));
+        assert_eq!(s.pressed().collect::<Vec<_>>(), [0x04, 0x28, 0xDF]);
+        s.release(0x04);
+        s.release(0xE1);
+        s.press(ERROR_ROLL_OVER);
+        assert!(s.is_down(ERROR_ROLL_OVER));
+        assert_eq!(s.pressed().collect::<Vec<_>>(), [0x28, 0xDF]);
+        assert_eq!(s.modifiers, Modifiers::empty());
+        assert_eq!(s.header, KeyboardState::HEADER);
+    }
+}
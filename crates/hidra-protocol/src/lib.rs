@@ -1,66 +1,551 @@
+#![cfg_attr(not(test), no_std)]
 #![deny(warnings)]
 #![allow(unused)]
 //! ABI-stable protocol: device kinds, flags, message envelopes.
+//! `no_std`; needs `alloc` only for descriptors, output decoding and the C header.
 
+extern crate alloc;
+
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod abi;
+pub mod buttons;
+pub mod caps;
+pub mod codec;
+pub mod descriptor;
+pub mod error;
+pub mod feature;
+pub mod generic;
+pub mod identity;
+pub mod keyboard;
+pub mod mouse;
+pub mod output;
+pub mod pid;
+pub mod process;
+pub mod range;
 pub mod report;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod switch_pro;
+pub mod touch;
+pub mod transport;
+pub mod xusb;
+
+pub use buttons::Buttons;
+pub use caps::{Capabilities, CreateError};
+pub use codec::Wire;
+pub use error::Error;
+pub use generic::GenericSpec;
+pub use identity::DeviceIdentity;
+pub use keyboard::{KeyboardState, Modifiers};
+pub use mouse::{MouseButtons, MouseState};
+pub use process::{AxisPipeline, PadPipeline};
+pub use range::{InputRanges, StickRange, TriggerRange};
+pub use touch::{Gesture, Touchpad};
+pub use transport::Transport;
+pub use xusb::XusbSubtype;
 
+/// Serialized by `name`; deserialized from a name (any case) or the numeric ID.
 #[repr(u16)]
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum DeviceKind {
     X360 = 0x0366,
     DS4 = 0x05C4,
     DS5 = 0x0CE6,
+    XboxOne = 0x0B13,
+    SwitchPro = 0x2009,
+    /// HIDra's own ID; the device's VID/PID come from `GenericSpec`.
+    Generic = 0x4844,
+    /// HIDra's own ID; a racing wheel with PID force feedback, see `pid`.
+    Wheel = 0x5748,
+    DS5Edge = 0x0DF2,
+    XboxElite = 0x0B00,
+    /// HIDra's own IDs; boot-protocol keyboard and mouse, updated with their own states.
+    Keyboard = 0x4B42,
+    Mouse = 0x4D53,
+}
+
+impl DeviceKind {
+    pub const ALL: [DeviceKind; 11] = [
+        DeviceKind::X360,
+        DeviceKind::DS4,
+        DeviceKind::DS5,
+        DeviceKind::XboxOne,
+        DeviceKind::SwitchPro,
+        DeviceKind::Generic,
+        DeviceKind::Wheel,
+        DeviceKind::DS5Edge,
+        DeviceKind::XboxElite,
+        DeviceKind::Keyboard,
+        DeviceKind::Mouse,
+    ];
+
+    /// This kind's bit in `VersionInfo::kinds`. Bits are append-only.
+    pub const fn bit(self) -> u32 {
+        match self {
+            DeviceKind::X360 => 1 << 0,
+            DeviceKind::DS4 => 1 << 1,
+            DeviceKind::DS5 => 1 << 2,
+            DeviceKind::XboxOne => 1 << 3,
+            DeviceKind::SwitchPro => 1 << 4,
+            DeviceKind::Generic => 1 << 5,
+            DeviceKind::Wheel => 1 << 6,
+            DeviceKind::DS5Edge => 1 << 7,
+            DeviceKind::XboxElite => 1 << 8,
+            DeviceKind::Keyboard => 1 << 9,
+            DeviceKind::Mouse => 1 << 10,
+        }
+    }
+
+    /// Stable wire name.
+    pub const fn name(self) -> &'static str {
+        match self {
+            DeviceKind::X360 => "x360",
+            DeviceKind::DS4 => "ds4",
+            DeviceKind::DS5 => "ds5",
+            DeviceKind::XboxOne => "xbox_one",
+            DeviceKind::SwitchPro => "switch_pro",
+            DeviceKind::Generic => "generic",
+            DeviceKind::Wheel => "wheel",
+            DeviceKind::DS5Edge => "ds5_edge",
+            DeviceKind::XboxElite => "xbox_elite",
+            DeviceKind::Keyboard => "keyboard",
+            DeviceKind::Mouse => "mouse",
+        }
+    }
+
+    /// Whether devices of this kind take `PadState` updates.
+    pub const fn is_pad(self) -> bool {
+        !matches!(self, DeviceKind::Keyboard | DeviceKind::Mouse)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn from_id(id: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| *k as u16 == id)
+    }
 }
 
 bitflags::bitflags! {
+    /// Serialized as a list of lowercase names; a bare number is also accepted.
     #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
     pub struct Features: u32 {
         const RUMBLE  = 1 << 0;
         const TOUCH   = 1 << 1;
         const GYRO    = 1 << 2;
         const LED     = 1 << 3;
+        /// Keyboards: report every key down instead of the boot report's six.
+        const NKRO    = 1 << 4;
     }
 }
 
-const _: [(); 14] = [(); size_of::<PadState>()];
-const _: [(); 2] = [(); align_of::<PadState>()];
+/// Create-time settings that shape how a device's reports are packed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DeviceConfig {
+    pub features: Features,
+    pub ranges: InputRanges,
+    pub transport: Transport,
+    /// Layout of a `DeviceKind::Generic` pad; ignored by every other kind.
+    pub generic: GenericSpec,
+    /// What a `DeviceKind::X360` presents as; every other kind is a gamepad.
+    pub subtype: XusbSubtype,
+    /// Picks the default identity; give each device of a kind its own slot.
+    pub slot: u8,
+    /// Blank fields are filled by `resolve_identity` before the device is created.
+    pub identity: DeviceIdentity,
+}
 
+impl DeviceConfig {
+    /// `s` as the packers expect it: gated to `features`, sticks at the full i16
+    /// range and triggers at full u16 scale.
+    pub fn prepare(&self, s: &PadState) -> PadState {
+        let mut s = s.gated(self.features);
+        let (stick, trigger) = (self.ranges.stick, self.ranges.trigger);
+        s.lx = stick.to_full(s.lx);
+        s.ly = stick.to_full(s.ly);
+        s.rx = stick.to_full(s.rx);
+        s.ry = stick.to_full(s.ry);
+        s.lt = trigger.to_full(s.lt);
+        s.rt = trigger.to_full(s.rt);
+        s
+    }
+}
+
+/// Leading header of every versioned ABI struct.
+/// `size` is the full struct size in bytes, so a reader can skip fields it doesn't know.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StateHeader {
+    pub size: u16,
+    pub version: u16,
+}
+
+pub const PAD_STATE_VERSION: u16 = 3;
+/// `PadStateV2`: same size as v3, buttons split into XUSB and extra bits.
+pub const PAD_STATE_V2_VERSION: u16 = 2;
+
+const _: [(); 48] = [(); size_of::<PadState>()];
+const _: [(); 4] = [(); align_of::<PadState>()];
+
+/// Pad state, ABI v3.
+/// `touch` is only meaningful with `Features::TOUCH`, `motion` only with `Features::GYRO`.
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PadState {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub header: StateHeader,
+    pub buttons: Buttons,
+    pub lx: i16,
+    pub ly: i16,
+    pub rx: i16,
+    pub ry: i16,
+    pub lt: u16,
+    pub rt: u16,
+    pub touch: [TouchPoint; 2],
+    pub motion: Motion,
+    pub battery: Battery,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reserved: [u8; 2],
+}
+
+impl PadState {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<PadState>() as u16, version: PAD_STATE_VERSION };
+
+    /// Copy with the sections of features the device lacks cleared.
+    pub fn gated(&self, features: Features) -> Self {
+        let mut s = *self;
+        if !features.contains(Features::TOUCH) {
+            s.touch = [TouchPoint::default(); 2];
+        }
+        if !features.contains(Features::GYRO) {
+            s.motion = Motion::default();
+        }
+        s
+    }
+}
+
+impl Default for PadState {
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            buttons: Buttons::empty(),
+            lx: 0,
+            ly: 0,
+            rx: 0,
+            ry: 0,
+            lt: 0,
+            rt: 0,
+            touch: [TouchPoint::default(); 2],
+            motion: Motion::default(),
+            battery: Battery::default(),
+            reserved: [0; 2],
+        }
+    }
+}
+
+impl From<PadStateV2> for PadState {
+    fn from(s: PadStateV2) -> Self {
+        let mut buttons = Buttons::from_xusb(s.buttons);
+        buttons.set(Buttons::TOUCHPAD, s.ext_buttons & PadStateV2::EXT_TOUCHPAD != 0);
+        buttons.set(Buttons::MUTE, s.ext_buttons & PadStateV2::EXT_MUTE != 0);
+        Self {
+            buttons,
+            lx: s.lx,
+            ly: s.ly,
+            rx: s.rx,
+            ry: s.ry,
+            lt: s.lt,
+            rt: s.rt,
+            touch: s.touch,
+            motion: s.motion,
+            battery: s.battery,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<PadStateV1> for PadState {
+    fn from(s: PadStateV1) -> Self {
+        Self {
+            buttons: Buttons::from_xusb(s.buttons),
+            lx: s.lx,
+            ly: s.ly,
+            rx: s.rx,
+            ry: s.ry,
+            lt: s.lt,
+            rt: s.rt,
+            ..Default::default()
+        }
+    }
+}
+
+/// One touchpad contact. `x`/`y` are normalized to the full u16 range,
+/// origin top-left; packers scale them to the pad's resolution.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TouchPoint {
+    /// 1 while the finger is down.
+    pub down: u8,
+    /// Tracking ID, stable for the lifetime of one contact (7 bits on the wire).
+    pub id: u8,
+    pub x: u16,
+    pub y: u16,
+}
+
+/// One IMU sample in DualShock/DualSense raw units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Motion {
+    pub gyro: [i16; 3],
+    pub accel: [i16; 3],
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ChargeState {
+    Discharging = 0,
+    Charging = 1,
+    #[default]
+    Full = 2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Battery {
+    /// Percent, 0..=100.
+    pub level: u8,
+    /// `ChargeState` discriminant; unknown values read as discharging.
+    pub charge: u8,
+}
+
+impl Battery {
+    pub fn charge_state(&self) -> ChargeState {
+        match self.charge {
+            1 => ChargeState::Charging,
+            2 => ChargeState::Full,
+            _ => ChargeState::Discharging,
+        }
+    }
+}
+
+impl Default for Battery {
+    fn default() -> Self {
+        Self { level: 100, charge: ChargeState::Full as u8 }
+    }
+}
+
+const _: [(); 48] = [(); size_of::<PadStateV2>()];
+const _: [(); 2] = [(); align_of::<PadStateV2>()];
+
+/// Pad state, ABI v2: `buttons` in XUSB bit positions and the DualShock extras
+/// in `ext_buttons`. Still accepted by the driver; told apart from v3 by
+/// `header.version`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PadStateV2 {
+    pub header: StateHeader,
     pub buttons: u16,
+    pub ext_buttons: u16,
     pub lx: i16,
     pub ly: i16,
     pub rx: i16,
     pub ry: i16,
     pub lt: u16,
     pub rt: u16,
+    pub touch: [TouchPoint; 2],
+    pub motion: Motion,
+    pub battery: Battery,
+    pub reserved: [u8; 2],
+}
+
+impl PadStateV2 {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<PadStateV2>() as u16, version: PAD_STATE_V2_VERSION };
+    pub const EXT_TOUCHPAD: u16 = 1 << 0;
+    pub const EXT_MUTE: u16 = 1 << 1;
 }
 
+impl Default for PadStateV2 {
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            buttons: 0,
+            ext_buttons: 0,
+            lx: 0,
+            ly: 0,
+            rx: 0,
+            ry: 0,
+            lt: 0,
+            rt: 0,
+            touch: [TouchPoint::default(); 2],
+            motion: Motion::default(),
+            battery: Battery::default(),
+            reserved: [0; 2],
+        }
+    }
+}
+
+const _: [(); 14] = [(); size_of::<PadStateV1>()];
+const _: [(); 2] = [(); align_of::<PadStateV1>()];
+
+/// Pad state, ABI v1 (no header). Still accepted by the driver for old clients.
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PadStateV1 {
+    pub buttons: u16,
+    pub lx: i16,
+    pub ly: i16,
+    pub rx: i16,
+    pub ry: i16,
+    pub lt: u16,
+    pub rt: u16,
+}
+
+pub const VERSION_INFO_VERSION: u16 = 1;
+
+/// Output of `IOCTL_HIDRA_GET_VERSION`: what the driver speaks and can emulate.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VersionInfo {
+    pub header: StateHeader,
+    pub abi_version: u32,
+    /// `DeviceKind::bit` of every supported kind.
+    pub kinds: u32,
+    /// `Features` bits the driver can honour.
+    pub features: u32,
+}
+
+impl VersionInfo {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<VersionInfo>() as u16, version: VERSION_INFO_VERSION };
+
+    /// Info for this build of the ABI.
+    pub fn new(kinds: &[DeviceKind], features: Features) -> Self {
+        Self {
+            header: Self::HEADER,
+            abi_version: HIDRA_FFI_ABI_VERSION,
+            kinds: kinds.iter().fold(0, |m, k| m | k.bit()),
+            features: features.bits(),
+        }
+    }
+
+    /// Fails unless the peer speaks exactly our ABI version.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.abi_version != HIDRA_FFI_ABI_VERSION {
+            return Err(Error::AbiVersion {
+                expected: HIDRA_FFI_ABI_VERSION,
+                got: self.abi_version,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn supports(&self, kind: DeviceKind) -> bool {
+        self.kinds & kind.bit() != 0
+    }
+
+    pub fn supported_kinds(&self) -> impl Iterator<Item = DeviceKind> + '_ {
+        DeviceKind::ALL.into_iter().filter(|k| self.supports(*k))
+    }
+
+    pub fn features(&self) -> Features {
+        Features::from_bits_truncate(self.features)
+    }
+}
+
+/// IOCTL structs spell out their padding as `reserved*` fields, always zero,
+/// so the `Wire` encoding is the struct's exact memory image.
+///
+/// `IOCTL_HIDRA_CREATE` input may be followed by a `DeviceIdentity` (after the
+/// `GenericSpec` for generic pads); without one the driver picks the identity.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct CreateIn {
     pub kind: DeviceKind,
+    pub reserved0: [u8; 2],
     pub features: u32,
+    pub ranges: InputRanges,
+    pub transport: Transport,
+    /// Was `reserved1`; clients that still send 0 get a gamepad.
+    pub subtype: XusbSubtype,
+}
+
+impl CreateIn {
+    pub fn new(kind: DeviceKind, config: &DeviceConfig) -> Self {
+        Self {
+            kind,
+            reserved0: [0; 2],
+            features: config.features.bits(),
+            ranges: config.ranges,
+            transport: config.transport,
+            subtype: config.subtype,
+        }
+    }
+}
+
+/// `IOCTL_HIDRA_CREATE` input for `DeviceKind::Generic`. The driver tells it
+/// from a plain `CreateIn` by its length.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CreateGenericIn {
+    pub create: CreateIn,
+    pub spec: GenericSpec,
+}
+
+impl CreateGenericIn {
+    pub fn new(config: &DeviceConfig) -> Self {
+        Self { create: CreateIn::new(DeviceKind::Generic, config), spec: config.generic }
+    }
 }
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct CreateOut {
     pub handle: u64,
 }
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct DestroyIn {
     pub handle: u64,
 }
+/// The driver tells v1 and v2+ updates apart by input length; v2+ then
+/// trusts `state.header.size` and ignores trailing fields it doesn't know.
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct UpdateIn {
     pub handle: u64,
     pub state: PadState,
 }
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdateInV1 {
+    pub handle: u64,
+    pub state: PadStateV1,
+    pub reserved: [u8; 2],
+}
+/// `IOCTL_HIDRA_UPDATE_KEYBOARD` input; versioned like `UpdateIn`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdateKeyboardIn {
+    pub handle: u64,
+    pub state: KeyboardState,
+}
+/// `IOCTL_HIDRA_UPDATE_MOUSE` input; versioned like `UpdateIn`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdateMouseIn {
+    pub handle: u64,
+    pub state: MouseState,
+}
 
 const FILE_DEVICE_UNKNOWN: u32 = 0x0000_0022;
 const FILE_ANY_ACCESS: u32 = 0x0000;
@@ -85,9 +570,21 @@ pub const IOCTL_HIDRA_UPDATE: u32 =
     ctl_code(HIDRA_DEVICE_TYPE, HIDRA_IOCTL_BASE + 1, METHOD_BUFFERED, FILE_WRITE_ACCESS);
 pub const IOCTL_HIDRA_DESTROY: u32 =
     ctl_code(HIDRA_DEVICE_TYPE, HIDRA_IOCTL_BASE + 2, METHOD_BUFFERED, FILE_WRITE_ACCESS);
+pub const IOCTL_HIDRA_GET_VERSION: u32 =
+    ctl_code(HIDRA_DEVICE_TYPE, HIDRA_IOCTL_BASE + 3, METHOD_BUFFERED, FILE_ANY_ACCESS);
+pub const IOCTL_HIDRA_UPDATE_KEYBOARD: u32 =
+    ctl_code(HIDRA_DEVICE_TYPE, HIDRA_IOCTL_BASE + 4, METHOD_BUFFERED, FILE_WRITE_ACCESS);
+pub const IOCTL_HIDRA_UPDATE_MOUSE: u32 =
+    ctl_code(HIDRA_DEVICE_TYPE, HIDRA_IOCTL_BASE + 5, METHOD_BUFFERED, FILE_WRITE_ACCESS);
+
+/// HIDraBus device interface GUID as a plain integer, usable without the Windows crates.
+pub const HIDRA_INTERFACE_ID: u128 = 0x468ADE0B_0432_4680_B57B_1850B33C7D9B;
 
 #[cfg(feature = "backend-driver")]
 pub const HIDRA_INTERFACE_GUID: windows::core::GUID =
-    windows::core::GUID::from_u128(0x468ADE0B_0432_4680_B57B_1850B33C7D9B);
+    windows::core::GUID::from_u128(HIDRA_INTERFACE_ID);
 
-pub const HIDRA_FFI_ABI_VERSION: u32 = 1;
+/// Version of the IOCTL and broker ABI; peers must match exactly.
+/// v2: 32-bit pad buttons (pad state v3), ranges, transport and XUSB subtype in
+/// `CreateIn`, the trailing `DeviceIdentity` and `CreateGenericIn`.
+pub const HIDRA_FFI_ABI_VERSION: u32 = 2;
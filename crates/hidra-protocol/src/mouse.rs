@@ -0,0 +1,68 @@
+//! Virtual mouse state: held buttons plus relative motion, wheel and
+//! horizontal pan accumulated since the last update.
+
+use crate::StateHeader;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+bitflags::bitflags! {
+    /// Button page usages 1..=5. Serialized like `Buttons`.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct MouseButtons: u8 {
+        const LEFT    = 1 << 0;
+        const RIGHT   = 1 << 1;
+        const MIDDLE  = 1 << 2;
+        const BACK    = 1 << 3;
+        const FORWARD = 1 << 4;
+    }
+}
+
+pub const MOUSE_STATE_VERSION: u16 = 1;
+
+const _: [(); 16] = [(); size_of::<MouseState>()];
+
+/// Mouse state, ABI v1. Motion is relative and consumed by the packer, which
+/// splits anything larger than one report carries across several reports.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MouseState {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub header: StateHeader,
+    pub buttons: MouseButtons,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reserved: [u8; 3],
+    /// Counts, positive right/down.
+    pub dx: i16,
+    pub dy: i16,
+    /// Wheel detents, positive away from the user.
+    pub wheel: i16,
+    /// Horizontal scroll detents, positive right.
+    pub pan: i16,
+}
+
+impl MouseState {
+    pub const HEADER: StateHeader =
+        StateHeader { size: size_of::<MouseState>() as u16, version: MOUSE_STATE_VERSION };
+
+    /// True once every relative axis has been consumed.
+    pub fn is_still(&self) -> bool {
+        (self.dx, self.dy, self.wheel, self.pan) == (0, 0, 0, 0)
+    }
+}
+
+impl Default for MouseState {
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            buttons: MouseButtons::empty(),
+            reserved: [0; 3],
+            dx: 0,
+            dy: 0,
+            wheel: 0,
+            pan: 0,
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//! Host-to-device output reports: rumble, lights, adaptive triggers, force feedback
+//! and keyboard LEDs.
+//! Each decoder turns one raw report into the `OutputEvent`s it carries.
+
+use crate::DeviceKind;
+use crate::error::{Error, Result};
+use crate::keyboard::KeyboardLeds;
+use crate::pid::FfbCommand;
+use crate::transport::{self, BT_OUTPUT_SEED, BT_REPORT_LEN};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "event", rename_all = "snake_case"))]
+pub enum OutputEvent {
+    /// Motor strengths, 0..255. `strong` is the left/low-frequency motor.
+    Rumble {
+        strong: u8,
+        weak: u8,
+    },
+    /// XUSB ring-of-light animation (`XINPUT_LED_*`, 0..=13).
+    XusbLed {
+        pattern: u8,
+    },
+    Lightbar {
+        r: u8,
+        g: u8,
+        b: u8,
+    },
+    /// DS4 lightbar blinking, both durations in 10ms units. Zero/zero stops it.
+    LightbarFlash {
+        on: u8,
+        off: u8,
+    },
+    /// DS5 player indicator, one bit per LED (5 LEDs, left to right).
+    PlayerLeds {
+        mask: u8,
+    },
+    MicLed {
+        state: MicLed,
+    },
+    TriggerEffect {
+        side: TriggerSide,
+        effect: TriggerEffect,
+    },
+    /// Xbox One impulse trigger motors, 0..255.
+    TriggerRumble {
+        left: u8,
+        right: u8,
+    },
+    /// Racing wheel PID report, see `pid`.
+    ForceFeedback {
+        command: FfbCommand,
+    },
+    /// Lock and status LEDs the host wants lit on a keyboard.
+    KeyboardLeds {
+        leds: KeyboardLeds,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MicLed {
+    Off,
+    On,
+    Pulse,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TriggerSide {
+    Left,
+    Right,
+}
+
+/// Raw DS5 adaptive trigger block: effect mode followed by its parameters.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TriggerEffect {
+    pub mode: u8,
+    pub params: [u8; 10],
+}
+
+impl TriggerEffect {
+    fn from_block(b: &[u8]) -> Self {
+        let mut params = [0u8; 10];
+        params.copy_from_slice(&b[1..11]);
+        Self { mode: b[0], params }
+    }
+}
+
+fn check(data: &[u8], id: Option<u8>, len: usize) -> Result<()> {
+    if data.len() < len {
+        return Err(Error::Length { expected: len, got: data.len() });
+    }
+    match id {
+        Some(id) if data[0] != id => Err(Error::ReportId(data[0])),
+        _ => Ok(()),
+    }
+}
+
+/// Decode an output report for `kind`. DS4/DS5 reports are told apart from their
+/// Bluetooth framing by report ID. Generic pads and mice declare no output
+/// reports, so anything sent to one is refused.
+pub fn decode(kind: DeviceKind, data: &[u8]) -> Result<Vec<OutputEvent>> {
+    match (kind, data.first()) {
+        (DeviceKind::X360, _) => decode_xusb(data),
+        (DeviceKind::DS4, Some(&DS4_OUTPUT_BT_ID)) => decode_ds4_bt(data),
+        (DeviceKind::DS4, _) => decode_ds4(data),
+        (DeviceKind::DS5 | DeviceKind::DS5Edge, Some(&DS5_OUTPUT_BT_ID)) => decode_ds5_bt(data),
+        (DeviceKind::DS5 | DeviceKind::DS5Edge, _) => decode_ds5(data),
+        (DeviceKind::XboxOne | DeviceKind::XboxElite, _) => decode_xbox_one(data),
+        (DeviceKind::SwitchPro, _) => decode_switch_pro(data),
+        (DeviceKind::Wheel, _) => {
+            Ok(vec![OutputEvent::ForceFeedback { command: crate::pid::decode(data)? }])
+        }
+        (DeviceKind::Keyboard, _) => decode_keyboard(data),
+        (DeviceKind::Generic | DeviceKind::Mouse, Some(&id)) => Err(Error::ReportId(id)),
+        (DeviceKind::Generic | DeviceKind::Mouse, None) => {
+            Err(Error::Length { expected: 1, got: 0 })
+        }
+    }
+}
+
+/// Check a Bluetooth output report's ID, length and CRC, then rebuild the USB
+/// report whose body starts at `body`.
+fn unwrap_bt<const N: usize>(data: &[u8], bt_id: u8, usb_id: u8, body: usize) -> Result<[u8; N]> {
+    check(data, Some(bt_id), BT_REPORT_LEN)?;
+    transport::verify(BT_OUTPUT_SEED, &data[..BT_REPORT_LEN])?;
+    let mut usb = [0u8; N];
+    usb[0] = usb_id;
+    usb[1..].copy_from_slice(&data[body..body + N - 1]);
+    Ok(usb)
+}
+
+const XUSB_RUMBLE: u8 = 0x00;
+const XUSB_LED: u8 = 0x01;
+
+/// XUSB packets: `00 08 00 <strong> <weak> 00 00 00` (rumble) or `01 03 <pattern>` (LED).
+pub fn decode_xusb(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    check(data, None, 2)?;
+    match data[0] {
+        XUSB_RUMBLE => {
+            check(data, None, 8)?;
+            Ok(vec![OutputEvent::Rumble { strong: data[3], weak: data[4] }])
+        }
+        XUSB_LED => {
+            check(data, None, 3)?;
+            Ok(vec![OutputEvent::XusbLed { pattern: data[2] }])
+        }
+        other => Err(Error::ReportId(other)),
+    }
+}
+
+/// Keyboard LED report, no report ID: one byte of `KeyboardLeds`, the top three
+/// bits padding.
+pub fn decode_keyboard(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    check(data, None, 1)?;
+    Ok(vec![OutputEvent::KeyboardLeds { leds: KeyboardLeds::from_bits_truncate(data[0]) }])
+}
+
+pub const DS4_OUTPUT_ID: u8 = 0x05;
+pub const DS4_OUTPUT_LEN: usize = 32;
+
+const DS4_FLAG_MOTOR: u8 = 0x01;
+const DS4_FLAG_LED: u8 = 0x02;
+const DS4_FLAG_LED_BLINK: u8 = 0x04;
+
+/// DS4 USB output report 0x05.
+/// `[1]` valid flags, `[4]` weak motor, `[5]` strong motor, `[6..9]` RGB, `[9..11]` flash on/off.
+pub fn decode_ds4(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    check(data, Some(DS4_OUTPUT_ID), DS4_OUTPUT_LEN)?;
+    let flags = data[1];
+    let mut out = Vec::new();
+    if flags & DS4_FLAG_MOTOR != 0 {
+        out.push(OutputEvent::Rumble { strong: data[5], weak: data[4] });
+    }
+    if flags & DS4_FLAG_LED != 0 {
+        out.push(OutputEvent::Lightbar { r: data[6], g: data[7], b: data[8] });
+    }
+    if flags & DS4_FLAG_LED_BLINK != 0 {
+        out.push(OutputEvent::LightbarFlash { on: data[9], off: data[10] });
+    }
+    Ok(out)
+}
+
+pub const DS4_OUTPUT_BT_ID: u8 = 0x11;
+
+/// DS4 Bluetooth output report 0x11: `[1]` HID/CRC flags, `[2]` audio control,
+/// then the USB body from `[3]`, CRC32 in `[74..78]`.
+pub fn decode_ds4_bt(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    decode_ds4(&unwrap_bt::<DS4_OUTPUT_LEN>(data, DS4_OUTPUT_BT_ID, DS4_OUTPUT_ID, 3)?)
+}
+
+pub const DS5_OUTPUT_ID: u8 = 0x02;
+pub const DS5_OUTPUT_LEN: usize = 48;
+
+const DS5_FLAG0_COMPATIBLE_VIBRATION: u8 = 0x01;
+const DS5_FLAG0_HAPTICS_SELECT: u8 = 0x02;
+const DS5_FLAG0_RIGHT_TRIGGER: u8 = 0x04;
+const DS5_FLAG0_LEFT_TRIGGER: u8 = 0x08;
+const DS5_FLAG1_MIC_MUTE_LED: u8 = 0x01;
+const DS5_FLAG1_LIGHTBAR: u8 = 0x04;
+const DS5_FLAG1_PLAYER_LEDS: u8 = 0x10;
+
+/// DualSense USB output report 0x02.
+/// `[1..3]` valid flags, `[3]` weak motor, `[4]` strong motor, `[9]` mic LED,
+/// `[11..22]` right trigger, `[22..33]` left trigger, `[44]` player LEDs, `[45..48]` RGB.
+pub fn decode_ds5(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    check(data, Some(DS5_OUTPUT_ID), DS5_OUTPUT_LEN)?;
+    let (flag0, flag1) = (data[1], data[2]);
+    let mut out = Vec::new();
+    if flag0 & (DS5_FLAG0_COMPATIBLE_VIBRATION | DS5_FLAG0_HAPTICS_SELECT) != 0 {
+        out.push(OutputEvent::Rumble { strong: data[4], weak: data[3] });
+    }
+    if flag0 & DS5_FLAG0_RIGHT_TRIGGER != 0 {
+        out.push(OutputEvent::TriggerEffect {
+            side: TriggerSide::Right,
+            effect: TriggerEffect::from_block(&data[11..22]),
+        });
+    }
+    if flag0 & DS5_FLAG0_LEFT_TRIGGER != 0 {
+        out.push(OutputEvent::TriggerEffect {
+            side: TriggerSide::Left,
+            effect: TriggerEffect::from_block(&data[22..33]),
+        });
+    }
+    if flag1 & DS5_FLAG1_MIC_MUTE_LED != 0 {
+        let state = match data[9] {
+            0 => MicLed::Off,
+            1 => MicLed::On,
+            _ => MicLed::Pulse,
+        };
+        out.push(OutputEvent::MicLed { state });
+    }
+    if flag1 & DS5_FLAG1_LIGHTBAR != 0 {
+        out.push(OutputEvent::Lightbar { r: data[45], g: data[46], b: data[47] });
+    }
+    if flag1 & DS5_FLAG1_PLAYER_LEDS != 0 {
+        out.push(OutputEvent::PlayerLeds { mask: data[44] & 0x1F });
+    }
+    Ok(out)
+}
+
+pub const DS5_OUTPUT_BT_ID: u8 = 0x31;
+
+/// DualSense Bluetooth output report 0x31: `[1]` sequence tag, `[2]` 0x10 tag,
+/// then the USB body from `[3]`, CRC32 in `[74..78]`.
+pub fn decode_ds5_bt(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    decode_ds5(&unwrap_bt::<DS5_OUTPUT_LEN>(data, DS5_OUTPUT_BT_ID, DS5_OUTPUT_ID, 3)?)
+}
+
+pub const XBOX_ONE_OUTPUT_ID: u8 = 0x03;
+pub const XBOX_ONE_OUTPUT_LEN: usize = 9;
+
+const XBOX_ONE_ENABLE_WEAK: u8 = 0x01;
+const XBOX_ONE_ENABLE_STRONG: u8 = 0x02;
+const XBOX_ONE_ENABLE_RIGHT_TRIGGER: u8 = 0x04;
+const XBOX_ONE_ENABLE_LEFT_TRIGGER: u8 = 0x08;
+
+/// Xbox One output report 0x03: `[1]` enabled actuators, `[2]`/`[3]` left/right
+/// trigger, `[4]` strong and `[5]` weak motor, all in percent; `[6..9]` duration,
+/// delay and repeat are not decoded. Disabled actuators read as 0.
+pub fn decode_xbox_one(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    check(data, Some(XBOX_ONE_OUTPUT_ID), XBOX_ONE_OUTPUT_LEN)?;
+    let enable = data[1];
+    let level = |flag: u8, i: usize| {
+        if enable & flag != 0 { (data[i].min(100) as u16 * 255 / 100) as u8 } else { 0 }
+    };
+    let mut out = Vec::new();
+    if enable & (XBOX_ONE_ENABLE_STRONG | XBOX_ONE_ENABLE_WEAK) != 0 {
+        out.push(OutputEvent::Rumble {
+            strong: level(XBOX_ONE_ENABLE_STRONG, 4),
+            weak: level(XBOX_ONE_ENABLE_WEAK, 5),
+        });
+    }
+    if enable & (XBOX_ONE_ENABLE_LEFT_TRIGGER | XBOX_ONE_ENABLE_RIGHT_TRIGGER) != 0 {
+        out.push(OutputEvent::TriggerRumble {
+            left: level(XBOX_ONE_ENABLE_LEFT_TRIGGER, 2),
+            right: level(XBOX_ONE_ENABLE_RIGHT_TRIGGER, 3),
+        });
+    }
+    Ok(out)
+}
+
+/// Switch Pro output reports 0x01 (rumble + subcommand) and 0x10 (rumble only).
+/// `[2..6]` left and `[6..10]` right HD rumble; the left actuator is reported as
+/// `strong`. Of a 0x01 report's subcommands only the player lights are an event,
+/// the rest is `switch_pro::Session`'s business.
+pub fn decode_switch_pro(data: &[u8]) -> Result<Vec<OutputEvent>> {
+    use crate::switch_pro::{OUTPUT_RUMBLE_ID, OUTPUT_SUBCOMMAND_ID, OUTPUT_USB_ID, subcommand};
+    check(data, None, 1)?;
+    let id = data[0];
+    match id {
+        OUTPUT_USB_ID => return Ok(Vec::new()),
+        OUTPUT_RUMBLE_ID | OUTPUT_SUBCOMMAND_ID => check(data, None, 10)?,
+        other => return Err(Error::ReportId(other)),
+    }
+    let mut out = vec![OutputEvent::Rumble {
+        strong: switch_rumble_amplitude(&data[2..6]),
+        weak: switch_rumble_amplitude(&data[6..10]),
+    }];
+    if id == OUTPUT_SUBCOMMAND_ID && data.get(10) == Some(&subcommand::SET_PLAYER_LIGHTS) {
+        check(data, None, 12)?;
+        out.push(OutputEvent::PlayerLeds { mask: data[11] & 0x0F });
+    }
+    Ok(out)
+}
+
+/// The louder of one actuator's high and low band. Both amplitude codes run 0..=100
+/// on the pad's logarithmic scale; they are mapped to 0..255 linearly.
+fn switch_rumble_amplitude(b: &[u8]) -> u8 {
+    let hf = (b[1] >> 1) as u16;
+    let lf = ((b[3].saturating_sub(0x40) & 0x3F) as u16) << 1 | (b[2] >> 7) as u16;
+    (hf.max(lf).min(100) * 255 / 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xusb_rumble_and_led() {
+        let r = decode_xusb(&[0x00, 0x08, 0x00, 0xC0, 0x40, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(r, vec![OutputEvent::Rumble { strong: 0xC0, weak: 0x40 }]);
+        let r = decode_xusb(&[0x01, 0x03, 0x06]).unwrap();
+        assert_eq!(r, vec![OutputEvent::XusbLed { pattern: 6 }]);
+        assert_eq!(decode_xusb(&[0x00, 0x08, 0x00]), Err(Error::Length { expected: 8, got: 3 }));
+        assert_eq!(decode_xusb(&[0x02, 0x03, 0x00]), Err(Error::ReportId(0x02)));
+    }
+
+    #[test]
+    fn ds4_output_report() {
+        let mut b = [0u8; DS4_OUTPUT_LEN];
+        b[0] = DS4_OUTPUT_ID;
+        b[1] = DS4_FLAG_MOTOR | DS4_FLAG_LED | DS4_FLAG_LED_BLINK;
+        b[4] = 0x10;
+        b[5] = 0xF0;
+        b[6..9].copy_from_slice(&[0x00, 0x00, 0x40]);
+        b[9] = 25;
+        b[10] = 50;
+        assert_eq!(
+            decode_ds4(&b).unwrap(),
+            vec![
+                OutputEvent::Rumble { strong: 0xF0, weak: 0x10 },
+                OutputEvent::Lightbar { r: 0, g: 0, b: 0x40 },
+                OutputEvent::LightbarFlash { on: 25, off: 50 },
+            ]
+        );
+
+        // only the flagged sections are reported
+        b[1] = DS4_FLAG_LED;
+        assert_eq!(decode_ds4(&b).unwrap(), vec![OutputEvent::Lightbar { r: 0, g: 0, b: 0x40 }]);
+
+        assert_eq!(decode_ds4(&b[..10]), Err(Error::Length { expected: 32, got: 10 }));
+        b[0] = 0x11;
+        assert_eq!(decode_ds4(&b), Err(Error::ReportId(0x11)));
+    }
+
+    #[test]
+    fn ds5_output_report() {
+        let mut b = [0u8; DS5_OUTPUT_LEN];
+        b[0] = DS5_OUTPUT_ID;
+        b[1] = DS5_FLAG0_COMPATIBLE_VIBRATION
+            | DS5_FLAG0_HAPTICS_SELECT
+            | DS5_FLAG0_RIGHT_TRIGGER
+            | DS5_FLAG0_LEFT_TRIGGER;
+        b[2] = DS5_FLAG1_MIC_MUTE_LED | DS5_FLAG1_LIGHTBAR | DS5_FLAG1_PLAYER_LEDS;
+        b[3] = 0x20;
+        b[4] = 0x80;
+        b[9] = 2;
+        // right: continuous resistance from 30% with force 0xFF
+        b[11..14].copy_from_slice(&[0x01, 0x4C, 0xFF]);
+        // left: section resistance
+        b[22..25].copy_from_slice(&[0x02, 0x20, 0xA0]);
+        b[44] = 0x04 | 0x20;
+        b[45..48].copy_from_slice(&[0xFF, 0x80, 0x00]);
+
+        let mut right = TriggerEffect { mode: 0x01, ..Default::default() };
+        right.params[..2].copy_from_slice(&[0x4C, 0xFF]);
+        let mut left = TriggerEffect { mode: 0x02, ..Default::default() };
+        left.params[..2].copy_from_slice(&[0x20, 0xA0]);
+        assert_eq!(
+            decode_ds5(&b).unwrap(),
+            vec![
+                OutputEvent::Rumble { strong: 0x80, weak: 0x20 },
+                OutputEvent::TriggerEffect { side: TriggerSide::Right, effect: right },
+                OutputEvent::TriggerEffect { side: TriggerSide::Left, effect: left },
+                OutputEvent::MicLed { state: MicLed::Pulse },
+                OutputEvent::Lightbar { r: 0xFF, g: 0x80, b: 0x00 },
+                OutputEvent::PlayerLeds { mask: 0x04 },
+            ]
+        );
+
+        b[1] = 0;
+        b[2] = 0;
+        assert_eq!(decode_ds5(&b).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn bluetooth_output_reports() {
+        let mut b = [0u8; BT_REPORT_LEN];
+        b[..4].copy_from_slice(&[DS4_OUTPUT_BT_ID, 0xC0, 0x00, DS4_FLAG_MOTOR]);
+        b[6] = 0x10;
+        b[7] = 0xF0;
+        transport::seal(BT_OUTPUT_SEED, &mut b);
+        let rumble = vec![OutputEvent::Rumble { strong: 0xF0, weak: 0x10 }];
+        assert_eq!(decode(DeviceKind::DS4, &b), Ok(rumble.clone()));
+        b[7] = 0xF1;
+        assert!(matches!(decode(DeviceKind::DS4, &b), Err(Error::Crc { .. })));
+
+        let mut b = [0u8; BT_REPORT_LEN];
+        b[..4].copy_from_slice(&[DS5_OUTPUT_BT_ID, 0x20, 0x10, DS5_FLAG0_COMPATIBLE_VIBRATION]);
+        b[5] = 0x10;
+        b[6] = 0xF0;
+        transport::seal(BT_OUTPUT_SEED, &mut b);
+        assert_eq!(decode(DeviceKind::DS5, &b), Ok(rumble));
+        assert_eq!(decode_ds5_bt(&b[..40]), Err(Error::Length { expected: 78, got: 40 }));
+    }
+
+    #[test]
+    fn keyboard_leds() {
+        let leds = KeyboardLeds::CAPS_LOCK | KeyboardLeds::NUM_LOCK;
+        assert_eq!(
+            decode(DeviceKind::Keyboard, &[0xE3]),
+            Ok(vec![OutputEvent::KeyboardLeds { leds }])
+        );
+        assert_eq!(decode_keyboard(&[]), Err(Error::Length { expected: 1, got: 0 }));
+        assert_eq!(decode(DeviceKind::Mouse, &[0x01]), Err(Error::ReportId(0x01)));
+    }
+
+    #[test]
+    fn decode_dispatches_on_kind() {
+        let mut b = [0u8; DS5_OUTPUT_LEN];
+        b[0] = DS5_OUTPUT_ID;
+        assert_eq!(decode(DeviceKind::DS5, &b), Ok(vec![]));
+        assert_eq!(decode(DeviceKind::DS4, &b), Err(Error::ReportId(DS5_OUTPUT_ID)));
+    }
+
+    #[test]
+    fn xbox_one_output_report() {
+        let mut b = [XBOX_ONE_OUTPUT_ID, 0x0F, 10, 20, 100, 50, 0xFF, 0, 0];
+        assert_eq!(
+            decode_xbox_one(&b).unwrap(),
+            vec![
+                OutputEvent::Rumble { strong: 255, weak: 127 },
+                OutputEvent::TriggerRumble { left: 25, right: 51 },
+            ]
+        );
+
+        // disabled motors read as 0, out-of-range percentages saturate
+        b[1] = XBOX_ONE_ENABLE_STRONG;
+        b[4] = 200;
+        assert_eq!(
+            decode_xbox_one(&b).unwrap(),
+            vec![OutputEvent::Rumble { strong: 255, weak: 0 }]
+        );
+        b[1] = 0;
+        assert_eq!(decode(DeviceKind::XboxOne, &b), Ok(vec![]));
+        assert_eq!(decode_xbox_one(&b[..5]), Err(Error::Length { expected: 9, got: 5 }));
+        assert_eq!(decode(DeviceKind::Generic, &b), Err(Error::ReportId(XBOX_ONE_OUTPUT_ID)));
+        assert_eq!(
+            decode(DeviceKind::Wheel, &[crate::pid::DEVICE_GAIN_ID, 64]),
+            Ok(vec![OutputEvent::ForceFeedback { command: FfbCommand::DeviceGain { gain: 64 } }])
+        );
+    }
+
+    #[test]
+    fn switch_pro_rumble_and_lights() {
+        let neutral = [0x00, 0x01, 0x40, 0x40];
+        let mut b = [0u8; 12];
+        b[0] = crate::switch_pro::OUTPUT_SUBCOMMAND_ID;
+        b[2..6].copy_from_slice(&[0x00, 0xC9, 0x40, 0x72]);
+        b[6..10].copy_from_slice(&neutral);
+        b[10] = crate::switch_pro::subcommand::SET_PLAYER_LIGHTS;
+        b[11] = 0x13;
+        assert_eq!(
+            decode(DeviceKind::SwitchPro, &b),
+            Ok(vec![
+                OutputEvent::Rumble { strong: 255, weak: 0 },
+                OutputEvent::PlayerLeds { mask: 0x03 }
+            ])
+        );
+
+        // half-strength low band only, in a rumble-only report
+        b[0] = crate::switch_pro::OUTPUT_RUMBLE_ID;
+        b[6..10].copy_from_slice(&[0x00, 0x01, 0xC0, 0x58]);
+        assert_eq!(
+            decode_switch_pro(&b[..10]),
+            Ok(vec![OutputEvent::Rumble { strong: 255, weak: 124 }])
+        );
+        assert_eq!(decode_switch_pro(&[0x80, 0x02]), Ok(vec![]));
+        assert_eq!(decode_switch_pro(&[0x10, 0x00]), Err(Error::Length { expected: 10, got: 2 }));
+    }
+}
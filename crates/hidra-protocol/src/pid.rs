@@ -0,0 +1,479 @@
+//! HID PID (Physical Interface Device) force feedback, as declared by
+//! `descriptor::wheel`: the host's output reports become `FfbCommand`s, and the
+//! feature reports it reads back to allocate effect blocks are built here.
+//! Magnitudes, coefficients and saturations use the DirectInput scale, ±10000.
+
+use crate::error::{Error, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub const SET_EFFECT_ID: u8 = 0x01;
+pub const SET_EFFECT_LEN: usize = 16;
+pub const SET_CONDITION_ID: u8 = 0x03;
+pub const SET_CONDITION_LEN: usize = 15;
+pub const SET_CONSTANT_FORCE_ID: u8 = 0x05;
+pub const SET_CONSTANT_FORCE_LEN: usize = 4;
+pub const EFFECT_OPERATION_ID: u8 = 0x0A;
+pub const EFFECT_OPERATION_LEN: usize = 4;
+pub const BLOCK_FREE_ID: u8 = 0x0B;
+pub const BLOCK_FREE_LEN: usize = 2;
+pub const DEVICE_CONTROL_ID: u8 = 0x0C;
+pub const DEVICE_CONTROL_LEN: usize = 2;
+pub const DEVICE_GAIN_ID: u8 = 0x0D;
+pub const DEVICE_GAIN_LEN: usize = 2;
+
+/// Feature report the host sets to ask for a new effect block.
+pub const CREATE_NEW_EFFECT_ID: u8 = 0x11;
+pub const CREATE_NEW_EFFECT_LEN: usize = 4;
+/// Feature report the host reads back for the block it just asked for.
+pub const BLOCK_LOAD_ID: u8 = 0x12;
+pub const BLOCK_LOAD_LEN: usize = 5;
+pub const POOL_ID: u8 = 0x13;
+pub const POOL_LEN: usize = 5;
+
+/// Effect block indices run 1..=MAX_EFFECTS.
+pub const MAX_EFFECTS: u8 = 40;
+/// Largest magnitude, coefficient and saturation value.
+pub const FORCE_MAX: i16 = 10000;
+/// `duration_ms` on the wire for an effect that plays until stopped.
+const INFINITE: u16 = 0xFFFF;
+
+/// The effect types the wheel declares, numbered as in its Effect Type array.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EffectType {
+    ConstantForce = 1,
+    Spring = 2,
+    Damper = 3,
+    Inertia = 4,
+    Friction = 5,
+}
+
+impl EffectType {
+    pub const ALL: [EffectType; 5] = [
+        EffectType::ConstantForce,
+        EffectType::Spring,
+        EffectType::Damper,
+        EffectType::Inertia,
+        EffectType::Friction,
+    ];
+
+    fn from_u8(v: u8) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| *t as u8 == v)
+            .ok_or(Error::Value { field: "effect type", value: v as u32 })
+    }
+}
+
+/// Set Effect: the parameters every effect type shares.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Effect {
+    pub index: u8,
+    pub effect_type: EffectType,
+    /// `None` plays until stopped.
+    pub duration_ms: Option<u16>,
+    pub trigger_repeat_ms: u16,
+    pub sample_period_ms: u16,
+    /// 0..255 of full strength.
+    pub gain: u8,
+    /// 1-based button that plays the effect, if any.
+    pub trigger_button: Option<u8>,
+    /// Hundredths of a degree, 0..36000; only meaningful with `direction_enable`.
+    pub direction: u16,
+    pub direction_enable: bool,
+    pub start_delay_ms: u16,
+}
+
+/// Set Condition: spring, damper, inertia and friction parameters for the wheel axis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Condition {
+    pub index: u8,
+    /// Center point, -10000..=10000 of the axis range.
+    pub cp_offset: i16,
+    pub positive_coefficient: i16,
+    pub negative_coefficient: i16,
+    pub positive_saturation: u16,
+    pub negative_saturation: u16,
+    /// Width of the zone around `cp_offset` with no force, 0..=10000.
+    pub dead_band: u16,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EffectOp {
+    Start,
+    /// Start this effect and stop every other one.
+    StartSolo,
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DeviceControl {
+    EnableActuators,
+    DisableActuators,
+    StopAllEffects,
+    Reset,
+    Pause,
+    Continue,
+}
+
+/// One PID output report, decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "command", rename_all = "snake_case"))]
+pub enum FfbCommand {
+    SetEffect {
+        effect: Effect,
+    },
+    SetCondition {
+        condition: Condition,
+    },
+    /// Constant force magnitude, -10000..=10000; positive pushes right.
+    SetConstantForce {
+        index: u8,
+        magnitude: i16,
+    },
+    /// `loop_count` 255 repeats until stopped.
+    Operation {
+        index: u8,
+        op: EffectOp,
+        loop_count: u8,
+    },
+    Free {
+        index: u8,
+    },
+    Control {
+        control: DeviceControl,
+    },
+    /// Overall strength, 0..255, applied on top of every effect's own gain.
+    DeviceGain {
+        gain: u8,
+    },
+}
+
+/// Result of a Create New Effect request, as the host reads it from Block Load.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockLoadStatus {
+    Success = 1,
+    Full = 2,
+    Error = 3,
+}
+
+fn check(data: &[u8], id: u8, len: usize) -> Result<()> {
+    if data.len() < len {
+        return Err(Error::Length { expected: len, got: data.len() });
+    }
+    if data[0] != id {
+        return Err(Error::ReportId(data[0]));
+    }
+    Ok(())
+}
+
+fn index_at(data: &[u8], i: usize) -> Result<u8> {
+    match data[i] {
+        v @ 1..=MAX_EFFECTS => Ok(v),
+        v => Err(Error::Value { field: "effect block index", value: v as u32 }),
+    }
+}
+
+/// Decode one PID output report.
+pub fn decode(data: &[u8]) -> Result<FfbCommand> {
+    let id = *data.first().ok_or(Error::Length { expected: 1, got: 0 })?;
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    let i16_at = |i: usize| i16::from_le_bytes([data[i], data[i + 1]]);
+    match id {
+        SET_EFFECT_ID => {
+            check(data, id, SET_EFFECT_LEN)?;
+            let duration = u16_at(3);
+            Ok(FfbCommand::SetEffect {
+                effect: Effect {
+                    index: index_at(data, 1)?,
+                    effect_type: EffectType::from_u8(data[2])?,
+                    duration_ms: (duration != INFINITE).then_some(duration),
+                    trigger_repeat_ms: u16_at(5),
+                    sample_period_ms: u16_at(7),
+                    gain: data[9],
+                    trigger_button: (data[10] != 0).then_some(data[10]),
+                    direction_enable: data[11] & 0x02 != 0,
+                    direction: u16_at(12),
+                    start_delay_ms: u16_at(14),
+                },
+            })
+        }
+        SET_CONDITION_ID => {
+            check(data, id, SET_CONDITION_LEN)?;
+            Ok(FfbCommand::SetCondition {
+                condition: Condition {
+                    index: index_at(data, 1)?,
+                    cp_offset: i16_at(3),
+                    positive_coefficient: i16_at(5),
+                    negative_coefficient: i16_at(7),
+                    positive_saturation: u16_at(9),
+                    negative_saturation: u16_at(11),
+                    dead_band: u16_at(13),
+                },
+            })
+        }
+        SET_CONSTANT_FORCE_ID => {
+            check(data, id, SET_CONSTANT_FORCE_LEN)?;
+            Ok(FfbCommand::SetConstantForce { index: index_at(data, 1)?, magnitude: i16_at(2) })
+        }
+        EFFECT_OPERATION_ID => {
+            check(data, id, EFFECT_OPERATION_LEN)?;
+            let op = match data[2] {
+                1 => EffectOp::Start,
+                2 => EffectOp::StartSolo,
+                3 => EffectOp::Stop,
+                v => return Err(Error::Value { field: "effect operation", value: v as u32 }),
+            };
+            Ok(FfbCommand::Operation { index: index_at(data, 1)?, op, loop_count: data[3] })
+        }
+        BLOCK_FREE_ID => {
+            check(data, id, BLOCK_FREE_LEN)?;
+            Ok(FfbCommand::Free { index: index_at(data, 1)? })
+        }
+        DEVICE_CONTROL_ID => {
+            check(data, id, DEVICE_CONTROL_LEN)?;
+            let control = match data[1] {
+                1 => DeviceControl::EnableActuators,
+                2 => DeviceControl::DisableActuators,
+                3 => DeviceControl::StopAllEffects,
+                4 => DeviceControl::Reset,
+                5 => DeviceControl::Pause,
+                6 => DeviceControl::Continue,
+                v => return Err(Error::Value { field: "device control", value: v as u32 }),
+            };
+            Ok(FfbCommand::Control { control })
+        }
+        DEVICE_GAIN_ID => {
+            check(data, id, DEVICE_GAIN_LEN)?;
+            Ok(FfbCommand::DeviceGain { gain: data[1] })
+        }
+        _ => Err(Error::ReportId(id)),
+    }
+}
+
+/// The effect type a Create New Effect feature report asks for.
+pub fn parse_create_new_effect(data: &[u8]) -> Result<EffectType> {
+    check(data, CREATE_NEW_EFFECT_ID, CREATE_NEW_EFFECT_LEN)?;
+    EffectType::from_u8(data[1])
+}
+
+/// Block Load answer: `index` is 0 unless `status` is `Success`.
+pub fn block_load(index: u8, status: BlockLoadStatus, pool_available: u16) -> [u8; BLOCK_LOAD_LEN] {
+    let [lo, hi] = pool_available.to_le_bytes();
+    [BLOCK_LOAD_ID, index, status as u8, lo, hi]
+}
+
+/// PID Pool: a device-managed pool of `MAX_EFFECTS` blocks, none shared.
+pub fn pool() -> [u8; POOL_LEN] {
+    let [lo, hi] = u16::MAX.to_le_bytes();
+    [POOL_ID, lo, hi, MAX_EFFECTS, 0x01]
+}
+
+/// The device's side of effect block allocation: Create New Effect takes the
+/// lowest free block, Block Load reports the outcome, and Block Free or a
+/// Reset give blocks back.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EffectBlocks {
+    /// Bit `i` set while block `i + 1` is in use.
+    used: u64,
+    load: [u8; BLOCK_LOAD_LEN],
+}
+
+impl Default for EffectBlocks {
+    fn default() -> Self {
+        Self { used: 0, load: block_load(0, BlockLoadStatus::Error, MAX_EFFECTS as u16) }
+    }
+}
+
+impl EffectBlocks {
+    /// Blocks not in use.
+    pub fn available(&self) -> u16 {
+        MAX_EFFECTS as u16 - self.used.count_ones() as u16
+    }
+
+    /// Handle a Create New Effect feature report; the block it got, if any,
+    /// is what the next Block Load read returns. A malformed request reads
+    /// back as an error.
+    pub fn create(&mut self, data: &[u8]) -> Result<Option<u8>> {
+        self.load = block_load(0, BlockLoadStatus::Error, self.available());
+        parse_create_new_effect(data)?;
+        let free = (!self.used).trailing_zeros();
+        if free >= MAX_EFFECTS as u32 {
+            self.load = block_load(0, BlockLoadStatus::Full, 0);
+            return Ok(None);
+        }
+        self.used |= 1 << free;
+        let index = free as u8 + 1;
+        self.load = block_load(index, BlockLoadStatus::Success, self.available());
+        Ok(Some(index))
+    }
+
+    /// Release the blocks an output report gives back.
+    pub fn apply(&mut self, command: &FfbCommand) {
+        match *command {
+            FfbCommand::Free { index: index @ 1..=MAX_EFFECTS } => self.used &= !(1 << (index - 1)),
+            FfbCommand::Control { control: DeviceControl::Reset } => self.used = 0,
+            _ => {}
+        }
+    }
+
+    /// Feature report `id` as the host reads it: Block Load or Pool.
+    pub fn get_feature(&self, id: u8) -> Option<[u8; BLOCK_LOAD_LEN]> {
+        match id {
+            BLOCK_LOAD_ID => Some(self.load),
+            POOL_ID => Some(pool()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_effect() {
+        // spring in block 3, infinite, gain 200, axis + direction enabled at 90°, 16ms delay
+        let mut b: [u8; SET_EFFECT_LEN] =
+            [SET_EFFECT_ID, 3, 2, 0xFF, 0xFF, 0, 0, 0, 0, 200, 0, 0x03, 0x28, 0x23, 0x10, 0x00];
+        assert_eq!(
+            decode(&b),
+            Ok(FfbCommand::SetEffect {
+                effect: Effect {
+                    index: 3,
+                    effect_type: EffectType::Spring,
+                    duration_ms: None,
+                    trigger_repeat_ms: 0,
+                    sample_period_ms: 0,
+                    gain: 200,
+                    trigger_button: None,
+                    direction: 9000,
+                    direction_enable: true,
+                    start_delay_ms: 16,
+                }
+            })
+        );
+        b[2] = 9;
+        assert_eq!(decode(&b), Err(Error::Value { field: "effect type", value: 9 }));
+        b[1] = 0;
+        assert_eq!(decode(&b), Err(Error::Value { field: "effect block index", value: 0 }));
+        assert_eq!(decode(&b[..10]), Err(Error::Length { expected: SET_EFFECT_LEN, got: 10 }));
+    }
+
+    #[test]
+    fn forces_and_conditions() {
+        let m = (-FORCE_MAX).to_le_bytes();
+        assert_eq!(
+            decode(&[SET_CONSTANT_FORCE_ID, 1, m[0], m[1]]),
+            Ok(FfbCommand::SetConstantForce { index: 1, magnitude: -10000 })
+        );
+        let mut c = [0u8; SET_CONDITION_LEN];
+        c[0] = SET_CONDITION_ID;
+        c[1] = MAX_EFFECTS;
+        c[3..5].copy_from_slice(&(-500i16).to_le_bytes());
+        c[5..7].copy_from_slice(&FORCE_MAX.to_le_bytes());
+        c[7..9].copy_from_slice(&5000i16.to_le_bytes());
+        c[9..11].copy_from_slice(&10000u16.to_le_bytes());
+        c[11..13].copy_from_slice(&2500u16.to_le_bytes());
+        c[13..15].copy_from_slice(&100u16.to_le_bytes());
+        assert_eq!(
+            decode(&c),
+            Ok(FfbCommand::SetCondition {
+                condition: Condition {
+                    index: 40,
+                    cp_offset: -500,
+                    positive_coefficient: 10000,
+                    negative_coefficient: 5000,
+                    positive_saturation: 10000,
+                    negative_saturation: 2500,
+                    dead_band: 100,
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn operations_and_device_commands() {
+        assert_eq!(
+            decode(&[EFFECT_OPERATION_ID, 2, 2, 255]),
+            Ok(FfbCommand::Operation { index: 2, op: EffectOp::StartSolo, loop_count: 255 })
+        );
+        assert_eq!(
+            decode(&[EFFECT_OPERATION_ID, 2, 4, 1]),
+            Err(Error::Value { field: "effect operation", value: 4 })
+        );
+        assert_eq!(decode(&[BLOCK_FREE_ID, 7]), Ok(FfbCommand::Free { index: 7 }));
+        assert_eq!(
+            decode(&[DEVICE_CONTROL_ID, 3]),
+            Ok(FfbCommand::Control { control: DeviceControl::StopAllEffects })
+        );
+        assert_eq!(decode(&[DEVICE_GAIN_ID, 128]), Ok(FfbCommand::DeviceGain { gain: 128 }));
+        assert_eq!(decode(&[0x42, 0]), Err(Error::ReportId(0x42)));
+        assert_eq!(decode(&[]), Err(Error::Length { expected: 1, got: 0 }));
+    }
+
+    #[test]
+    fn block_allocation_reports() {
+        assert_eq!(
+            parse_create_new_effect(&[CREATE_NEW_EFFECT_ID, 1, 0, 0]),
+            Ok(EffectType::ConstantForce)
+        );
+        assert_eq!(
+            parse_create_new_effect(&[BLOCK_LOAD_ID, 1, 0, 0]),
+            Err(Error::ReportId(BLOCK_LOAD_ID))
+        );
+        assert_eq!(block_load(4, BlockLoadStatus::Success, 0x1234), [0x12, 4, 1, 0x34, 0x12]);
+        assert_eq!(pool(), [0x13, 0xFF, 0xFF, 40, 1]);
+    }
+
+    #[test]
+    fn effect_blocks() {
+        let new = |t: EffectType| [CREATE_NEW_EFFECT_ID, t as u8, 0, 0];
+        let mut blocks = EffectBlocks::default();
+        assert_eq!(blocks.get_feature(BLOCK_LOAD_ID), Some([0x12, 0, 3, 40, 0]));
+        assert_eq!(blocks.create(&new(EffectType::Spring)), Ok(Some(1)));
+        assert_eq!(blocks.create(&new(EffectType::Damper)), Ok(Some(2)));
+        assert_eq!(blocks.get_feature(BLOCK_LOAD_ID), Some([0x12, 2, 1, 38, 0]));
+        assert_eq!(blocks.get_feature(POOL_ID), Some(pool()));
+        assert_eq!(blocks.get_feature(SET_EFFECT_ID), None);
+
+        blocks.apply(&FfbCommand::Free { index: 1 });
+        assert_eq!(blocks.create(&new(EffectType::Friction)), Ok(Some(1)));
+        assert!(blocks.create(&[CREATE_NEW_EFFECT_ID, 9, 0, 0]).is_err());
+        assert_eq!(blocks.get_feature(BLOCK_LOAD_ID), Some([0x12, 0, 3, 38, 0]));
+
+        while blocks.available() > 0 {
+            blocks.create(&new(EffectType::ConstantForce)).unwrap();
+        }
+        assert_eq!(blocks.create(&new(EffectType::Spring)), Ok(None));
+        assert_eq!(blocks.get_feature(BLOCK_LOAD_ID), Some([0x12, 0, 2, 0, 0]));
+        blocks.apply(&FfbCommand::Control { control: DeviceControl::Reset });
+        assert_eq!(blocks.available(), 40);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_form() {
+        let json = serde_json::to_value(FfbCommand::Operation {
+            index: 1,
+            op: EffectOp::Stop,
+            loop_count: 1,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"command": "operation", "index": 1, "op": "stop", "loop_count": 1})
+        );
+    }
+}
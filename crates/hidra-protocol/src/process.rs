@@ -0,0 +1,377 @@
+//! Stick and trigger conditioning: deadzones, response curves, anti-deadzone and
+//! hair triggers. Stages act on normalized deflection (0..=1, radially for
+//! sticks), so one pipeline fits any declared `InputRanges`.
+
+use crate::{CreateError, InputRanges, PadState, StickRange, TriggerRange};
+use alloc::vec::Vec;
+use libm::{hypotf, powf, roundf};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Response curve. Every shape except a `Linear` gain maps 0 to 0 and 1 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "curve", rename_all = "snake_case"))]
+pub enum Curve {
+    Linear {
+        gain: f32,
+    },
+    /// `x^exponent`: above 1 gives finer control near center, below 1 a faster start.
+    Exponential {
+        exponent: f32,
+    },
+    /// Cubic Bézier from (0,0) to (1,1) with control heights `p1`/`p2` at x = 1/3 and 2/3.
+    Cubic {
+        p1: f32,
+        p2: f32,
+    },
+}
+
+impl Curve {
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Curve::Linear { gain } => x * gain,
+            Curve::Exponential { exponent } => powf(x, exponent),
+            Curve::Cubic { p1, p2 } => {
+                let t = x.min(1.0);
+                let u = 1.0 - t;
+                3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "stage", rename_all = "snake_case"))]
+pub enum Stage {
+    /// Zero within `radius` of center; the rest is rescaled to start from 0.
+    RadialDeadzone {
+        radius: f32,
+    },
+    /// Like `RadialDeadzone`, but per axis, so a stick pushed straight up keeps x at 0.
+    /// On triggers it is the same as `RadialDeadzone`.
+    AxialDeadzone {
+        size: f32,
+    },
+    /// Deflection past `1 - size` reads as full.
+    OuterDeadzone {
+        size: f32,
+    },
+    /// Any deflection starts at `size`, cancelling a game-side deadzone that large.
+    AntiDeadzone {
+        size: f32,
+    },
+    Curve(Curve),
+    /// At or past `threshold` reads as full, below it as released.
+    HairTrigger {
+        threshold: f32,
+    },
+}
+
+impl Stage {
+    /// Finite parameters, sizes in `0.0..1.0`, exponents above 0 and gains not negative.
+    fn is_valid(self) -> bool {
+        let size = |v: f32| (0.0..1.0).contains(&v);
+        match self {
+            Stage::RadialDeadzone { radius: v }
+            | Stage::AxialDeadzone { size: v }
+            | Stage::OuterDeadzone { size: v }
+            | Stage::AntiDeadzone { size: v }
+            | Stage::HairTrigger { threshold: v } => size(v),
+            Stage::Curve(Curve::Linear { gain }) => gain.is_finite() && gain >= 0.0,
+            Stage::Curve(Curve::Exponential { exponent }) => exponent.is_finite() && exponent > 0.0,
+            Stage::Curve(Curve::Cubic { p1, p2 }) => p1.is_finite() && p2.is_finite(),
+        }
+    }
+
+    /// Maps one magnitude; `AxialDeadzone` is applied per axis by `AxisPipeline::stick`.
+    fn magnitude(self, m: f32) -> f32 {
+        match self {
+            Stage::RadialDeadzone { radius: dz } | Stage::AxialDeadzone { size: dz } => {
+                if m <= dz {
+                    0.0
+                } else {
+                    (m - dz) / (1.0 - dz)
+                }
+            }
+            Stage::OuterDeadzone { size } => m / (1.0 - size).max(f32::EPSILON),
+            Stage::AntiDeadzone { size } => {
+                if m > 0.0 {
+                    size + (1.0 - size) * m
+                } else {
+                    0.0
+                }
+            }
+            Stage::Curve(c) => c.apply(m),
+            Stage::HairTrigger { threshold } => {
+                if m > 0.0 && m >= threshold {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Stages applied in order to one stick or trigger. Empty means pass-through.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AxisPipeline {
+    pub stages: Vec<Stage>,
+}
+
+impl AxisPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Stick position with both axes in -1..=1. Stages scale the radial magnitude
+    /// and keep the direction. A stage can't push the magnitude past the rim (or
+    /// past the input's, for square-gated corners), so diagonals stay round.
+    pub fn stick(&self, mut x: f32, mut y: f32) -> (f32, f32) {
+        for stage in &self.stages {
+            if let Stage::AxialDeadzone { .. } = stage {
+                x = x.signum() * stage.magnitude(x.abs());
+                y = y.signum() * stage.magnitude(y.abs());
+                continue;
+            }
+            let m = hypotf(x, y);
+            if m > 0.0 {
+                let k = stage.magnitude(m).min(m.max(1.0)) / m;
+                x *= k;
+                y *= k;
+            }
+        }
+        (x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0))
+    }
+
+    /// Trigger value in 0..=1.
+    pub fn trigger(&self, v: f32) -> f32 {
+        self.stages.iter().fold(v, |v, s| s.magnitude(v)).clamp(0.0, 1.0)
+    }
+
+    /// Rejects the first stage whose parameters would make `axis` misbehave,
+    /// e.g. a negative deadzone that amplifies drift.
+    pub fn validate(&self, axis: PipelineAxis) -> Result<(), CreateError> {
+        match self.stages.iter().position(|s| !s.is_valid()) {
+            Some(stage) => Err(CreateError::InvalidPipeline { axis, stage: stage as u32 }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Which `PadPipeline` axis a create request got wrong.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PipelineAxis {
+    Left,
+    Right,
+    Lt,
+    Rt,
+}
+
+/// A pipeline per stick and trigger.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PadPipeline {
+    pub left: AxisPipeline,
+    pub right: AxisPipeline,
+    pub lt: AxisPipeline,
+    pub rt: AxisPipeline,
+}
+
+impl PadPipeline {
+    pub fn is_identity(&self) -> bool {
+        [&self.left, &self.right, &self.lt, &self.rt].iter().all(|p| p.stages.is_empty())
+    }
+
+    pub fn validate(&self) -> Result<(), CreateError> {
+        self.left.validate(PipelineAxis::Left)?;
+        self.right.validate(PipelineAxis::Right)?;
+        self.lt.validate(PipelineAxis::Lt)?;
+        self.rt.validate(PipelineAxis::Rt)
+    }
+
+    /// `s` with its sticks and triggers processed, read and written in the units
+    /// declared by `ranges`. Axes with an empty pipeline are left bit-exact.
+    pub fn apply(&self, s: &PadState, ranges: InputRanges) -> PadState {
+        let mut s = *s;
+        let stick = |p: &AxisPipeline, x: &mut i16, y: &mut i16| {
+            if p.stages.is_empty() {
+                return;
+            }
+            let (nx, ny) = p.stick(stick_norm(*x, ranges.stick), stick_norm(*y, ranges.stick));
+            *x = stick_denorm(nx, ranges.stick);
+            *y = stick_denorm(ny, ranges.stick);
+        };
+        stick(&self.left, &mut s.lx, &mut s.ly);
+        stick(&self.right, &mut s.rx, &mut s.ry);
+        let trigger = |p: &AxisPipeline, v: &mut u16| {
+            if !p.stages.is_empty() {
+                let max = ranges.trigger.max() as f32;
+                *v = roundf(p.trigger((*v).min(ranges.trigger.max()) as f32 / max) * max) as u16;
+            }
+        };
+        trigger(&self.lt, &mut s.lt);
+        trigger(&self.rt, &mut s.rt);
+        s
+    }
+}
+
+/// Magnitude of the negative and positive extremes.
+fn stick_bounds(r: StickRange) -> (f32, f32) {
+    match r {
+        StickRange::Full => (32768.0, 32767.0),
+        StickRange::Half => (16384.0, 16383.0),
+    }
+}
+
+fn stick_norm(v: i16, r: StickRange) -> f32 {
+    let (neg, pos) = stick_bounds(r);
+    let v = v as f32;
+    if v < 0.0 { (v / neg).max(-1.0) } else { (v / pos).min(1.0) }
+}
+
+fn stick_denorm(v: f32, r: StickRange) -> i16 {
+    let (neg, pos) = stick_bounds(r);
+    roundf(if v < 0.0 { v * neg } else { v * pos }) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn radial_and_axial_deadzones() {
+        let p = AxisPipeline::new().then(Stage::RadialDeadzone { radius: 0.2 });
+        assert_eq!(p.stick(0.1, 0.1), (0.0, 0.0));
+        let (x, y) = p.stick(0.6, 0.0);
+        assert!(close(x, 0.5) && y == 0.0);
+        // direction is kept
+        let (x, y) = p.stick(0.3, 0.4);
+        assert!(close(x / y, 0.75));
+
+        let p = AxisPipeline::new().then(Stage::AxialDeadzone { size: 0.2 });
+        let (x, y) = p.stick(0.15, -0.6);
+        assert!(x == 0.0 && close(y, -0.5));
+    }
+
+    #[test]
+    fn outer_and_anti_deadzone() {
+        let p = AxisPipeline::new().then(Stage::OuterDeadzone { size: 0.1 });
+        assert!(close(p.trigger(0.45), 0.5));
+        assert_eq!(p.trigger(0.95), 1.0);
+        // near the edge off-axis: pushed out to the rim along the same direction
+        let (x, y) = p.stick(0.99, 0.14);
+        assert!(close(hypotf(x, y), 1.0));
+        assert!(close(y / x, 0.14 / 0.99));
+
+        let p = AxisPipeline::new().then(Stage::AntiDeadzone { size: 0.25 });
+        assert_eq!(p.trigger(0.0), 0.0);
+        assert!(close(p.trigger(0.01), 0.2575));
+        assert_eq!(p.trigger(1.0), 1.0);
+    }
+
+    #[test]
+    fn curves_keep_endpoints() {
+        for c in [Curve::Exponential { exponent: 2.5 }, Curve::Cubic { p1: 0.1, p2: 0.9 }] {
+            assert!(close(c.apply(0.0), 0.0) && close(c.apply(1.0), 1.0), "{c:?}");
+        }
+        assert!(close(Curve::Exponential { exponent: 2.0 }.apply(0.5), 0.25));
+        // control points at the diagonal make the cubic linear
+        assert!(close(Curve::Cubic { p1: 1.0 / 3.0, p2: 2.0 / 3.0 }.apply(0.3), 0.3));
+        assert!(close(Curve::Linear { gain: 1.5 }.apply(0.4), 0.6));
+    }
+
+    #[test]
+    fn hair_trigger() {
+        let p = AxisPipeline::new().then(Stage::HairTrigger { threshold: 0.05 });
+        assert_eq!(p.trigger(0.0), 0.0);
+        assert_eq!(p.trigger(0.04), 0.0);
+        assert_eq!(p.trigger(0.05), 1.0);
+    }
+
+    #[test]
+    fn pad_pipeline_respects_ranges() {
+        let pipeline = PadPipeline {
+            left: AxisPipeline::new().then(Stage::RadialDeadzone { radius: 0.5 }),
+            lt: AxisPipeline::new().then(Stage::HairTrigger { threshold: 0.1 }),
+            ..Default::default()
+        };
+        let ranges = InputRanges { trigger: TriggerRange::Bits10, stick: StickRange::Half };
+        let s = PadState { lx: 4000, rx: 1234, lt: 200, rt: 5, ..Default::default() };
+        let out = pipeline.apply(&s, ranges);
+        assert_eq!((out.lx, out.ly), (0, 0));
+        assert_eq!((out.rx, out.lt, out.rt), (1234, 1023, 5));
+        let out = pipeline.apply(&PadState { lx: 0, ly: -16384, ..s }, ranges);
+        assert_eq!((out.lx, out.ly), (0, -16384));
+
+        let s = PadState { lx: i16::MIN, ly: i16::MAX, ..Default::default() };
+        let id = PadPipeline {
+            left: AxisPipeline::new().then(Stage::Curve(Curve::Linear { gain: 1.0 })),
+            ..Default::default()
+        };
+        let out = id.apply(&s, InputRanges::default());
+        assert_eq!((out.lx, out.ly), (i16::MIN, i16::MAX));
+        assert!(PadPipeline::default().is_identity() && !id.is_identity());
+    }
+
+    #[test]
+    fn rejects_bad_parameters() {
+        let bad = |stage| AxisPipeline::new().then(stage).validate(PipelineAxis::Lt).is_err();
+        assert!(bad(Stage::Curve(Curve::Exponential { exponent: 0.0 })));
+        assert!(bad(Stage::Curve(Curve::Exponential { exponent: f32::NAN })));
+        assert!(bad(Stage::Curve(Curve::Linear { gain: -1.0 })));
+        assert!(bad(Stage::Curve(Curve::Cubic { p1: f32::INFINITY, p2: 0.5 })));
+        assert!(bad(Stage::RadialDeadzone { radius: -0.5 }));
+        assert!(bad(Stage::AxialDeadzone { size: 1.0 }));
+        assert!(bad(Stage::OuterDeadzone { size: f32::NAN }));
+        assert!(bad(Stage::AntiDeadzone { size: 1.5 }));
+        assert!(bad(Stage::HairTrigger { threshold: -0.1 }));
+        assert!(!bad(Stage::HairTrigger { threshold: 0.0 }));
+        assert!(!bad(Stage::Curve(Curve::Linear { gain: 0.0 })));
+
+        let pad = PadPipeline {
+            left: AxisPipeline::new().then(Stage::RadialDeadzone { radius: 0.1 }),
+            rt: AxisPipeline::new()
+                .then(Stage::AntiDeadzone { size: 0.2 })
+                .then(Stage::Curve(Curve::Exponential { exponent: 0.0 })),
+            ..Default::default()
+        };
+        assert_eq!(
+            pad.validate(),
+            Err(CreateError::InvalidPipeline { axis: PipelineAxis::Rt, stage: 1 })
+        );
+        assert_eq!(PadPipeline { rt: AxisPipeline::new(), ..pad }.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_form() {
+        let p = AxisPipeline::new()
+            .then(Stage::RadialDeadzone { radius: 0.1 })
+            .then(Stage::Curve(Curve::Exponential { exponent: 2.0 }));
+        let json = serde_json::json!([
+            {"stage": "radial_deadzone", "radius": 0.1f32},
+            {"stage": "curve", "curve": "exponential", "exponent": 2.0},
+        ]);
+        assert_eq!(serde_json::to_value(&p).unwrap(), json);
+        assert_eq!(serde_json::from_value::<AxisPipeline>(json).unwrap(), p);
+        let pad: PadPipeline = serde_json::from_str(r#"{"rt": []}"#).unwrap();
+        assert!(pad.is_identity());
+    }
+}
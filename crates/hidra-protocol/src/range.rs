@@ -0,0 +1,121 @@
+//! Declared input ranges, chosen at create time, and scaling from them into the
+//! full-scale values the report packers work with.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Resolution of the `lt`/`rt` values a client sends.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TriggerRange {
+    #[default]
+    Bits8 = 8,
+    Bits10 = 10,
+    Bits12 = 12,
+    Bits16 = 16,
+}
+
+impl TriggerRange {
+    pub const fn max(self) -> u16 {
+        ((1u32 << self as u32) - 1) as u16
+    }
+
+    /// Scale `v` (clamped to the declared range) to 0..=u16::MAX, rounding to nearest.
+    pub fn to_full(self, v: u16) -> u16 {
+        let max = self.max() as u32;
+        ((v.min(self.max()) as u32 * u16::MAX as u32 + max / 2) / max) as u16
+    }
+}
+
+impl TryFrom<u8> for TriggerRange {
+    type Error = u8;
+
+    fn try_from(bits: u8) -> Result<Self, u8> {
+        match bits {
+            8 => Ok(Self::Bits8),
+            10 => Ok(Self::Bits10),
+            12 => Ok(Self::Bits12),
+            16 => Ok(Self::Bits16),
+            other => Err(other),
+        }
+    }
+}
+
+/// Span of the `lx`/`ly`/`rx`/`ry` values a client sends.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StickRange {
+    /// -32768..=32767.
+    #[default]
+    Full = 0,
+    /// -16384..=16383, as produced by 15-bit sources.
+    Half = 1,
+}
+
+impl StickRange {
+    /// Scale `v` (clamped to the declared range) to the full i16 range, keeping both
+    /// extremes at the extremes and 0 at 0.
+    pub fn to_full(self, v: i16) -> i16 {
+        match self {
+            StickRange::Full => v,
+            StickRange::Half => {
+                let v = v.clamp(-0x4000, 0x3FFF) as i32;
+                if v >= 0 { (v * 0x7FFF / 0x3FFF) as i16 } else { (v * 2) as i16 }
+            }
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputRanges {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trigger: TriggerRange,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stick: StickRange,
+}
+
+/// Full-scale trigger down to 8 bits.
+#[inline]
+pub(crate) fn full_to_u8(v: u16) -> u8 {
+    (v >> 8) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_scaling_is_proportional() {
+        for range in [TriggerRange::Bits8, TriggerRange::Bits10, TriggerRange::Bits16] {
+            assert_eq!(range.to_full(0), 0);
+            assert_eq!(range.to_full(range.max()), u16::MAX);
+            // out-of-range input saturates instead of wrapping
+            assert_eq!(range.to_full(u16::MAX), u16::MAX);
+        }
+        assert_eq!(full_to_u8(TriggerRange::Bits10.to_full(512)), 128);
+        assert_eq!(full_to_u8(TriggerRange::Bits12.to_full(1024)), 64);
+        assert_eq!(full_to_u8(TriggerRange::Bits8.to_full(200)), 200);
+    }
+
+    #[test]
+    fn stick_scaling_keeps_extremes() {
+        assert_eq!(StickRange::Full.to_full(i16::MIN), i16::MIN);
+        assert_eq!(StickRange::Half.to_full(0), 0);
+        assert_eq!(StickRange::Half.to_full(0x3FFF), i16::MAX);
+        assert_eq!(StickRange::Half.to_full(-0x4000), i16::MIN);
+        assert_eq!(StickRange::Half.to_full(0x2000), 0x4000);
+        assert_eq!(StickRange::Half.to_full(i16::MAX), i16::MAX);
+    }
+
+    #[test]
+    fn trigger_range_from_bits() {
+        assert_eq!(TriggerRange::try_from(12), Ok(TriggerRange::Bits12));
+        assert_eq!(TriggerRange::try_from(9), Err(9));
+    }
+}
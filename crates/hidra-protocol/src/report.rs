@@ -1,4 +1,47 @@
-use crate::PadState;
+use crate::caps::CreateError;
+use crate::error::{Error, Result};
+use crate::generic::GenericSpec;
+use crate::keyboard::{ERROR_ROLL_OVER, KEY_MAX, KEY_MIN};
+use crate::range::full_to_u8;
+use crate::transport::{self, BT_INPUT_SEED, BT_REPORT_LEN};
+use crate::{
+    Battery, Buttons, ChargeState, DeviceConfig, Features, KeyboardState, Modifiers, Motion,
+    MouseButtons, MouseState, PadState, TouchPoint,
+};
+use core::time::Duration;
+
+/// Touchpad resolutions, in wire units.
+const DS4_TOUCH_W: u16 = 1920;
+const DS4_TOUCH_H: u16 = 942;
+const DS5_TOUCH_W: u16 = 1920;
+const DS5_TOUCH_H: u16 = 1080;
+
+/// Rolling frame counter and sensor clock carried by DS4/DS5 input reports.
+/// The packers only read it; call `tick` once per emitted report.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ReportClock {
+    pub counter: u8,
+    pub micros: u64,
+}
+
+impl ReportClock {
+    pub fn tick(&mut self, elapsed: Duration) {
+        self.counter = self.counter.wrapping_add(1);
+        self.micros = self.micros.wrapping_add(elapsed.as_micros() as u64);
+    }
+
+    /// DS4 sensor timestamp, 16/3 us per tick.
+    #[inline]
+    pub fn ds4_timestamp(&self) -> u16 {
+        (self.micros.wrapping_mul(3) / 16) as u16
+    }
+
+    /// DualSense sensor timestamp, 1/3 us per tick.
+    #[inline]
+    pub fn ds5_timestamp(&self) -> u32 {
+        self.micros.wrapping_mul(3) as u32
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct X360Report(pub [u8; 14]);
@@ -16,27 +59,52 @@ impl X360Report {
     }
 }
 
-/// Pack IOCTL PadState into XUSB (Xbox 360) report.
-/// Assumptions:
-/// - `buttons`: already uses XUSB bit positions (u16).
-/// - `lx,ly,rx,ry`: signed i16, LE in the report.
-/// - `lt,rt`: u16; we clamp and downscale to 0..255.
-impl From<&PadState> for X360Report {
-    fn from(s: &PadState) -> Self {
+impl TryFrom<&[u8]> for X360Report {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(X360Report(exact(data, None)?))
+    }
+}
+
+impl X360Report {
+    /// Reconstruct the pad state. Triggers come back in 0..255.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        let i16_at = |i: usize| i16::from_le_bytes([b[i], b[i + 1]]);
+        PadState {
+            buttons: Buttons::from_xusb(u16::from_le_bytes([b[0], b[1]])),
+            lt: b[2] as u16,
+            rt: b[3] as u16,
+            lx: i16_at(4),
+            ly: i16_at(6),
+            rx: i16_at(8),
+            ry: i16_at(10),
+            ..Default::default()
+        }
+    }
+}
+
+impl X360Report {
+    /// Pack IOCTL PadState into XUSB (Xbox 360) report.
+    /// - `buttons`: mapped through `Buttons::to_xusb`; buttons XUSB lacks are dropped.
+    /// - `lx,ly,rx,ry`: scaled from the declared stick range to i16, LE in the report.
+    /// - `lt,rt`: scaled from the declared trigger range to 0..255.
+    /// - controls `cfg.subtype` lacks are zeroed, see `XusbSubtype::shape`.
+    pub fn pack(s: &PadState, cfg: &DeviceConfig) -> Self {
+        let s = &cfg.subtype.shape(&cfg.prepare(s));
         let mut b = [0u8; 14];
 
         // buttons (LE u16)
-        let btn = s.buttons.to_le_bytes();
+        let btn = s.buttons.to_xusb().to_le_bytes();
         b[0] = btn[0];
         b[1] = btn[1];
 
         // triggers
-        // If your lt/rt are already 0..255, this is a no-op.
-        // If they are 0..1023/4095, simple downscale (>> 2 / >> 4) works.
-        b[2] = (s.lt.min(255)) as u8;
-        b[3] = (s.rt.min(255)) as u8;
+        b[2] = full_to_u8(s.lt);
+        b[3] = full_to_u8(s.rt);
 
-        // sticks: pass through i16 (LE)
+        // sticks (LE)
         b[4..6].copy_from_slice(&s.lx.to_le_bytes());
         b[6..8].copy_from_slice(&s.ly.to_le_bytes());
         b[8..10].copy_from_slice(&s.rx.to_le_bytes());
@@ -46,60 +114,1090 @@ impl From<&PadState> for X360Report {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct DS4Report(pub Vec<u8>);
+/// Pack IOCTL PadState into XUSB report with 8-bit triggers and full-range sticks.
+impl From<&PadState> for X360Report {
+    fn from(s: &PadState) -> Self {
+        X360Report::pack(s, &DeviceConfig::default())
+    }
+}
 
-impl From<Vec<u8>> for DS4Report {
-    fn from(data: Vec<u8>) -> Self {
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DS4Report(pub [u8; 64]);
+
+impl From<[u8; 64]> for DS4Report {
+    fn from(data: [u8; 64]) -> Self {
         DS4Report(data)
     }
 }
 
 impl DS4Report {
+    /// USB input report ID.
+    pub const ID: u8 = 0x01;
+
     #[inline]
-    pub fn as_bytes(&self) -> &[u8] {
+    pub fn as_bytes(&self) -> &[u8; 64] {
         &self.0
     }
+
+    /// Pack IOCTL PadState into a DS4 USB input report (ID 0x01).
+    /// Layout:
+    /// - `[1..5]`: LX, LY, RX, RY re-centered to 0..255 (0x80 = center, Y grows downwards).
+    /// - `[5]`: hat in the low nibble, square/cross/circle/triangle in the high nibble.
+    /// - `[6]`: L1, R1, L2, R2, share, options, L3, R3.
+    /// - `[7]`: PS, touchpad click, 6-bit frame counter in the upper bits.
+    /// - `[8..10]`: L2/R2 analog.
+    /// - `[10..12]`: sensor timestamp (LE u16, 5.33us units).
+    /// - `[13..25]`: gyro/accel, `[30]`: power status.
+    /// - `[33..43]`: one touch packet with two points.
+    ///
+    /// Without `Features::GYRO` motion reads zero; without `Features::TOUCH` both
+    /// fingers read lifted (a real DS4 always reports its touchpad).
+    pub fn pack(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        let s = &cfg.prepare(s);
+        let mut b = [0u8; 64];
+        b[0] = Self::ID;
+
+        // sticks
+        b[1] = stick_to_u8(s.lx);
+        b[2] = stick_to_u8_inverted(s.ly);
+        b[3] = stick_to_u8(s.rx);
+        b[4] = stick_to_u8_inverted(s.ry);
+
+        // hat + face buttons, shoulders, share/options, stick clicks, PS, touchpad click
+        b[5..8].copy_from_slice(&s.buttons.to_ds4());
+
+        // digital triggers, frame counter
+        let (lt, rt) = (full_to_u8(s.lt), full_to_u8(s.rt));
+        b[6] |= if lt > 0 { 0x04 } else { 0 } | if rt > 0 { 0x08 } else { 0 };
+        b[7] |= (clock.counter & 0x3F) << 2;
+
+        // analog triggers
+        b[8] = lt;
+        b[9] = rt;
+
+        b[10..12].copy_from_slice(&clock.ds4_timestamp().to_le_bytes());
+
+        b[13..25].copy_from_slice(&pack_motion(&s.motion));
+        b[30] = ds4_power_status(&s.battery);
+
+        // one touch packet
+        b[33] = 1;
+        b[34] = clock.counter;
+        b[35..39].copy_from_slice(&pack_touch(&s.touch[0], DS4_TOUCH_W, DS4_TOUCH_H));
+        b[39..43].copy_from_slice(&pack_touch(&s.touch[1], DS4_TOUCH_W, DS4_TOUCH_H));
+
+        DS4Report(b)
+    }
+}
+
+impl TryFrom<&[u8]> for DS4Report {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(DS4Report(exact(data, Some(Self::ID))?))
+    }
 }
 
-/// Pack IOCTL PadState into DS4 report.
+impl DS4Report {
+    /// Reconstruct the pad state. Sticks and triggers come back at 8-bit precision,
+    /// touch coordinates at the pad's resolution.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        PadState {
+            buttons: Buttons::from_ds4([b[5], b[6], b[7]]),
+            lx: u8_to_stick(b[1]),
+            ly: u8_to_stick_inverted(b[2]),
+            rx: u8_to_stick(b[3]),
+            ry: u8_to_stick_inverted(b[4]),
+            lt: b[8] as u16,
+            rt: b[9] as u16,
+            touch: [
+                unpack_touch(&b[35..39], DS4_TOUCH_W, DS4_TOUCH_H),
+                unpack_touch(&b[39..43], DS4_TOUCH_W, DS4_TOUCH_H),
+            ],
+            motion: unpack_motion(&b[13..25]),
+            battery: ds4_battery(b[30]),
+            ..Default::default()
+        }
+    }
+
+    /// 6-bit frame counter.
+    pub fn counter(&self) -> u8 {
+        self.0[7] >> 2
+    }
+}
+
+/// Pack IOCTL PadState into DS4 report for a device created with default settings,
+/// with a zeroed frame counter and timestamp.
 impl From<&PadState> for DS4Report {
-    fn from(_s: &PadState) -> Self {
-        todo!("DS4 report packing not implemented yet");
+    fn from(s: &PadState) -> Self {
+        DS4Report::pack(s, &ReportClock::default(), &DeviceConfig::default())
+    }
+}
+
+/// HID | CRC flags in byte 1 of DS4 Bluetooth reports.
+const DS4_BT_FLAGS: u8 = 0xC0;
+
+/// DS4 Bluetooth input report 0x11: `[1]` flags, `[3..66]` the USB report body,
+/// `[74..78]` CRC32 seeded with `BT_INPUT_SEED`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DS4BtReport(pub [u8; BT_REPORT_LEN]);
+
+impl DS4BtReport {
+    pub const ID: u8 = 0x11;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; BT_REPORT_LEN] {
+        &self.0
+    }
+
+    pub fn pack(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        Self::wrap(&DS4Report::pack(s, clock, cfg))
+    }
+
+    /// Bluetooth framing of a USB report.
+    pub fn wrap(usb: &DS4Report) -> Self {
+        let mut b = [0u8; BT_REPORT_LEN];
+        b[0] = Self::ID;
+        b[1] = DS4_BT_FLAGS;
+        b[3..66].copy_from_slice(&usb.0[1..]);
+        transport::seal(BT_INPUT_SEED, &mut b);
+        DS4BtReport(b)
+    }
+
+    /// The USB report carrying the same data.
+    pub fn usb(&self) -> DS4Report {
+        let mut b = [0u8; 64];
+        b[0] = DS4Report::ID;
+        b[1..].copy_from_slice(&self.0[3..66]);
+        DS4Report(b)
+    }
+
+    pub fn state(&self) -> PadState {
+        self.usb().state()
+    }
+}
+
+/// Checks length, report ID and CRC.
+impl TryFrom<&[u8]> for DS4BtReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        let b = exact(data, Some(Self::ID))?;
+        transport::verify(BT_INPUT_SEED, &b)?;
+        Ok(DS4BtReport(b))
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct DS5Report(pub Vec<u8>);
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DS5Report(pub [u8; 64]);
 
-impl From<Vec<u8>> for DS5Report {
-    fn from(data: Vec<u8>) -> Self {
+impl From<[u8; 64]> for DS5Report {
+    fn from(data: [u8; 64]) -> Self {
         DS5Report(data)
     }
 }
 
 impl DS5Report {
+    /// USB input report ID.
+    pub const ID: u8 = 0x01;
+
     #[inline]
-    pub fn as_bytes(&self) -> &[u8] {
+    pub fn as_bytes(&self) -> &[u8; 64] {
         &self.0
     }
+
+    /// Pack IOCTL PadState into a DualSense USB input report (ID 0x01).
+    /// Layout:
+    /// - `[1..5]`: LX, LY, RX, RY re-centered to 0..255 (0x80 = center, Y grows downwards).
+    /// - `[5..7]`: L2/R2 analog.
+    /// - `[7]`: sequence number.
+    /// - `[8]`: hat in the low nibble, square/cross/circle/triangle in the high nibble.
+    /// - `[9]`: L1, R1, L2, R2, create, options, L3, R3.
+    /// - `[10]`: PS, touchpad click, mute.
+    /// - `[16..28]`: gyro/accel, `[28..32]`: sensor timestamp (LE u32, 0.33us units).
+    /// - `[33..41]`: two touch points, `[53]`: battery/charging status.
+    ///
+    /// Motion and touch sections for features the device was created without stay zeroed.
+    pub fn pack(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        Self::pack_with(s, clock, cfg, Buttons::to_ds5)
+    }
+
+    /// `pack` for a DualSense Edge: `[10]` also carries Fn left/right and the
+    /// left/right back buttons.
+    pub fn pack_edge(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        Self::pack_with(s, clock, cfg, Buttons::to_ds5_edge)
+    }
+
+    fn pack_with(
+        s: &PadState,
+        clock: &ReportClock,
+        cfg: &DeviceConfig,
+        buttons: fn(Buttons) -> [u8; 4],
+    ) -> Self {
+        let s = &cfg.prepare(s);
+        let mut b = [0u8; 64];
+        b[0] = Self::ID;
+
+        // sticks
+        b[1] = stick_to_u8(s.lx);
+        b[2] = stick_to_u8_inverted(s.ly);
+        b[3] = stick_to_u8(s.rx);
+        b[4] = stick_to_u8_inverted(s.ry);
+
+        // analog triggers
+        let (lt, rt) = (full_to_u8(s.lt), full_to_u8(s.rt));
+        b[5] = lt;
+        b[6] = rt;
+
+        b[7] = clock.counter;
+
+        // hat + face buttons, shoulders, create/options, stick clicks, PS, touchpad, mute
+        b[8..12].copy_from_slice(&buttons(s.buttons));
+
+        // digital triggers
+        b[9] |= if lt > 0 { 0x04 } else { 0 } | if rt > 0 { 0x08 } else { 0 };
+
+        if cfg.features.contains(Features::GYRO) {
+            b[16..28].copy_from_slice(&pack_motion(&s.motion));
+            b[28..32].copy_from_slice(&clock.ds5_timestamp().to_le_bytes());
+        }
+
+        if cfg.features.contains(Features::TOUCH) {
+            b[33..37].copy_from_slice(&pack_touch(&s.touch[0], DS5_TOUCH_W, DS5_TOUCH_H));
+            b[37..41].copy_from_slice(&pack_touch(&s.touch[1], DS5_TOUCH_W, DS5_TOUCH_H));
+        }
+
+        b[53] = ds5_power_status(&s.battery);
+
+        DS5Report(b)
+    }
+}
+
+impl TryFrom<&[u8]> for DS5Report {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(DS5Report(exact(data, Some(Self::ID))?))
+    }
+}
+
+impl DS5Report {
+    /// Reconstruct the pad state. Sticks and triggers come back at 8-bit precision,
+    /// touch coordinates at the pad's resolution. Edge buttons are read too; a
+    /// plain DualSense leaves their bits clear.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        PadState {
+            buttons: Buttons::from_ds5_edge([b[8], b[9], b[10], b[11]]),
+            lx: u8_to_stick(b[1]),
+            ly: u8_to_stick_inverted(b[2]),
+            rx: u8_to_stick(b[3]),
+            ry: u8_to_stick_inverted(b[4]),
+            lt: b[5] as u16,
+            rt: b[6] as u16,
+            touch: [
+                unpack_touch(&b[33..37], DS5_TOUCH_W, DS5_TOUCH_H),
+                unpack_touch(&b[37..41], DS5_TOUCH_W, DS5_TOUCH_H),
+            ],
+            motion: unpack_motion(&b[16..28]),
+            battery: ds5_battery(b[53]),
+            ..Default::default()
+        }
+    }
+
+    pub fn sequence(&self) -> u8 {
+        self.0[7]
+    }
 }
 
-/// Pack IOCTL PadState into DS5 report.
+/// Pack IOCTL PadState into DS5 report for a device created with default settings.
 impl From<&PadState> for DS5Report {
-    fn from(_s: &PadState) -> Self {
-        todo!("DS5 report packing not implemented yet");
+    fn from(s: &PadState) -> Self {
+        DS5Report::pack(s, &ReportClock::default(), &DeviceConfig::default())
+    }
+}
+
+/// DualSense Bluetooth input report 0x31: `[1]` sequence tag in the high nibble,
+/// `[2..65]` the USB report body, `[74..78]` CRC32 seeded with `BT_INPUT_SEED`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DS5BtReport(pub [u8; BT_REPORT_LEN]);
+
+impl DS5BtReport {
+    pub const ID: u8 = 0x31;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; BT_REPORT_LEN] {
+        &self.0
+    }
+
+    pub fn pack(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        Self::wrap(&DS5Report::pack(s, clock, cfg), clock.counter)
+    }
+
+    pub fn pack_edge(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        Self::wrap(&DS5Report::pack_edge(s, clock, cfg), clock.counter)
+    }
+
+    /// Bluetooth framing of a USB report; only the low 4 bits of `seq` are sent.
+    pub fn wrap(usb: &DS5Report, seq: u8) -> Self {
+        let mut b = [0u8; BT_REPORT_LEN];
+        b[0] = Self::ID;
+        b[1] = (seq & 0x0F) << 4;
+        b[2..65].copy_from_slice(&usb.0[1..]);
+        transport::seal(BT_INPUT_SEED, &mut b);
+        DS5BtReport(b)
+    }
+
+    /// The USB report carrying the same data.
+    pub fn usb(&self) -> DS5Report {
+        let mut b = [0u8; 64];
+        b[0] = DS5Report::ID;
+        b[1..].copy_from_slice(&self.0[2..65]);
+        DS5Report(b)
+    }
+
+    pub fn state(&self) -> PadState {
+        self.usb().state()
+    }
+
+    /// 4-bit sequence tag.
+    pub fn tag(&self) -> u8 {
+        self.0[1] >> 4
+    }
+}
+
+/// Checks length, report ID and CRC.
+impl TryFrom<&[u8]> for DS5BtReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        let b = exact(data, Some(Self::ID))?;
+        transport::verify(BT_INPUT_SEED, &b)?;
+        Ok(DS5BtReport(b))
+    }
+}
+
+/// Xbox One/Series input report 0x01, the layout the pad uses over Bluetooth HID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct XboxOneReport(pub [u8; 17]);
+
+impl From<[u8; 17]> for XboxOneReport {
+    fn from(data: [u8; 17]) -> Self {
+        XboxOneReport(data)
+    }
+}
+
+impl XboxOneReport {
+    pub const ID: u8 = 0x01;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 17] {
+        &self.0
+    }
+
+    /// Pack IOCTL PadState into an Xbox One input report (ID 0x01).
+    /// Layout:
+    /// - `[1..9]`: LX, LY, RX, RY as LE u16 (0x8000 = center, Y grows downwards).
+    /// - `[9..11]`, `[11..13]`: LT, RT as LE 10-bit values.
+    /// - `[13]`: hat, 1 = N clockwise to 8 = NW, 0 = neutral.
+    /// - `[14]`: A, B, -, X, Y, -, LB, RB.
+    /// - `[15]`: -, -, view, menu, guide, LS, RS.
+    /// - `[16]`: share.
+    pub fn pack(s: &PadState, cfg: &DeviceConfig) -> Self {
+        Self::pack_with(s, cfg, Buttons::to_xbox_one)
+    }
+
+    /// `pack` for an Xbox Elite Series 2: `[16]` also carries paddles P1..P4 after share.
+    pub fn pack_elite(s: &PadState, cfg: &DeviceConfig) -> Self {
+        Self::pack_with(s, cfg, Buttons::to_xbox_elite)
+    }
+
+    fn pack_with(s: &PadState, cfg: &DeviceConfig, buttons: fn(Buttons) -> [u8; 4]) -> Self {
+        let s = &cfg.prepare(s);
+        let mut b = [0u8; 17];
+        b[0] = Self::ID;
+
+        // sticks
+        b[1..3].copy_from_slice(&stick_to_u16(s.lx).to_le_bytes());
+        b[3..5].copy_from_slice(&stick_to_u16(s.ly.saturating_neg()).to_le_bytes());
+        b[5..7].copy_from_slice(&stick_to_u16(s.rx).to_le_bytes());
+        b[7..9].copy_from_slice(&stick_to_u16(s.ry.saturating_neg()).to_le_bytes());
+
+        // triggers, full u16 scale down to 10 bits
+        b[9..11].copy_from_slice(&(s.lt >> 6).to_le_bytes());
+        b[11..13].copy_from_slice(&(s.rt >> 6).to_le_bytes());
+
+        b[13..17].copy_from_slice(&buttons(s.buttons));
+
+        XboxOneReport(b)
+    }
+
+    /// Reconstruct the pad state. Triggers come back in 0..1023. Elite paddles are
+    /// read too; other pads leave their bits clear.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        let u16_at = |i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+        PadState {
+            buttons: Buttons::from_xbox_elite([b[13], b[14], b[15], b[16]]),
+            lx: u16_to_stick(u16_at(1)),
+            ly: u16_to_stick(u16_at(3)).saturating_neg(),
+            rx: u16_to_stick(u16_at(5)),
+            ry: u16_to_stick(u16_at(7)).saturating_neg(),
+            lt: u16_at(9) & 0x3FF,
+            rt: u16_at(11) & 0x3FF,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for XboxOneReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(XboxOneReport(exact(data, Some(Self::ID))?))
+    }
+}
+
+/// Pack IOCTL PadState into an Xbox One report for a device created with default settings.
+impl From<&PadState> for XboxOneReport {
+    fn from(s: &PadState) -> Self {
+        XboxOneReport::pack(s, &DeviceConfig::default())
+    }
+}
+
+/// Switch Pro Controller standard full input report 0x30. The session in
+/// `switch_pro` decides when it may be sent and builds the 0x21/0x81 replies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SwitchProReport(pub [u8; 64]);
+
+impl From<[u8; 64]> for SwitchProReport {
+    fn from(data: [u8; 64]) -> Self {
+        SwitchProReport(data)
+    }
+}
+
+impl SwitchProReport {
+    pub const ID: u8 = 0x30;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+
+    /// Pack IOCTL PadState into a standard full input report (ID 0x30).
+    /// Layout:
+    /// - `[1]`: timer (frame counter).
+    /// - `[2]`: battery level 0..8 and charging bit in the high nibble, powered bit in the low.
+    /// - `[3..6]`: right, shared and left button bytes, see `SWITCH_PRO_LAYOUT`.
+    /// - `[6..9]`, `[9..12]`: left and right stick, two 12-bit values each (0x800 = center,
+    ///   Y grows upwards).
+    /// - `[13..49]`: three IMU samples, accel then gyro, LE i16 each.
+    ///
+    /// ZL/ZR are digital; any trigger travel presses them. The IMU section stays
+    /// zeroed without `Features::GYRO`.
+    pub fn pack(s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> Self {
+        let s = &cfg.prepare(s);
+        let mut b = [0u8; 64];
+        b[0] = Self::ID;
+        b[1] = clock.counter;
+        b[2] = switch_power_status(&s.battery);
+
+        b[3..6].copy_from_slice(&s.buttons.to_switch_pro());
+        b[3] |= if full_to_u8(s.rt) > 0 { 0x80 } else { 0 };
+        b[5] |= if full_to_u8(s.lt) > 0 { 0x80 } else { 0 };
+
+        b[6..9].copy_from_slice(&pack_stick12(s.lx, s.ly));
+        b[9..12].copy_from_slice(&pack_stick12(s.rx, s.ry));
+
+        if cfg.features.contains(Features::GYRO) {
+            let m = &s.motion;
+            let sample = pack_motion(&Motion { gyro: m.accel, accel: m.gyro });
+            for chunk in b[13..49].chunks_exact_mut(12) {
+                chunk.copy_from_slice(&sample);
+            }
+        }
+
+        SwitchProReport(b)
+    }
+
+    /// Reconstruct the pad state from the first IMU sample. Sticks come back at
+    /// 12-bit precision, triggers as 0 or 255.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        let (lx, ly) = unpack_stick12(&b[6..9]);
+        let (rx, ry) = unpack_stick12(&b[9..12]);
+        let m = unpack_motion(&b[13..25]);
+        PadState {
+            buttons: Buttons::from_switch_pro([b[3], b[4], b[5]]),
+            lx,
+            ly,
+            rx,
+            ry,
+            lt: if b[5] & 0x80 != 0 { 255 } else { 0 },
+            rt: if b[3] & 0x80 != 0 { 255 } else { 0 },
+            motion: Motion { gyro: m.accel, accel: m.gyro },
+            battery: switch_battery(b[2]),
+            ..Default::default()
+        }
+    }
+
+    pub fn timer(&self) -> u8 {
+        self.0[1]
+    }
+}
+
+impl TryFrom<&[u8]> for SwitchProReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(SwitchProReport(exact(data, Some(Self::ID))?))
+    }
+}
+
+/// Pack IOCTL PadState into a Switch Pro report for a device created with default settings.
+impl From<&PadState> for SwitchProReport {
+    fn from(s: &PadState) -> Self {
+        SwitchProReport::pack(s, &ReportClock::default(), &DeviceConfig::default())
+    }
+}
+
+/// Racing wheel input report 0x01.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WheelReport(pub [u8; 11]);
+
+impl From<[u8; 11]> for WheelReport {
+    fn from(data: [u8; 11]) -> Self {
+        WheelReport(data)
+    }
+}
+
+impl WheelReport {
+    pub const ID: u8 = 0x01;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 11] {
+        &self.0
+    }
+
+    /// Pack IOCTL PadState into a wheel input report (ID 0x01).
+    /// Layout, all LE u16:
+    /// - `[1..3]`: steering from LX, 0x8000 = center.
+    /// - `[3..5]`, `[5..7]`: throttle from RT, brake from LT, 0 = released.
+    /// - `[7..9]`: clutch from RY's upper half, so a resting stick leaves it released.
+    /// - `[9..11]`: hat in the low nibble, then buttons per `WHEEL_LAYOUT`.
+    pub fn pack(s: &PadState, cfg: &DeviceConfig) -> Self {
+        let s = &cfg.prepare(s);
+        let mut b = [0u8; 11];
+        b[0] = Self::ID;
+        b[1..3].copy_from_slice(&stick_to_u16(s.lx).to_le_bytes());
+        b[3..5].copy_from_slice(&s.rt.to_le_bytes());
+        b[5..7].copy_from_slice(&s.lt.to_le_bytes());
+        b[7..9].copy_from_slice(&stick_to_pedal(s.ry).to_le_bytes());
+        b[9..11].copy_from_slice(&s.buttons.to_wheel());
+        WheelReport(b)
+    }
+
+    /// Reconstruct the pad state. Triggers come back at full u16 scale.
+    pub fn state(&self) -> PadState {
+        let b = &self.0;
+        let u16_at = |i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+        PadState {
+            buttons: Buttons::from_wheel([b[9], b[10]]),
+            lx: u16_to_stick(u16_at(1)),
+            rt: u16_at(3),
+            lt: u16_at(5),
+            ry: pedal_to_stick(u16_at(7)),
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for WheelReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(WheelReport(exact(data, Some(Self::ID))?))
+    }
+}
+
+/// Pack IOCTL PadState into a wheel report for a device created with default settings.
+impl From<&PadState> for WheelReport {
+    fn from(s: &PadState) -> Self {
+        WheelReport::pack(s, &DeviceConfig::default())
+    }
+}
+
+/// Stick axis 0..=i16::MAX to a 0..=65535 pedal; the lower half reads as released.
+#[inline]
+fn stick_to_pedal(v: i16) -> u16 {
+    (v.max(0) as u32 * 0xFFFF / i16::MAX as u32) as u16
+}
+
+/// Inverse of `stick_to_pedal`.
+#[inline]
+fn pedal_to_stick(v: u16) -> i16 {
+    ((v as u32 * i16::MAX as u32 + 0x7FFF) / 0xFFFF) as i16
+}
+
+/// Boot protocol keyboard input report, no report ID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeyboardReport(pub [u8; 8]);
+
+impl From<[u8; 8]> for KeyboardReport {
+    fn from(data: [u8; 8]) -> Self {
+        KeyboardReport(data)
+    }
+}
+
+impl KeyboardReport {
+    /// Key slots in the report.
+    pub const ROLLOVER: usize = 6;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+
+    /// Pack into the boot layout:
+    /// - `[0]`: modifiers, `[1]`: reserved.
+    /// - `[2..8]`: keys down, lowest usage first. With more than six down every
+    ///   slot reads `ERROR_ROLL_OVER` and only the modifiers get through, as on a
+    ///   real keyboard.
+    pub fn pack(s: &KeyboardState) -> Self {
+        let mut b = [0u8; 8];
+        b[0] = s.modifiers.bits();
+        if s.pressed().nth(Self::ROLLOVER).is_some() {
+            b[2..].fill(ERROR_ROLL_OVER);
+        } else {
+            b[2..].iter_mut().zip(s.pressed()).for_each(|(slot, u)| *slot = u);
+        }
+        KeyboardReport(b)
+    }
+
+    pub fn is_rollover(&self) -> bool {
+        self.0[2] == ERROR_ROLL_OVER
+    }
+
+    /// Reconstruct the state; a rollover report has no keys down.
+    pub fn state(&self) -> KeyboardState {
+        let mut s = KeyboardState {
+            modifiers: Modifiers::from_bits_retain(self.0[0]),
+            ..Default::default()
+        };
+        if !self.is_rollover() {
+            self.0[2..].iter().filter(|u| **u >= KEY_MIN).for_each(|u| s.press(*u));
+        }
+        s
+    }
+}
+
+impl TryFrom<&[u8]> for KeyboardReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(KeyboardReport(exact(data, None)?))
+    }
+}
+
+/// Bytes of the NKRO key bitmap, one bit per usage `0..=KEY_MAX`.
+const NKRO_KEY_BYTES: usize = KEY_MAX as usize / 8 + 1;
+
+/// N-key rollover keyboard input report, no report ID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeyboardNkroReport(pub [u8; 2 + NKRO_KEY_BYTES]);
+
+impl From<[u8; 2 + NKRO_KEY_BYTES]> for KeyboardNkroReport {
+    fn from(data: [u8; 2 + NKRO_KEY_BYTES]) -> Self {
+        KeyboardNkroReport(data)
+    }
+}
+
+impl KeyboardNkroReport {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 2 + NKRO_KEY_BYTES] {
+        &self.0
+    }
+
+    /// Pack every key down:
+    /// - `[0]`: modifiers, `[1]`: reserved, as in `KeyboardReport`.
+    /// - `[2..30]`: bit `u % 8` of byte `2 + u / 8` per usage `u`, `0..KEY_MIN` always clear.
+    pub fn pack(s: &KeyboardState) -> Self {
+        let mut b = [0u8; 2 + NKRO_KEY_BYTES];
+        b[0] = s.modifiers.bits();
+        for u in s.pressed() {
+            b[2 + u as usize / 8] |= 1 << (u % 8);
+        }
+        KeyboardNkroReport(b)
+    }
+
+    pub fn state(&self) -> KeyboardState {
+        let mut s = KeyboardState {
+            modifiers: Modifiers::from_bits_retain(self.0[0]),
+            ..Default::default()
+        };
+        s.keys[..NKRO_KEY_BYTES].copy_from_slice(&self.0[2..]);
+        s.keys[0] &= !((1 << KEY_MIN) - 1);
+        s
+    }
+}
+
+impl TryFrom<&[u8]> for KeyboardNkroReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(KeyboardNkroReport(exact(data, None)?))
+    }
+}
+
+/// Boot-compatible mouse input report, no report ID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MouseReport(pub [u8; 5]);
+
+impl From<[u8; 5]> for MouseReport {
+    fn from(data: [u8; 5]) -> Self {
+        MouseReport(data)
+    }
+}
+
+impl MouseReport {
+    /// Largest step on any axis in one report.
+    pub const MAX_STEP: i16 = 127;
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 5] {
+        &self.0
+    }
+
+    /// Pack as much of `s`'s motion as one report carries and leave the rest
+    /// in `s`; call again until `s.is_still()`. Buttons stay as they are.
+    /// Layout: `[0]` buttons, then i8 X, Y, wheel and AC pan in `[1..5]`. The
+    /// first three bytes are the boot protocol report.
+    pub fn take(s: &mut MouseState) -> Self {
+        let step = |v: &mut i16| {
+            let d = (*v).clamp(-Self::MAX_STEP, Self::MAX_STEP);
+            *v -= d;
+            d as i8 as u8
+        };
+        MouseReport([
+            s.buttons.bits(),
+            step(&mut s.dx),
+            step(&mut s.dy),
+            step(&mut s.wheel),
+            step(&mut s.pan),
+        ])
+    }
+
+    pub fn state(&self) -> MouseState {
+        let b = &self.0;
+        MouseState {
+            buttons: MouseButtons::from_bits_retain(b[0]),
+            dx: b[1] as i8 as i16,
+            dy: b[2] as i8 as i16,
+            wheel: b[3] as i8 as i16,
+            pan: b[4] as i8 as i16,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for MouseReport {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Ok(MouseReport(exact(data, None)?))
+    }
+}
+
+/// Largest input report any valid `GenericSpec` produces.
+pub const GENERIC_MAX_REPORT_LEN: usize = 15;
+
+/// Input report of a `DeviceKind::Generic` pad, laid out by its `GenericSpec`.
+/// There is no report ID; fields are packed LSB-first with no padding between them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GenericReport {
+    bytes: [u8; GENERIC_MAX_REPORT_LEN],
+    len: u8,
+}
+
+impl GenericReport {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Pack IOCTL PadState by `cfg.generic`.
+    /// Layout:
+    /// - `axes` values of `axis_bits` each, taken from LX, LY, RX, RY, LT, RT in
+    ///   that order. Sticks are centered at half range, Y grows downwards.
+    /// - with a hat, a 4-bit hat, 0 = N clockwise, 8 = neutral.
+    /// - `buttons` bits in `GENERIC_ORDER`, then zero padding to a whole byte.
+    pub fn pack(s: &PadState, cfg: &DeviceConfig) -> Self {
+        let spec = &cfg.generic;
+        let s = &cfg.prepare(s);
+        let full = [
+            stick_to_u16(s.lx),
+            stick_to_u16(s.ly.saturating_neg()),
+            stick_to_u16(s.rx),
+            stick_to_u16(s.ry.saturating_neg()),
+            s.lt,
+            s.rt,
+        ];
+        let mut r =
+            GenericReport { bytes: [0; GENERIC_MAX_REPORT_LEN], len: spec.report_len() as u8 };
+        let width = spec.axis_bits as usize;
+        let mut at = 0;
+        for v in full.iter().take(spec.axes as usize) {
+            put_bits(&mut r.bytes, at, width, (*v >> (16 - width)) as u32);
+            at += width;
+        }
+        if spec.hats > 0 {
+            put_bits(&mut r.bytes, at, 4, s.buttons.hat() as u32);
+            at += 4;
+        }
+        put_bits(&mut r.bytes, at, spec.buttons as usize, s.buttons.to_generic(spec.buttons));
+        r
+    }
+
+    /// Checks `spec` is valid and `data` is exactly `spec.report_len()` bytes.
+    pub fn parse(data: &[u8], spec: &GenericSpec) -> Result<Self> {
+        check_spec(spec)?;
+        let len = spec.report_len();
+        if data.len() != len {
+            return Err(Error::Length { expected: len, got: data.len() });
+        }
+        let mut bytes = [0; GENERIC_MAX_REPORT_LEN];
+        bytes[..len].copy_from_slice(data);
+        Ok(GenericReport { bytes, len: len as u8 })
+    }
+
+    /// Reconstruct the pad state. Triggers come back at `axis_bits` resolution,
+    /// and axes the spec leaves out read as centered/released. `spec` must be
+    /// valid and match the report's length, as in `parse`.
+    pub fn state(&self, spec: &GenericSpec) -> Result<PadState> {
+        check_spec(spec)?;
+        if spec.report_len() != self.len as usize {
+            return Err(Error::Length { expected: spec.report_len(), got: self.len as usize });
+        }
+        let b = &self.bytes;
+        let width = spec.axis_bits as usize;
+        let mut full = [0x8000, 0x8000, 0x8000, 0x8000, 0, 0];
+        let mut raw = [0u16; 6];
+        let mut at = 0;
+        for i in 0..spec.axes as usize {
+            raw[i] = get_bits(b, at, width) as u16;
+            full[i] = raw[i] << (16 - width);
+            at += width;
+        }
+        let mut buttons = Buttons::empty();
+        if spec.hats > 0 {
+            buttons |= Buttons::from_hat(get_bits(b, at, 4) as u8);
+            at += 4;
+        }
+        buttons |= Buttons::from_generic(get_bits(b, at, spec.buttons as usize), spec.buttons);
+        Ok(PadState {
+            buttons,
+            lx: u16_to_stick(full[0]),
+            ly: u16_to_stick(full[1]).saturating_neg(),
+            rx: u16_to_stick(full[2]),
+            ry: u16_to_stick(full[3]).saturating_neg(),
+            lt: raw[4],
+            rt: raw[5],
+            ..Default::default()
+        })
+    }
+}
+
+/// A spec out of `GenericSpec::validate`'s limits would index past the axis
+/// and report buffers.
+fn check_spec(spec: &GenericSpec) -> Result<()> {
+    spec.validate().map_err(|e| match e {
+        CreateError::InvalidGenericSpec { field, value } => {
+            Error::Value { field: field.name(), value: value as u32 }
+        }
+        _ => Error::Value { field: "generic spec", value: 0 },
+    })
+}
+
+/// Write the low `bits` of `v` at bit offset `at`, LSB-first.
+fn put_bits(b: &mut [u8], at: usize, bits: usize, v: u32) {
+    for i in 0..bits {
+        if v & (1 << i) != 0 {
+            b[(at + i) / 8] |= 1 << ((at + i) % 8);
+        }
+    }
+}
+
+/// Inverse of `put_bits`.
+fn get_bits(b: &[u8], at: usize, bits: usize) -> u32 {
+    (0..bits).filter(|i| b[(at + i) / 8] & (1 << ((at + i) % 8)) != 0).fold(0, |v, i| v | 1 << i)
+}
+
+/// Copy `data` into a fixed-size report, checking its length and leading report ID.
+fn exact<const N: usize>(data: &[u8], id: Option<u8>) -> Result<[u8; N]> {
+    let b: [u8; N] = data.try_into().map_err(|_| Error::Length { expected: N, got: data.len() })?;
+    match id {
+        Some(id) if b[0] != id => Err(Error::ReportId(b[0])),
+        _ => Ok(b),
+    }
+}
+
+/// i16 stick axis to 0..255 with 0x80 at rest.
+#[inline]
+fn stick_to_u8(v: i16) -> u8 {
+    ((v as i32 + 0x8000) >> 8) as u8
+}
+
+/// Same as `stick_to_u8`, for axes where HID grows the opposite way from XInput (Y).
+#[inline]
+fn stick_to_u8_inverted(v: i16) -> u8 {
+    stick_to_u8(v.saturating_neg())
+}
+
+/// i16 stick axis to 0..65535 with 0x8000 at rest.
+#[inline]
+fn stick_to_u16(v: i16) -> u16 {
+    (v as i32 + 0x8000) as u16
+}
+
+/// Inverse of `stick_to_u16`.
+#[inline]
+fn u16_to_stick(v: u16) -> i16 {
+    (v as i32 - 0x8000) as i16
+}
+
+/// Stick pair as two 12-bit values (0x800 = center) packed into 3 bytes, X first.
+fn pack_stick12(x: i16, y: i16) -> [u8; 3] {
+    let x = ((x as i32 + 0x8000) >> 4) as u16;
+    let y = ((y as i32 + 0x8000) >> 4) as u16;
+    [x as u8, (x >> 8) as u8 | ((y & 0x0F) << 4) as u8, (y >> 4) as u8]
+}
+
+/// Inverse of `pack_stick12`.
+fn unpack_stick12(b: &[u8]) -> (i16, i16) {
+    let x = b[0] as i32 | ((b[1] as i32 & 0x0F) << 8);
+    let y = (b[1] as i32 >> 4) | ((b[2] as i32) << 4);
+    (((x - 0x800) << 4) as i16, ((y - 0x800) << 4) as i16)
+}
+
+/// Touch point to the 4-byte form shared by DS4 and DS5: contact byte
+/// (bit 7 = lifted, low 7 bits = tracking ID), then 12-bit X and Y packed LE.
+fn pack_touch(p: &TouchPoint, width: u16, height: u16) -> [u8; 4] {
+    let x = (p.x as u32 * (width - 1) as u32 / u16::MAX as u32) as u16;
+    let y = (p.y as u32 * (height - 1) as u32 / u16::MAX as u32) as u16;
+    let contact = (p.id & 0x7F) | if p.down != 0 { 0 } else { 0x80 };
+    [contact, x as u8, ((x >> 8) & 0x0F) as u8 | ((y & 0x0F) << 4) as u8, (y >> 4) as u8]
+}
+
+/// Inverse of `stick_to_u8`; 0x80 reads back as exactly 0.
+#[inline]
+fn u8_to_stick(v: u8) -> i16 {
+    ((v as i32 - 0x80) << 8) as i16
+}
+
+/// Inverse of `stick_to_u8_inverted`.
+#[inline]
+fn u8_to_stick_inverted(v: u8) -> i16 {
+    u8_to_stick(v).saturating_neg()
+}
+
+/// Inverse of `pack_touch`, back to normalized coordinates.
+fn unpack_touch(b: &[u8], width: u16, height: u16) -> TouchPoint {
+    let x = b[1] as u32 | ((b[2] as u32 & 0x0F) << 8);
+    let y = (b[2] as u32 >> 4) | ((b[3] as u32) << 4);
+    TouchPoint {
+        down: (b[0] & 0x80 == 0) as u8,
+        id: b[0] & 0x7F,
+        x: (x * u16::MAX as u32).div_ceil(width as u32 - 1).min(u16::MAX as u32) as u16,
+        y: (y * u16::MAX as u32).div_ceil(height as u32 - 1).min(u16::MAX as u32) as u16,
     }
 }
 
+/// Gyro then accel, LE i16 each.
+fn pack_motion(m: &Motion) -> [u8; 12] {
+    let mut b = [0u8; 12];
+    for (i, v) in m.gyro.iter().chain(m.accel.iter()).enumerate() {
+        b[i * 2..i * 2 + 2].copy_from_slice(&v.to_le_bytes());
+    }
+    b
+}
+
+/// DS4: level in tenths in the low nibble (11 = full), bit 4 = cable connected.
+fn ds4_power_status(bat: &Battery) -> u8 {
+    let level = bat.level.min(100) / 10;
+    match bat.charge_state() {
+        ChargeState::Discharging => level,
+        ChargeState::Charging => 0x10 | level,
+        ChargeState::Full => 0x10 | 0x0B,
+    }
+}
+
+fn unpack_motion(b: &[u8]) -> Motion {
+    let v = |i: usize| i16::from_le_bytes([b[i * 2], b[i * 2 + 1]]);
+    Motion { gyro: [v(0), v(1), v(2)], accel: [v(3), v(4), v(5)] }
+}
+
+fn ds4_battery(status: u8) -> Battery {
+    let level = status & 0x0F;
+    let charge = match (status & 0x10 != 0, level) {
+        (true, 11) => ChargeState::Full,
+        (true, _) => ChargeState::Charging,
+        (false, _) => ChargeState::Discharging,
+    };
+    Battery { level: (level * 10).min(100), charge: charge as u8 }
+}
+
+fn ds5_battery(status: u8) -> Battery {
+    Battery { level: ((status & 0x0F) * 10).min(100), charge: (status >> 4).min(2) }
+}
+
+/// DS5: level in tenths in the low nibble, charge state in the high nibble.
+fn ds5_power_status(bat: &Battery) -> u8 {
+    let level = bat.level.min(100) / 10;
+    ((bat.charge_state() as u8) << 4) | level
+}
+
+/// Switch: level 0..8 in even steps in bits 5..7, bit 4 = charging, bit 0 = external power.
+fn switch_power_status(bat: &Battery) -> u8 {
+    let level = (bat.level.min(100) / 25) * 2;
+    match bat.charge_state() {
+        ChargeState::Discharging => level << 4,
+        ChargeState::Charging => (level << 4) | 0x10 | 0x01,
+        ChargeState::Full => (8 << 4) | 0x01,
+    }
+}
+
+fn switch_battery(status: u8) -> Battery {
+    let level = (status >> 5) * 2;
+    let charge = match (status & 0x10 != 0, status & 0x01 != 0) {
+        (true, _) => ChargeState::Charging,
+        (false, true) => ChargeState::Full,
+        (false, false) => ChargeState::Discharging,
+    };
+    Battery { level: (level * 25 / 2).min(100), charge: charge as u8 }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::PadState;
+    use crate::{InputRanges, PadState, StickRange, TriggerRange};
+
+    fn cfg(features: Features) -> DeviceConfig {
+        DeviceConfig { features, ..Default::default() }
+    }
 
     #[test]
     fn x360_zeroed_report() {
-        let s = PadState { buttons: 0, lx: 0, ly: 0, rx: 0, ry: 0, lt: 0, rt: 0 };
+        let s = PadState {
+            buttons: Buttons::empty(),
+            lx: 0,
+            ly: 0,
+            rx: 0,
+            ry: 0,
+            lt: 0,
+            rt: 0,
+            ..Default::default()
+        };
         let r = X360Report::from(&s);
         assert_eq!(r.as_bytes().len(), 14);
         assert_eq!(r.as_bytes()[0], 0);
@@ -115,19 +1213,20 @@ mod tests {
     #[test]
     fn x360_buttons_and_axes() {
         let s = PadState {
-            buttons: 0b1010_0000_0000_0011, // sample bits
+            buttons: Buttons::from_xusb(0b1010_0000_0000_0011), // sample bits
             lx: 123,
             ly: -456,
             rx: 32767,
             ry: -32768,
             lt: 255,
             rt: 4096, // rt will clamp to 255 in the simple path
+            ..Default::default()
         };
         let r = X360Report::from(&s);
         let bytes = r.as_bytes();
 
         // buttons little-endian
-        let expected_btn = s.buttons.to_le_bytes();
+        let expected_btn = 0b1010_0000_0000_0011u16.to_le_bytes();
         assert_eq!(bytes[0], expected_btn[0]);
         assert_eq!(bytes[1], expected_btn[1]);
 
@@ -141,4 +1240,697 @@ mod tests {
         assert_eq!(&bytes[8..10], &s.rx.to_le_bytes());
         assert_eq!(&bytes[10..12], &s.ry.to_le_bytes());
     }
+
+    #[rustfmt::skip]
+    const DS4_NEUTRAL: [u8; 64] = [
+        0x01, 0x80, 0x80, 0x80, 0x80, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1B, 0x00,
+        0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn ds4_neutral_report() {
+        let r = DS4Report::from(&PadState::default());
+        assert_eq!(r.as_bytes(), &DS4_NEUTRAL);
+    }
+
+    #[test]
+    fn ds4_sticks_recentered() {
+        let s = PadState {
+            lx: i16::MIN,
+            ly: i16::MAX,
+            rx: i16::MAX,
+            ry: i16::MIN,
+            ..Default::default()
+        };
+        let r = DS4Report::from(&s);
+        // left stick full left/up, right stick full right/down
+        assert_eq!(&r.as_bytes()[1..5], &[0x00, 0x00, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn ds4_buttons_and_triggers() {
+        let s = PadState {
+            buttons: Buttons::A
+                | Buttons::Y
+                | Buttons::LEFT_SHOULDER
+                | Buttons::BACK
+                | Buttons::RIGHT_THUMB
+                | Buttons::GUIDE
+                | Buttons::TOUCHPAD,
+            lt: 0,
+            rt: 200,
+            ..Default::default()
+        };
+        let r = DS4Report::from(&s);
+        let bytes = r.as_bytes();
+        assert_eq!(bytes[5], 0x08 | 0x20 | 0x80);
+        assert_eq!(bytes[6], 0x01 | 0x08 | 0x10 | 0x80);
+        assert_eq!(bytes[7], 0x01 | 0x02);
+        assert_eq!(bytes[8], 0);
+        assert_eq!(bytes[9], 200);
+    }
+
+    #[test]
+    fn ds4_dpad_hat() {
+        let cases = [
+            (Buttons::empty(), 8),
+            (Buttons::DPAD_UP, 0),
+            (Buttons::DPAD_UP | Buttons::DPAD_RIGHT, 1),
+            (Buttons::DPAD_RIGHT, 2),
+            (Buttons::DPAD_DOWN | Buttons::DPAD_RIGHT, 3),
+            (Buttons::DPAD_DOWN, 4),
+            (Buttons::DPAD_DOWN | Buttons::DPAD_LEFT, 5),
+            (Buttons::DPAD_LEFT, 6),
+            (Buttons::DPAD_UP | Buttons::DPAD_LEFT, 7),
+            (Buttons::DPAD_UP | Buttons::DPAD_DOWN, 8),
+        ];
+        for (buttons, hat) in cases {
+            let r = DS4Report::from(&PadState { buttons, ..Default::default() });
+            assert_eq!(r.as_bytes()[5], hat, "buttons {buttons:?}");
+        }
+    }
+
+    #[test]
+    fn ds4_frame_counter_and_timestamp() {
+        let mut clock = ReportClock::default();
+        for _ in 0..65 {
+            clock.tick(Duration::from_micros(1000));
+        }
+        let r = DS4Report::pack(&PadState::default(), &clock, &DeviceConfig::default());
+        // counter wraps at 6 bits: 65 & 0x3F == 1
+        assert_eq!(r.as_bytes()[7], 1 << 2);
+        // 65ms in 5.33us units
+        assert_eq!(&r.as_bytes()[10..12], &12187u16.to_le_bytes());
+    }
+
+    // The expected DS5 reports below are worked out by hand from the byte layout
+    // documented on `DS5Report::pack`, not captured from a controller.
+    #[rustfmt::skip]
+    const DS5_NEUTRAL: [u8; 64] = [
+        0x01, 0x80, 0x80, 0x80, 0x80, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    // Cross + R1 held, left stick pushed up-right, R2 half pulled, touch and
+    // motion enabled, 10th frame at 10ms: sticks C0/40, R2 0x80, sequence 0x0A,
+    // hat neutral | cross 0x28, R1 | R2 digital 0x0A, timestamp 30000 (0x7530),
+    // both touch points up (0x80), battery status 0x2A.
+    #[rustfmt::skip]
+    const DS5_CROSS_R1: [u8; 64] = [
+        0x01, 0xC0, 0x40, 0x80, 0x80, 0x00, 0x80, 0x0A, 0x28, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x75, 0x00, 0x00,
+        0x00, 0x80, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn ds5_neutral_report() {
+        let r = DS5Report::from(&PadState::default());
+        assert_eq!(r.as_bytes(), &DS5_NEUTRAL);
+    }
+
+    #[test]
+    fn ds5_cross_r1_report() {
+        let s = PadState {
+            buttons: Buttons::A | Buttons::RIGHT_SHOULDER,
+            lx: 0x4000,
+            ly: 0x4000,
+            rt: 128,
+            ..Default::default()
+        };
+        let mut clock = ReportClock::default();
+        for _ in 0..10 {
+            clock.tick(Duration::from_millis(1));
+        }
+        let r = DS5Report::pack(&s, &clock, &cfg(Features::TOUCH | Features::GYRO));
+        assert_eq!(r.as_bytes(), &DS5_CROSS_R1);
+    }
+
+    #[test]
+    fn ds5_buttons() {
+        let s = PadState {
+            buttons: Buttons::DPAD_LEFT
+                | Buttons::B
+                | Buttons::X
+                | Buttons::BACK
+                | Buttons::START
+                | Buttons::LEFT_THUMB
+                | Buttons::GUIDE
+                | Buttons::TOUCHPAD
+                | Buttons::MUTE,
+            lt: 1,
+            ..Default::default()
+        };
+        let r = DS5Report::from(&s);
+        let bytes = r.as_bytes();
+        assert_eq!(bytes[8], 0x06 | 0x10 | 0x40);
+        assert_eq!(bytes[9], 0x04 | 0x10 | 0x20 | 0x40);
+        assert_eq!(bytes[10], 0x01 | 0x02 | 0x04);
+    }
+
+    #[test]
+    fn ds5_optional_sections_follow_features() {
+        let mut clock = ReportClock::default();
+        clock.tick(Duration::from_millis(5));
+        let s = PadState::default();
+
+        let bare = DS5Report::pack(&s, &clock, &DeviceConfig::default());
+        assert!(bare.as_bytes()[16..53].iter().all(|&b| b == 0));
+        assert_eq!(bare.as_bytes()[7], 1);
+
+        let full = DS5Report::pack(&s, &clock, &cfg(Features::TOUCH | Features::GYRO));
+        assert_eq!(&full.as_bytes()[28..32], &15000u32.to_le_bytes());
+        assert_eq!(full.as_bytes()[33], 0x80);
+        assert_eq!(full.as_bytes()[37], 0x80);
+    }
+
+    #[test]
+    fn ds4_touch_motion_and_battery() {
+        let s = PadState {
+            touch: [
+                TouchPoint { down: 1, id: 5, x: u16::MAX, y: 0 },
+                TouchPoint { down: 0, id: 6, x: 0, y: u16::MAX },
+            ],
+            motion: Motion { gyro: [1, -1, 256], accel: [0, 8192, -8192] },
+            battery: Battery { level: 42, charge: ChargeState::Charging as u8 },
+            ..Default::default()
+        };
+        let r =
+            DS4Report::pack(&s, &ReportClock::default(), &cfg(Features::TOUCH | Features::GYRO));
+        let bytes = r.as_bytes();
+        assert_eq!(&bytes[13..19], &[0x01, 0x00, 0xFF, 0xFF, 0x00, 0x01]);
+        assert_eq!(&bytes[19..25], &[0x00, 0x00, 0x00, 0x20, 0x00, 0xE0]);
+        assert_eq!(bytes[30], 0x14);
+        // x = 1919 (0x77F), y = 0; then x = 0, y = 941 (0x3AD), lifted
+        assert_eq!(&bytes[35..39], &[0x05, 0x7F, 0x07, 0x00]);
+        assert_eq!(&bytes[39..43], &[0x86, 0x00, 0xD0, 0x3A]);
+
+        // same state on a device without touch/gyro
+        let r = DS4Report::pack(&s, &ReportClock::default(), &DeviceConfig::default());
+        assert!(r.as_bytes()[13..25].iter().all(|&b| b == 0));
+        assert_eq!(r.as_bytes()[35], 0x80);
+        assert_eq!(r.as_bytes()[39], 0x80);
+    }
+
+    #[test]
+    fn ds5_touch_motion_and_battery() {
+        let s = PadState {
+            touch: [
+                TouchPoint { down: 1, id: 0x7F, x: u16::MAX, y: u16::MAX },
+                TouchPoint::default(),
+            ],
+            motion: Motion { gyro: [-2, 0, 0], accel: [0, 0, 1] },
+            battery: Battery { level: 100, charge: ChargeState::Discharging as u8 },
+            ..Default::default()
+        };
+        let r =
+            DS5Report::pack(&s, &ReportClock::default(), &cfg(Features::TOUCH | Features::GYRO));
+        let bytes = r.as_bytes();
+        assert_eq!(&bytes[16..18], &[0xFE, 0xFF]);
+        assert_eq!(&bytes[26..28], &[0x01, 0x00]);
+        // x = 1919 (0x77F), y = 1079 (0x437)
+        assert_eq!(&bytes[33..37], &[0x7F, 0x7F, 0x77, 0x43]);
+        assert_eq!(&bytes[37..41], &[0x80, 0x00, 0x00, 0x00]);
+        assert_eq!(bytes[53], 0x0A);
+    }
+
+    #[test]
+    fn keyboard_boot_and_rollover() {
+        let mut s = KeyboardState::with_keys(&[0x2C, 0x04, 0xE0, 0xE5]);
+        let r = KeyboardReport::pack(&s);
+        assert_eq!(r.as_bytes(), &[0x21, 0, 0x04, 0x2C, 0, 0, 0, 0]);
+        assert_eq!(KeyboardReport::try_from(&r.as_bytes()[..]).unwrap().state(), s);
+
+        (0x05..0x0A).for_each(|u| s.press(u));
+        let r = KeyboardReport::pack(&s);
+        assert!(r.is_rollover());
+        assert_eq!(r.as_bytes(), &[0x21, 0, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(r.state(), KeyboardState { modifiers: s.modifiers, ..Default::default() });
+    }
+
+    #[test]
+    fn keyboard_nkro() {
+        let s = KeyboardState::with_keys(&[0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0xDF, 0xE7]);
+        let r = KeyboardNkroReport::pack(&s);
+        let b = r.as_bytes();
+        assert_eq!(b.len(), 30);
+        assert_eq!(b[..4], [0x80, 0, 0xF0, 0x07]);
+        assert_eq!(b[29], 0x80);
+        assert_eq!(KeyboardNkroReport::try_from(&b[..]).unwrap().state(), s);
+
+        let mut raw = *b;
+        raw[2] |= 0x03;
+        assert_eq!(KeyboardNkroReport(raw).state(), s);
+    }
+
+    #[test]
+    fn mouse_splits_large_motion() {
+        let mut s = MouseState {
+            buttons: MouseButtons::LEFT | MouseButtons::FORWARD,
+            dx: 300,
+            dy: -5,
+            wheel: -1,
+            pan: 128,
+            ..Default::default()
+        };
+        let r = MouseReport::take(&mut s);
+        assert_eq!(r.as_bytes(), &[0x11, 127, 0xFB, 0xFF, 127]);
+        assert_eq!((s.dx, s.dy, s.wheel, s.pan), (173, 0, 0, 1));
+        assert_eq!(MouseReport::take(&mut s).as_bytes(), &[0x11, 127, 0, 0, 1]);
+        let r = MouseReport::take(&mut s);
+        assert!(s.is_still());
+        assert_eq!(
+            MouseReport::try_from(&r.as_bytes()[..]).unwrap().state(),
+            MouseState { buttons: s.buttons, dx: 46, ..Default::default() }
+        );
+        assert_eq!(MouseReport::take(&mut s).as_bytes(), &[0x11, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn v1_state_upgrades_with_header() {
+        let v1 = crate::PadStateV1 { buttons: 0x1000, lx: -1, rt: 7, ..Default::default() };
+        let s = PadState::from(v1);
+        assert_eq!(s.header, PadState::HEADER);
+        assert_eq!(s.header.size, 48);
+        assert_eq!((s.buttons, s.lx, s.rt), (Buttons::A, -1, 7));
+        assert_eq!(s.touch, [TouchPoint::default(); 2]);
+    }
+
+    /// xorshift32, enough to sweep the packers without a property-testing dependency.
+    fn rng(seed: &mut u32) -> u32 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 17;
+        *seed ^= *seed << 5;
+        *seed
+    }
+
+    /// A random state already at the precision the DS4/DS5 reports carry.
+    fn random_state(seed: &mut u32, touch: (u16, u16)) -> PadState {
+        let stick = |seed: &mut u32| u8_to_stick(rng(seed) as u8);
+        let stick_inv = |seed: &mut u32| u8_to_stick_inverted(rng(seed) as u8);
+        let point = |seed: &mut u32| {
+            let p = TouchPoint {
+                down: (rng(seed) & 1) as u8,
+                id: (rng(seed) & 0x7F) as u8,
+                x: rng(seed) as u16,
+                y: rng(seed) as u16,
+            };
+            unpack_touch(&pack_touch(&p, touch.0, touch.1), touch.0, touch.1)
+        };
+        let dpad = Buttons::from_hat((rng(seed) % 9) as u8);
+        PadState {
+            buttons: (Buttons::from_bits_truncate(rng(seed)) - Buttons::DPAD) | dpad,
+            lx: stick(seed),
+            ly: stick_inv(seed),
+            rx: stick(seed),
+            ry: stick_inv(seed),
+            lt: rng(seed) as u8 as u16,
+            rt: rng(seed) as u8 as u16,
+            touch: [point(seed), point(seed)],
+            motion: Motion {
+                gyro: [rng(seed) as i16, rng(seed) as i16, rng(seed) as i16],
+                accel: [rng(seed) as i16, rng(seed) as i16, rng(seed) as i16],
+            },
+            battery: Battery { level: (rng(seed) % 11) as u8 * 10, charge: (rng(seed) % 3) as u8 },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn x360_round_trip() {
+        let s = PadState {
+            buttons: Buttons::A | Buttons::DPAD_DOWN | Buttons::GUIDE,
+            lx: -1,
+            ly: i16::MIN,
+            rx: i16::MAX,
+            ry: 1234,
+            lt: 17,
+            rt: 255,
+            ..Default::default()
+        };
+        let r = X360Report::try_from(&X360Report::from(&s).as_bytes()[..]).unwrap();
+        assert_eq!(r.state(), s);
+    }
+
+    #[test]
+    fn x360_subtypes() {
+        use crate::XusbSubtype;
+        use crate::xusb::{Drums, Frets, Guitar};
+        let pack = |s: &PadState, subtype| {
+            X360Report::pack(s, &DeviceConfig { subtype, ..Default::default() }).state()
+        };
+
+        let g =
+            Guitar { frets: Frets::RED, whammy: 40000, tilt: -5, pickup: 2, ..Default::default() };
+        assert_eq!(Guitar::from_state(&pack(&g.to_state(), XusbSubtype::Guitar)), g);
+        let d = Drums { yellow: 100, blue_cymbal: 30, kick: true, ..Default::default() };
+        assert_eq!(Drums::from_state(&pack(&d.to_state(), XusbSubtype::DrumKit)), d);
+
+        let s = PadState { buttons: Buttons::A, lx: 500, lt: 200, rt: 100, ..Default::default() };
+        let arcade = pack(&s, XusbSubtype::ArcadeStick);
+        assert_eq!((arcade.buttons, arcade.lx, arcade.lt, arcade.rt), (Buttons::A, 0, 255, 0));
+        // opposite arrows are held together on a dance pad
+        let arrows = PadState { buttons: Buttons::DPAD_UP | Buttons::DPAD_DOWN, ..s };
+        let dance = pack(&arrows, XusbSubtype::DancePad);
+        assert_eq!(dance, PadState { buttons: arrows.buttons, ..Default::default() });
+    }
+
+    #[test]
+    fn ds4_round_trip() {
+        let all = cfg(Features::TOUCH | Features::GYRO);
+        let mut seed = 0x1234_5678;
+        for _ in 0..2000 {
+            let mut s = random_state(&mut seed, (DS4_TOUCH_W, DS4_TOUCH_H));
+            s.buttons -= Buttons::MUTE | Buttons::PADDLE1 | Buttons::PADDLE2;
+            s.buttons -= Buttons::PADDLE3 | Buttons::PADDLE4 | Buttons::SHARE;
+            s.buttons -= Buttons::FN_LEFT | Buttons::FN_RIGHT;
+            if s.battery.charge_state() == ChargeState::Full {
+                s.battery.level = 100;
+            }
+            let r = DS4Report::pack(&s, &ReportClock::default(), &all);
+            let back = DS4Report::try_from(&r.as_bytes()[..]).unwrap().state();
+            assert_eq!(back, s);
+            assert_eq!(DS4Report::pack(&back, &ReportClock::default(), &all), r);
+        }
+    }
+
+    #[test]
+    fn ds5_round_trip() {
+        let all = cfg(Features::TOUCH | Features::GYRO);
+        let clock = ReportClock::default();
+        let mut seed = 0x9E37_79B9;
+        for _ in 0..2000 {
+            let mut s = random_state(&mut seed, (DS5_TOUCH_W, DS5_TOUCH_H));
+            s.buttons -= Buttons::PADDLE2 | Buttons::PADDLE4 | Buttons::SHARE;
+            let edge = DS5Report::pack_edge(&s, &clock, &all);
+            let back = DS5Report::try_from(&edge.as_bytes()[..]).unwrap().state();
+            assert_eq!(back, s);
+            assert_eq!(DS5Report::pack_edge(&back, &clock, &all), edge);
+
+            // a plain DualSense has no Fn or back buttons
+            s.buttons -= Buttons::PADDLE1 | Buttons::PADDLE3 | Buttons::FN_LEFT | Buttons::FN_RIGHT;
+            let r = DS5Report::pack(&back, &clock, &all);
+            assert_eq!(r.state(), s);
+            assert_eq!(DS5Report::pack_edge(&s, &clock, &all), r);
+        }
+    }
+
+    #[test]
+    fn xbox_one_round_trip() {
+        let wide = DeviceConfig {
+            ranges: InputRanges { trigger: TriggerRange::Bits10, ..Default::default() },
+            ..Default::default()
+        };
+        let unsupported = Buttons::TOUCHPAD | Buttons::MUTE | Buttons::FN_LEFT | Buttons::FN_RIGHT;
+        let paddles = Buttons::PADDLE1 | Buttons::PADDLE2 | Buttons::PADDLE3 | Buttons::PADDLE4;
+        let mut seed = 0x0B13_0B13;
+        for _ in 0..2000 {
+            let r = random_state(&mut seed, (DS4_TOUCH_W, DS4_TOUCH_H));
+            let s = PadState {
+                buttons: r.buttons - unsupported,
+                lx: rng(&mut seed) as i16,
+                // i16::MIN has no positive twin to flip to
+                ly: (rng(&mut seed) as i16).max(-i16::MAX),
+                rx: r.rx,
+                ry: r.ry,
+                lt: (rng(&mut seed) & 0x3FF) as u16,
+                rt: r.rt,
+                ..Default::default()
+            };
+            let elite = XboxOneReport::pack_elite(&s, &wide);
+            assert_eq!(XboxOneReport::try_from(&elite.as_bytes()[..]).unwrap().state(), s);
+            let rpt = XboxOneReport::pack(&s, &wide);
+            assert_eq!(rpt.state(), PadState { buttons: s.buttons - paddles, ..s });
+        }
+    }
+
+    #[test]
+    fn xbox_one_neutral_and_extremes() {
+        let r = XboxOneReport::from(&PadState::default());
+        assert_eq!(r.as_bytes(), &[1, 0, 0x80, 0, 0x80, 0, 0x80, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let s =
+            PadState { ly: i16::MAX, lt: 255, buttons: Buttons::DPAD_LEFT, ..Default::default() };
+        let b = *XboxOneReport::from(&s).as_bytes();
+        // up is the low end of HID Y
+        assert_eq!(b[3..5], [0x01, 0x00]);
+        assert_eq!(b[9..11], 1023u16.to_le_bytes());
+        assert_eq!(b[13], 7);
+        assert_eq!(XboxOneReport::try_from(&b[..16]), Err(Error::Length { expected: 17, got: 16 }));
+    }
+
+    #[test]
+    fn switch_pro_round_trip() {
+        let all = cfg(Features::GYRO);
+        let unsupported = Buttons::TOUCHPAD | Buttons::MUTE | Buttons::PADDLE1 | Buttons::PADDLE2;
+        let unsupported = unsupported | Buttons::FN_LEFT | Buttons::FN_RIGHT;
+        let mut seed = 0x2009_0057;
+        for _ in 0..2000 {
+            let r = random_state(&mut seed, (DS4_TOUCH_W, DS4_TOUCH_H));
+            let stick = |seed: &mut u32| ((rng(seed) as i16) >> 4) << 4;
+            let s = PadState {
+                buttons: (r.buttons - unsupported - Buttons::PADDLE3 - Buttons::PADDLE4)
+                    | (Buttons::from_bits_truncate(rng(&mut seed)) & Buttons::DPAD),
+                lx: stick(&mut seed),
+                ly: stick(&mut seed),
+                rx: stick(&mut seed),
+                ry: stick(&mut seed),
+                lt: if r.lt > 0 { 255 } else { 0 },
+                rt: if r.rt > 0 { 255 } else { 0 },
+                motion: r.motion,
+                battery: match r.battery.charge_state() {
+                    ChargeState::Full => Battery { level: 100, ..r.battery },
+                    _ => Battery { level: (rng(&mut seed) % 5) as u8 * 25, ..r.battery },
+                },
+                ..Default::default()
+            };
+            let rpt = SwitchProReport::pack(&s, &ReportClock::default(), &all);
+            assert_eq!(SwitchProReport::try_from(&rpt.as_bytes()[..]).unwrap().state(), s);
+        }
+    }
+
+    #[test]
+    fn switch_pro_layout() {
+        let s = PadState {
+            buttons: Buttons::B | Buttons::DPAD_LEFT | Buttons::SHARE,
+            lx: i16::MIN,
+            ly: i16::MAX,
+            rt: 1,
+            motion: Motion { gyro: [1, 2, 3], accel: [4, 5, 6] },
+            ..Default::default()
+        };
+        let b =
+            SwitchProReport::pack(&s, &ReportClock { counter: 9, micros: 0 }, &cfg(Features::GYRO))
+                .0;
+        assert_eq!(b[..6], [0x30, 9, 0x81, 0x88, 0x20, 0x08]);
+        assert_eq!(b[6..12], [0x00, 0xF0, 0xFF, 0x00, 0x08, 0x80]);
+        // accel first, repeated for all three samples
+        assert_eq!(b[13..15], [4, 0]);
+        assert_eq!(b[19..21], [1, 0]);
+        assert_eq!(b[37..49], b[13..25]);
+        let b = SwitchProReport::pack(&s, &ReportClock::default(), &cfg(Features::empty())).0;
+        assert_eq!(b[13..49], [0; 36]);
+    }
+
+    #[test]
+    fn decoders_reject_malformed_reports() {
+        assert_eq!(
+            X360Report::try_from(&[0u8; 13][..]),
+            Err(Error::Length { expected: 14, got: 13 })
+        );
+        assert_eq!(
+            DS4Report::try_from(&DS4_NEUTRAL[..63]),
+            Err(Error::Length { expected: 64, got: 63 })
+        );
+        let mut bad = DS5_NEUTRAL;
+        bad[0] = 0x31;
+        assert_eq!(DS5Report::try_from(&bad[..]), Err(Error::ReportId(0x31)));
+        assert!(DS4Report::try_from(&DS4_NEUTRAL[..]).is_ok());
+    }
+
+    #[test]
+    fn decoders_expose_counters() {
+        let mut clock = ReportClock::default();
+        for _ in 0..70 {
+            clock.tick(Duration::from_millis(1));
+        }
+        let s = PadState::default();
+        assert_eq!(DS4Report::pack(&s, &clock, &DeviceConfig::default()).counter(), 70 & 0x3F);
+        assert_eq!(DS5Report::pack(&s, &clock, &DeviceConfig::default()).sequence(), 70);
+    }
+
+    #[test]
+    fn bluetooth_framing_wraps_usb_body() {
+        let usb = DS4Report(DS4_NEUTRAL);
+        let bt = DS4BtReport::wrap(&usb);
+        assert_eq!(bt.as_bytes()[..3], [0x11, 0xC0, 0x00]);
+        assert_eq!(bt.as_bytes()[3..66], DS4_NEUTRAL[1..]);
+        assert_eq!(DS4BtReport::try_from(&bt.as_bytes()[..]), Ok(bt));
+        assert_eq!(bt.usb(), usb);
+
+        let usb = DS5Report(DS5_CROSS_R1);
+        let bt = DS5BtReport::wrap(&usb, 0x17);
+        assert_eq!(bt.as_bytes()[..2], [0x31, 0x70]);
+        assert_eq!(bt.as_bytes()[2..65], DS5_CROSS_R1[1..]);
+        assert_eq!(bt.tag(), 7);
+        assert_eq!(DS5BtReport::try_from(&bt.as_bytes()[..]), Ok(bt));
+        assert_eq!(bt.state(), usb.state());
+    }
+
+    #[test]
+    fn bluetooth_decoders_check_crc() {
+        let s = PadState { lx: 1000, buttons: Buttons::A, ..Default::default() };
+        let mut b =
+            *DS4BtReport::pack(&s, &ReportClock::default(), &DeviceConfig::default()).as_bytes();
+        b[5] ^= 0x01;
+        assert!(matches!(DS4BtReport::try_from(&b[..]), Err(Error::Crc { .. })));
+        let b = DS5BtReport::pack(&s, &ReportClock::default(), &DeviceConfig::default());
+        assert_eq!(
+            DS5BtReport::try_from(&b.as_bytes()[..77]),
+            Err(Error::Length { expected: 78, got: 77 })
+        );
+        assert_eq!(DS4BtReport::try_from(&b.as_bytes()[..]), Err(Error::ReportId(0x31)));
+    }
+
+    #[test]
+    fn declared_ranges_scale_proportionally() {
+        let s = PadState { lx: 0x3FFF, ly: -0x4000, lt: 1023, rt: 512, ..Default::default() };
+        let cfg = DeviceConfig {
+            ranges: InputRanges { trigger: TriggerRange::Bits10, stick: StickRange::Half },
+            ..Default::default()
+        };
+
+        let x = X360Report::pack(&s, &cfg);
+        assert_eq!(&x.as_bytes()[2..4], &[255, 128]);
+        assert_eq!(&x.as_bytes()[4..6], &i16::MAX.to_le_bytes());
+        assert_eq!(&x.as_bytes()[6..8], &i16::MIN.to_le_bytes());
+
+        let ds4 = DS4Report::pack(&s, &ReportClock::default(), &cfg);
+        assert_eq!(&ds4.as_bytes()[1..3], &[0xFF, 0xFF]);
+        assert_eq!(&ds4.as_bytes()[8..10], &[255, 128]);
+
+        let ds5 = DS5Report::pack(&s, &ReportClock::default(), &cfg);
+        assert_eq!(&ds5.as_bytes()[5..7], &[255, 128]);
+    }
+
+    #[test]
+    fn wide_triggers_no_longer_saturate() {
+        let s = PadState { lt: 0x4000, rt: 0xFFFF, ..Default::default() };
+        let cfg = DeviceConfig {
+            ranges: InputRanges { trigger: TriggerRange::Bits16, ..Default::default() },
+            ..Default::default()
+        };
+        assert_eq!(&X360Report::pack(&s, &cfg).as_bytes()[2..4], &[0x40, 0xFF]);
+        // the 8-bit default still clamps
+        assert_eq!(&X360Report::from(&s).as_bytes()[2..4], &[0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn generic_round_trip() {
+        let full = GenericSpec { buttons: 18, ..Default::default() };
+        let cfg = DeviceConfig {
+            ranges: InputRanges { trigger: TriggerRange::Bits16, ..Default::default() },
+            generic: full,
+            ..Default::default()
+        };
+        let mut seed = 0x4844_4844;
+        for _ in 0..2000 {
+            let r = random_state(&mut seed, (DS4_TOUCH_W, DS4_TOUCH_H));
+            let s = PadState {
+                // the 18 buttons a pad with a hat has room for, plus the hat
+                buttons: (Buttons::from_bits_truncate(rng(&mut seed))
+                    - Buttons::DPAD
+                    - Buttons::FN_LEFT
+                    - Buttons::FN_RIGHT)
+                    | Buttons::from_hat((rng(&mut seed) % 9) as u8),
+                lx: r.lx,
+                ly: r.ly.max(-i16::MAX),
+                rx: r.rx,
+                ry: r.ry.max(-i16::MAX),
+                lt: rng(&mut seed) as u16,
+                rt: rng(&mut seed) as u16,
+                ..Default::default()
+            };
+            let rpt = GenericReport::pack(&s, &cfg);
+            assert_eq!(rpt.as_bytes().len(), full.report_len());
+            assert_eq!(GenericReport::parse(rpt.as_bytes(), &full).unwrap().state(&full), Ok(s));
+        }
+    }
+
+    #[test]
+    fn generic_layouts() {
+        // two 8-bit axes, no hat, the d-pad as buttons 19..22
+        let spec =
+            GenericSpec { axes: 2, axis_bits: 8, buttons: 22, hats: 0, ..Default::default() };
+        let cfg = DeviceConfig { generic: spec, ..Default::default() };
+        let s = PadState {
+            buttons: Buttons::A | Buttons::DPAD_RIGHT,
+            lx: i16::MIN,
+            ly: i16::MAX,
+            rx: 1234,
+            ..Default::default()
+        };
+        let r = GenericReport::pack(&s, &cfg);
+        assert_eq!(r.as_bytes(), &[0x00, 0x00, 0x01, 0x00, 0x20]);
+        let back = r.state(&spec).unwrap();
+        assert_eq!((back.buttons, back.lx, back.rx), (s.buttons, i16::MIN, 0));
+
+        // 10-bit axes put the hat mid-byte
+        let spec =
+            GenericSpec { axes: 1, axis_bits: 10, buttons: 2, hats: 1, ..Default::default() };
+        let cfg = DeviceConfig { generic: spec, ..Default::default() };
+        let r = GenericReport::pack(&PadState { buttons: Buttons::B, ..Default::default() }, &cfg);
+        assert_eq!(r.as_bytes(), &[0x00, 0xA2]);
+
+        assert_eq!(
+            GenericReport::parse(&[0; 3], &spec),
+            Err(Error::Length { expected: 2, got: 3 })
+        );
+        let wide = GenericSpec { axes: 7, ..spec };
+        assert_eq!(
+            GenericReport::parse(&[0; 2], &wide),
+            Err(Error::Value { field: "generic axes", value: 7 })
+        );
+        let fine = GenericReport::parse(&[0; 2], &spec).unwrap();
+        assert!(fine.state(&GenericSpec { axis_bits: 17, ..spec }).is_err());
+        assert!(fine.state(&GenericSpec { buttons: 10, ..spec }).is_err());
+    }
+
+    #[test]
+    fn wheel_round_trip() {
+        let wide = DeviceConfig {
+            ranges: InputRanges { trigger: TriggerRange::Bits16, ..Default::default() },
+            ..Default::default()
+        };
+        let mut seed = 0xC24F_C24F;
+        for _ in 0..2000 {
+            let r = random_state(&mut seed, (DS4_TOUCH_W, DS4_TOUCH_H));
+            let s = PadState {
+                buttons: r.buttons & (Buttons::DPAD | Buttons::from_wheel([0xF0, 0x7F])),
+                lx: rng(&mut seed) as i16,
+                ry: (rng(&mut seed) as i16).max(0),
+                lt: rng(&mut seed) as u16,
+                rt: rng(&mut seed) as u16,
+                ..Default::default()
+            };
+            let rpt = WheelReport::pack(&s, &wide);
+            assert_eq!(WheelReport::try_from(&rpt.as_bytes()[..]).unwrap().state(), s);
+        }
+    }
+
+    #[test]
+    fn wheel_neutral_and_pedals() {
+        let r = WheelReport::from(&PadState::default());
+        assert_eq!(r.as_bytes(), &[1, 0, 0x80, 0, 0, 0, 0, 0, 0, 8, 0]);
+        let s = PadState { ry: i16::MAX, rt: 255, ..Default::default() };
+        let b = *WheelReport::from(&s).as_bytes();
+        assert_eq!(b[3..5], [0xFF, 0xFF]);
+        assert_eq!(b[7..9], [0xFF, 0xFF]);
+        // pulling the clutch stick down doesn't press it
+        let s = PadState { ry: i16::MIN, ..Default::default() };
+        assert_eq!(WheelReport::from(&s).as_bytes()[7..9], [0, 0]);
+    }
 }
@@ -0,0 +1,376 @@
+//! Hand-written serde for types whose wire form differs from their Rust shape:
+//! flag sets as lists of names, device kinds by name or numeric ID, fixed
+//! UTF-16 buffers as strings, keyboard bitmaps as lists of keys, MACs as text.
+
+use crate::keyboard::{self, KeyboardLeds};
+use crate::{Buttons, DeviceKind, Features, KeyboardState, Modifiers, MouseButtons};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use bitflags::Flags;
+use core::fmt;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for DeviceKind {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceKind {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct KindVisitor;
+
+        impl Visitor<'_> for KindVisitor {
+            type Value = DeviceKind;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a device kind name or numeric ID")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<DeviceKind, E> {
+                DeviceKind::from_name(v)
+                    .ok_or_else(|| E::custom(format!("unknown device kind `{v}`")))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<DeviceKind, E> {
+                u16::try_from(v)
+                    .ok()
+                    .and_then(DeviceKind::from_id)
+                    .ok_or_else(|| E::custom(format!("unknown device kind ID {v:#06x}")))
+            }
+        }
+
+        d.deserialize_any(KindVisitor)
+    }
+}
+
+/// Set flags as lowercase names. Bits without a name are not representable and dropped.
+fn serialize_names<F: Flags, S: Serializer>(flags: &F, s: S) -> Result<S::Ok, S::Error> {
+    let mut seq = s.serialize_seq(None)?;
+    for (name, _) in flags.iter_names() {
+        seq.serialize_element(&name.to_ascii_lowercase())?;
+    }
+    seq.end()
+}
+
+/// Accepts a list of names (any case) or a bare number, which `from_bits` turns into flags.
+fn deserialize_names<'de, F, D>(
+    d: D,
+    what: &'static str,
+    from_bits: fn(u32) -> F,
+) -> Result<F, D::Error>
+where
+    F: Flags,
+    D: Deserializer<'de>,
+{
+    struct NamesVisitor<F> {
+        what: &'static str,
+        from_bits: fn(u32) -> F,
+    }
+
+    impl<'de, F: Flags> Visitor<'de> for NamesVisitor<F> {
+        type Value = F;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a list of {} names or a number", self.what)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<F, E> {
+            let bits = u32::try_from(v)
+                .map_err(|_| E::custom(format!("{} bits out of range", self.what)))?;
+            Ok((self.from_bits)(bits))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<F, A::Error> {
+            let mut out = F::empty();
+            // owned, so names from `from_value`, readers and escaped strings work too
+            while let Some(name) = seq.next_element::<String>()? {
+                let flag = F::FLAGS
+                    .iter()
+                    .find(|f| f.name().eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| de::Error::custom(format!("unknown {} `{name}`", self.what)))?;
+                out.insert(F::from_bits_retain(flag.value().bits()));
+            }
+            Ok(out)
+        }
+    }
+
+    d.deserialize_any(NamesVisitor { what, from_bits })
+}
+
+/// Unknown feature bits survive a numeric round trip so create validation can report them.
+impl Serialize for Features {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_names(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Features {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_names(d, "feature", Features::from_bits_retain)
+    }
+}
+
+impl Serialize for Buttons {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_names(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Buttons {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_names(d, "button", Buttons::from_bits_truncate)
+    }
+}
+
+/// Serialize and deserialize u8 flag sets by name, like `Buttons`.
+macro_rules! byte_flags {
+    ($($t:ty, $what:literal;)*) => {$(
+        impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                serialize_names(self, s)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                deserialize_names(d, $what, |b| <$t>::from_bits_truncate(b as u8))
+            }
+        }
+    )*};
+}
+
+byte_flags! {
+    Modifiers, "modifier";
+    MouseButtons, "mouse button";
+    KeyboardLeds, "keyboard LED";
+}
+
+/// One key: its name, or the raw usage for keys without one.
+struct Key(u8);
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match keyboard::key_name(self.0) {
+            Some(name) => s.serialize_str(name),
+            None => s.serialize_u8(self.0),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key name or usage")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Key, E> {
+                keyboard::key_from_name(v)
+                    .map(Key)
+                    .ok_or_else(|| E::custom(format!("unknown key `{v}`")))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Key, E> {
+                u8::try_from(v)
+                    .map(Key)
+                    .map_err(|_| E::custom(format!("key usage {v} out of range")))
+            }
+        }
+
+        d.deserialize_any(KeyVisitor)
+    }
+}
+
+/// `{"modifiers": [...], "keys": [...]}`; modifier keys may also be listed in `keys`.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct KeyboardRepr {
+    modifiers: Modifiers,
+    keys: Vec<Key>,
+}
+
+impl Default for KeyboardRepr {
+    fn default() -> Self {
+        Self { modifiers: Modifiers::empty(), keys: Vec::new() }
+    }
+}
+
+impl Serialize for KeyboardState {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        KeyboardRepr { modifiers: self.modifiers, keys: self.pressed().map(Key).collect() }
+            .serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyboardState {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let repr = KeyboardRepr::deserialize(d)?;
+        let mut s = KeyboardState { modifiers: repr.modifiers, ..Default::default() };
+        repr.keys.iter().for_each(|k| s.press(k.0));
+        Ok(s)
+    }
+}
+
+/// `[u16; N]` NUL-padded UTF-16 as a string; longer strings are cut to fit
+/// with the NUL kept.
+pub(crate) mod utf16 {
+    use alloc::string::String;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(v: &[u16; N], s: S) -> Result<S::Ok, S::Error> {
+        let end = v.iter().position(|c| *c == 0).unwrap_or(N);
+        s.serialize_str(&String::from_utf16_lossy(&v[..end]))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        d: D,
+    ) -> Result<[u16; N], D::Error> {
+        let text = String::deserialize(d)?;
+        let mut out = [0; N];
+        for (o, c) in out[..N.saturating_sub(1)].iter_mut().zip(text.encode_utf16()) {
+            *o = c;
+        }
+        Ok(out)
+    }
+}
+
+/// A MAC in display order as `aa:bb:cc:dd:ee:ff`; `-` separators and
+/// uppercase digits are accepted too.
+pub(crate) mod mac {
+    use alloc::format;
+    use alloc::string::String;
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(v: &[u8; 6], s: S) -> Result<S::Ok, S::Error> {
+        let [a, b, c, d, e, f] = v;
+        s.serialize_str(&format!("{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{f:02x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u8; 6], D::Error> {
+        let text = String::deserialize(d)?;
+        let bad = || de::Error::custom(format!("invalid MAC `{text}`"));
+        let mut out = [0; 6];
+        let mut parts = text.split([':', '-']);
+        for o in &mut out {
+            let part = parts.next().filter(|p| p.len() == 2).ok_or_else(bad)?;
+            *o = u8::from_str_radix(part, 16).map_err(|_| bad())?;
+        }
+        match parts.next() {
+            Some(_) => Err(bad()),
+            None => Ok(out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Buttons, DeviceIdentity, DeviceKind, Features, InputRanges, KeyboardState, Modifiers,
+        MouseButtons, MouseState, PadState, Transport,
+    };
+    use serde_json::{from_str, json, to_value};
+
+    #[test]
+    fn kinds_by_name_or_id() {
+        assert_eq!(to_value(DeviceKind::DS4).unwrap(), json!("ds4"));
+        assert_eq!(from_str::<DeviceKind>(r#""DS5""#).unwrap(), DeviceKind::DS5);
+        assert_eq!(from_str::<DeviceKind>("1476").unwrap(), DeviceKind::DS4);
+        assert_eq!(from_str::<DeviceKind>("870").unwrap(), DeviceKind::X360);
+        assert!(from_str::<DeviceKind>(r#""ds6""#).is_err());
+        assert!(from_str::<DeviceKind>("1").is_err());
+    }
+
+    #[test]
+    fn flags_as_names() {
+        let f = Features::RUMBLE | Features::GYRO;
+        assert_eq!(to_value(f).unwrap(), json!(["rumble", "gyro"]));
+        assert_eq!(from_str::<Features>(r#"["GYRO", "rumble"]"#).unwrap(), f);
+        assert_eq!(from_str::<Features>("5").unwrap(), f);
+        assert_eq!(from_str::<Features>("256").unwrap().bits(), 256);
+        assert!(from_str::<Features>(r#"["warp"]"#).is_err());
+
+        let b = Buttons::A | Buttons::DPAD;
+        assert_eq!(
+            to_value(b).unwrap(),
+            json!(["dpad_up", "dpad_down", "dpad_left", "dpad_right", "a"])
+        );
+        assert_eq!(from_str::<Buttons>(r#"["dpad", "a"]"#).unwrap(), b);
+        assert_eq!(from_str::<Buttons>("4096").unwrap(), Buttons::A);
+        assert_eq!(serde_json::from_value::<Buttons>(json!(["a", "DPAD"])).unwrap(), b);
+        assert_eq!(from_str::<Features>(r#"["\u0072umble"]"#).unwrap(), Features::RUMBLE);
+        let r = serde_json::from_reader::<_, MouseButtons>(&br#"["left"]"#[..]);
+        assert_eq!(r.unwrap(), MouseButtons::LEFT);
+    }
+
+    #[test]
+    fn pad_state_round_trip_and_defaults() {
+        let s = PadState { buttons: Buttons::B, lx: -5, rt: 200, ..Default::default() };
+        let back: PadState = from_str(&serde_json::to_string(&s).unwrap()).unwrap();
+        assert_eq!(back, s);
+
+        // v1-era JSON: numeric buttons, no touch/motion/battery, no header
+        let v1: PadState = from_str(r#"{"buttons": 8192, "lx": 100}"#).unwrap();
+        assert_eq!(v1, PadState { buttons: Buttons::B, lx: 100, ..Default::default() });
+        assert_eq!(v1.header, PadState::HEADER);
+    }
+
+    #[test]
+    fn keyboard_and_mouse_states() {
+        let k = KeyboardState::with_keys(&[0x04, 0x28, 0xE4, 0xF0]);
+        let v = to_value(k).unwrap();
+        assert_eq!(v, json!({"modifiers": ["right_ctrl"], "keys": ["a", "enter"]}));
+        assert_eq!(from_str::<KeyboardState>(&v.to_string()).unwrap().modifiers, k.modifiers);
+        let k: KeyboardState = from_str(r#"{"keys": ["A", "left_shift", 104]}"#).unwrap();
+        assert_eq!(k.modifiers, Modifiers::LEFT_SHIFT);
+        assert_eq!(k.pressed().collect::<Vec<_>>(), [0x04, 0x68]);
+        assert_eq!(to_value(k).unwrap()["keys"], json!(["a", 104]));
+        assert!(from_str::<KeyboardState>(r#"{"keys": ["hyper"]}"#).is_err());
+        assert!(from_str::<KeyboardState>(r#"{"keys": [256]}"#).is_err());
+
+        let m: MouseState = from_str(r#"{"buttons": ["left"], "dx": -3, "wheel": 1}"#).unwrap();
+        assert_eq!(
+            m,
+            MouseState { buttons: MouseButtons::LEFT, dx: -3, wheel: 1, ..Default::default() }
+        );
+        assert_eq!(from_str::<MouseState>(&serde_json::to_string(&m).unwrap()).unwrap(), m);
+    }
+
+    #[test]
+    fn identity_strings_and_mac() {
+        let id: DeviceIdentity =
+            from_str(r#"{"vendor_id": 1356, "serial": "abc", "mac": "AA-BB-CC-00-11-22"}"#)
+                .unwrap();
+        assert_eq!(id.mac, [0xAA, 0xBB, 0xCC, 0x00, 0x11, 0x22]);
+        assert_eq!(
+            id,
+            DeviceIdentity { vendor_id: 0x054C, mac: id.mac, ..Default::default() }
+                .with_serial("abc")
+        );
+        let v = to_value(id).unwrap();
+        assert_eq!(
+            (&v["mac"], &v["serial"], &v["product"]),
+            (&json!("aa:bb:cc:00:11:22"), &json!("abc"), &json!(""))
+        );
+        assert_eq!(from_str::<DeviceIdentity>(&v.to_string()).unwrap(), id);
+        for bad in ["aa:bb:cc:00:11", "aa:bb:cc:00:11:22:33", "aa:bb:cc:00:11:zz", "aabbcc001122"] {
+            assert!(from_str::<DeviceIdentity>(&json!({"mac": bad}).to_string()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn enums_are_snake_case() {
+        assert_eq!(to_value(Transport::Bluetooth).unwrap(), json!("bluetooth"));
+        assert_eq!(
+            to_value(InputRanges::default()).unwrap(),
+            json!({"trigger": "bits8", "stick": "full"})
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Switch Pro Controller host protocol. The pad only streams full 0x30 reports
+//! after the host walks it through 0x01 subcommands (and, over USB, the 0x80
+//! handshake); `Session` answers them as a pure state machine, no I/O.
+
+use crate::error::{Error, Result};
+use crate::report::{ReportClock, SwitchProReport};
+use crate::{DeviceConfig, Features, PadState};
+
+/// Rumble data followed by a subcommand.
+pub const OUTPUT_SUBCOMMAND_ID: u8 = 0x01;
+/// Rumble data only.
+pub const OUTPUT_RUMBLE_ID: u8 = 0x10;
+/// USB-only commands (handshake, baud rate, HID-only mode).
+pub const OUTPUT_USB_ID: u8 = 0x80;
+/// Input report carrying a subcommand reply.
+pub const REPLY_ID: u8 = 0x21;
+/// Input report answering a USB command.
+pub const USB_REPLY_ID: u8 = 0x81;
+
+/// Subcommand IDs, byte 10 of output report 0x01.
+pub mod subcommand {
+    pub const DEVICE_INFO: u8 = 0x02;
+    pub const SET_INPUT_MODE: u8 = 0x03;
+    pub const TRIGGER_ELAPSED: u8 = 0x04;
+    pub const SPI_READ: u8 = 0x10;
+    pub const SET_PLAYER_LIGHTS: u8 = 0x30;
+    pub const ENABLE_IMU: u8 = 0x40;
+    pub const ENABLE_VIBRATION: u8 = 0x48;
+}
+
+/// USB commands, byte 1 of output report 0x80.
+pub mod usb {
+    pub const STATUS: u8 = 0x01;
+    pub const HANDSHAKE: u8 = 0x02;
+    pub const HIGH_SPEED: u8 = 0x03;
+    pub const HID_ONLY: u8 = 0x04;
+    pub const TIMEOUT: u8 = 0x05;
+}
+
+/// `SET_INPUT_MODE` argument for standard full (0x30) reports.
+pub const MODE_FULL: u8 = 0x30;
+/// Input mode at power-on: simple HID reports, which HIDra does not emulate.
+pub const MODE_SIMPLE: u8 = 0x3F;
+
+/// Largest SPI read the pad answers in one go.
+pub const SPI_READ_MAX: usize = 0x1D;
+
+/// Controller type in device info and USB status: Pro Controller.
+const TYPE_PRO: u8 = 0x03;
+const FIRMWARE: [u8; 2] = [0x03, 0x48];
+
+/// Factory calibration in SPI flash, as read by SDL and hid-nintendo. Everything
+/// else reads as erased (0xFF), which also means "no user calibration".
+const SPI_FLASH: &[(u32, &[u8])] = &[
+    // device type, color info present
+    (0x6012, &[TYPE_PRO]),
+    (0x601B, &[0x01]),
+    // IMU: accel origin, accel sensitivity 0x4000, gyro origin, gyro sensitivity 0x343B
+    (
+        0x6020,
+        &[
+            0, 0, 0, 0, 0, 0, 0x00, 0x40, 0x00, 0x40, 0x00, 0x40, 0, 0, 0, 0, 0, 0, 0x3B, 0x34,
+            0x3B, 0x34, 0x3B, 0x34,
+        ],
+    ),
+    // sticks, full 12-bit travel around 0x800. Left: above, center, below.
+    // Right: center, below, above.
+    (
+        0x603D,
+        &[
+            0xFF, 0xF7, 0x7F, 0x00, 0x08, 0x80, 0x00, 0x08, 0x80, 0x00, 0x08, 0x80, 0x00, 0x08,
+            0x80, 0xFF, 0xF7, 0x7F,
+        ],
+    ),
+    // body, buttons, left grip, right grip
+    (0x6050, &[0x32, 0x32, 0x32, 0xFF, 0xFF, 0xFF, 0x32, 0x32, 0x32, 0x32, 0x32, 0x32]),
+];
+
+/// Up to 35 bytes of reply data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Payload {
+    len: u8,
+    bytes: [u8; 35],
+}
+
+impl Payload {
+    fn new(data: &[u8]) -> Self {
+        let mut bytes = [0u8; 35];
+        bytes[..data.len()].copy_from_slice(data);
+        Self { len: data.len() as u8, bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+/// What the pad sends back for one output report.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reply {
+    /// Input report 0x81: the command echoed, then `data`.
+    Usb { command: u8, data: Payload },
+    /// Input report 0x21: the current pad state, then `ack`, the subcommand echoed and `data`.
+    Subcommand { ack: u8, id: u8, data: Payload },
+}
+
+/// Host-visible protocol state of one emulated pad.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Session {
+    /// Bluetooth address in display order.
+    pub mac: [u8; 6],
+    /// Set by `SET_INPUT_MODE`; full reports stream once it is `MODE_FULL`.
+    pub mode: u8,
+    pub imu: bool,
+    pub vibration: bool,
+    /// Low nibble on, high nibble flashing.
+    pub player_lights: u8,
+    /// Set by `usb::HID_ONLY`: the host drives the pad over HID from now on.
+    pub hid_only: bool,
+}
+
+impl Session {
+    pub fn new(mac: [u8; 6]) -> Self {
+        Self {
+            mac,
+            mode: MODE_SIMPLE,
+            imu: false,
+            vibration: false,
+            player_lights: 0,
+            hid_only: false,
+        }
+    }
+
+    /// Whether 0x30 reports should be streamed.
+    pub fn streaming(&self) -> bool {
+        self.mode == MODE_FULL
+    }
+
+    /// Apply one output report. Rumble-only reports and a few USB commands have no reply.
+    pub fn handle(&mut self, data: &[u8]) -> Result<Option<Reply>> {
+        match data.first() {
+            Some(&OUTPUT_SUBCOMMAND_ID) => {
+                check_len(data, 11)?;
+                Ok(Some(self.subcommand(data[10], &data[11..])))
+            }
+            Some(&OUTPUT_RUMBLE_ID) => Ok(None),
+            Some(&OUTPUT_USB_ID) => {
+                check_len(data, 2)?;
+                Ok(self.usb(data[1]))
+            }
+            Some(&id) => Err(Error::ReportId(id)),
+            None => Err(Error::Length { expected: 1, got: 0 }),
+        }
+    }
+
+    fn usb(&mut self, command: u8) -> Option<Reply> {
+        let data = match command {
+            usb::STATUS => {
+                let mut d = [0u8; 8];
+                d[1] = TYPE_PRO;
+                d[2..].copy_from_slice(&self.mac);
+                d[2..].reverse();
+                Payload::new(&d)
+            }
+            usb::HANDSHAKE | usb::HIGH_SPEED => Payload::new(&[]),
+            usb::HID_ONLY => {
+                self.hid_only = true;
+                return None;
+            }
+            usb::TIMEOUT => {
+                self.hid_only = false;
+                return None;
+            }
+            _ => return None,
+        };
+        Some(Reply::Usb { command, data })
+    }
+
+    fn subcommand(&mut self, id: u8, args: &[u8]) -> Reply {
+        let arg = args.first().copied().unwrap_or(0);
+        let (ack, data) = match id {
+            subcommand::DEVICE_INFO => {
+                let mut d = [0u8; 12];
+                d[..4].copy_from_slice(&[FIRMWARE[0], FIRMWARE[1], TYPE_PRO, 0x02]);
+                d[4..10].copy_from_slice(&self.mac);
+                d[10..].copy_from_slice(&[0x01, 0x01]);
+                (0x82, Payload::new(&d))
+            }
+            subcommand::SET_INPUT_MODE => {
+                self.mode = arg;
+                (0x80, Payload::new(&[]))
+            }
+            subcommand::TRIGGER_ELAPSED => (0x83, Payload::new(&[0; 14])),
+            subcommand::SPI_READ => (0x90, spi_read(args)),
+            subcommand::SET_PLAYER_LIGHTS => {
+                self.player_lights = arg;
+                (0x80, Payload::new(&[]))
+            }
+            subcommand::ENABLE_IMU => {
+                self.imu = arg != 0;
+                (0x80, Payload::new(&[]))
+            }
+            subcommand::ENABLE_VIBRATION => {
+                self.vibration = arg != 0;
+                (0x80, Payload::new(&[]))
+            }
+            // shipment mode, home light, IMU sensitivity, ...: acknowledged and ignored
+            _ => (0x80, Payload::new(&[])),
+        };
+        Reply::Subcommand { ack, id, data }
+    }
+
+    /// The 0x30 report for `s`. The IMU section stays zeroed until the host enables it.
+    pub fn report(&self, s: &PadState, clock: &ReportClock, cfg: &DeviceConfig) -> SwitchProReport {
+        let mut cfg = *cfg;
+        if !self.imu {
+            cfg.features -= Features::GYRO;
+        }
+        SwitchProReport::pack(s, clock, &cfg)
+    }
+
+    /// The input report carrying `reply`; subcommand replies include `s` like real pads do.
+    pub fn reply_report(
+        &self,
+        reply: &Reply,
+        s: &PadState,
+        clock: &ReportClock,
+        cfg: &DeviceConfig,
+    ) -> [u8; 64] {
+        match reply {
+            Reply::Usb { command, data } => {
+                let mut b = [0u8; 64];
+                b[0] = USB_REPLY_ID;
+                b[1] = *command;
+                b[2..2 + data.as_bytes().len()].copy_from_slice(data.as_bytes());
+                b
+            }
+            Reply::Subcommand { ack, id, data } => {
+                let mut b = self.report(s, clock, cfg).0;
+                b[0] = REPLY_ID;
+                b[12..].fill(0);
+                b[13] = *ack;
+                b[14] = *id;
+                b[15..15 + data.as_bytes().len()].copy_from_slice(data.as_bytes());
+                b
+            }
+        }
+    }
+}
+
+fn check_len(data: &[u8], len: usize) -> Result<()> {
+    if data.len() < len {
+        return Err(Error::Length { expected: len, got: data.len() });
+    }
+    Ok(())
+}
+
+/// `SPI_READ` reply: address (LE u32) and length echoed, then the bytes.
+/// Reads longer than `SPI_READ_MAX` are cut short.
+fn spi_read(args: &[u8]) -> Payload {
+    let mut a = [0u8; 5];
+    let n = args.len().min(5);
+    a[..n].copy_from_slice(&args[..n]);
+    let addr = u32::from_le_bytes([a[0], a[1], a[2], a[3]]);
+    let len = (a[4] as usize).min(SPI_READ_MAX);
+    let mut d = [0xFFu8; 5 + SPI_READ_MAX];
+    d[..4].copy_from_slice(&a[..4]);
+    d[4] = len as u8;
+    for (i, byte) in d[5..5 + len].iter_mut().enumerate() {
+        let at = addr.wrapping_add(i as u32);
+        for (base, bytes) in SPI_FLASH {
+            if let Some(v) = at.checked_sub(*base).and_then(|off| bytes.get(off as usize)) {
+                *byte = *v;
+            }
+        }
+    }
+    Payload::new(&d[..5 + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Buttons;
+
+    const MAC: [u8; 6] = [0x02, 0x48, 0x49, 0x44, 0x52, 0x41];
+
+    fn subcommand(id: u8, args: &[u8]) -> Vec<u8> {
+        let mut b = vec![0u8; 49];
+        b[0] = OUTPUT_SUBCOMMAND_ID;
+        b[2..10].copy_from_slice(&[0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40]);
+        b[10] = id;
+        b[11..11 + args.len()].copy_from_slice(args);
+        b
+    }
+
+    fn reply_data(r: Option<Reply>) -> (u8, Vec<u8>) {
+        match r {
+            Some(Reply::Subcommand { ack, data, .. }) => (ack, data.as_bytes().to_vec()),
+            other => panic!("not a subcommand reply: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn usb_handshake() {
+        let mut s = Session::new(MAC);
+        let r = s.handle(&[OUTPUT_USB_ID, usb::STATUS]).unwrap().unwrap();
+        let b = s.reply_report(
+            &r,
+            &PadState::default(),
+            &ReportClock::default(),
+            &DeviceConfig::default(),
+        );
+        assert_eq!(b[..10], [0x81, 0x01, 0x00, 0x03, 0x41, 0x52, 0x44, 0x49, 0x48, 0x02]);
+        let r = s.handle(&[OUTPUT_USB_ID, usb::HANDSHAKE]).unwrap();
+        assert_eq!(r, Some(Reply::Usb { command: usb::HANDSHAKE, data: Payload::new(&[]) }));
+        assert_eq!(s.handle(&[OUTPUT_USB_ID, usb::HID_ONLY]).unwrap(), None);
+        assert!(s.hid_only);
+    }
+
+    #[test]
+    fn device_info_and_mode() {
+        let mut s = Session::new(MAC);
+        let (ack, d) = reply_data(s.handle(&subcommand(subcommand::DEVICE_INFO, &[])).unwrap());
+        assert_eq!(ack, 0x82);
+        assert_eq!(d[2], TYPE_PRO);
+        assert_eq!(d[4..10], MAC);
+
+        assert!(!s.streaming());
+        let (ack, d) =
+            reply_data(s.handle(&subcommand(subcommand::SET_INPUT_MODE, &[MODE_FULL])).unwrap());
+        assert_eq!((ack, d.len()), (0x80, 0));
+        assert!(s.streaming());
+    }
+
+    #[test]
+    fn spi_calibration_reads() {
+        let mut s = Session::new(MAC);
+        let read = |s: &mut Session, addr: u32, len: u8| {
+            let mut args = addr.to_le_bytes().to_vec();
+            args.push(len);
+            reply_data(s.handle(&subcommand(subcommand::SPI_READ, &args)).unwrap())
+        };
+        let (ack, d) = read(&mut s, 0x603D, 18);
+        assert_eq!(ack, 0x90);
+        assert_eq!(d[..5], [0x3D, 0x60, 0, 0, 18]);
+        assert_eq!(d[5..], SPI_FLASH[3].1[..]);
+
+        // user calibration is absent, reads past a region are erased flash
+        let (_, d) = read(&mut s, 0x8010, 22);
+        assert!(d[5..].iter().all(|b| *b == 0xFF));
+        let (_, d) = read(&mut s, 0x6038, 0x40);
+        assert_eq!(d.len(), 5 + SPI_READ_MAX);
+        assert_eq!(d[4] as usize, SPI_READ_MAX);
+        assert_eq!(d[5..10], [0xFF; 5]);
+        assert_eq!(d[10..13], [0xFF, 0xF7, 0x7F]);
+    }
+
+    #[test]
+    fn imu_enable_gates_motion() {
+        let mut s = Session::new(MAC);
+        let pad = PadState {
+            buttons: Buttons::A,
+            motion: crate::Motion { gyro: [7; 3], accel: [9; 3] },
+            ..Default::default()
+        };
+        let cfg = DeviceConfig { features: Features::GYRO, ..Default::default() };
+        let clock = ReportClock::default();
+        assert_eq!(s.report(&pad, &clock, &cfg).0[13..49], [0; 36]);
+
+        let r = s.handle(&subcommand(subcommand::ENABLE_IMU, &[1])).unwrap().unwrap();
+        assert!(s.imu);
+        assert_eq!(s.report(&pad, &clock, &cfg).0[13..15], [9, 0]);
+
+        // replies carry the pad state but not the IMU
+        let b = s.reply_report(&r, &pad, &clock, &cfg);
+        assert_eq!(b[0], REPLY_ID);
+        assert_eq!(b[3], 0x04);
+        assert_eq!(b[13..16], [0x80, subcommand::ENABLE_IMU, 0]);
+    }
+
+    #[test]
+    fn malformed_output_reports() {
+        let mut s = Session::new(MAC);
+        assert_eq!(s.handle(&[0x01, 0x00]), Err(Error::Length { expected: 11, got: 2 }));
+        assert_eq!(s.handle(&[0x42]), Err(Error::ReportId(0x42)));
+        assert_eq!(s.handle(&[OUTPUT_RUMBLE_ID; 10]), Ok(None));
+    }
+}
@@ -0,0 +1,360 @@
+//! Touchpad model for DS4/DS5 pads: two fingers with tracking IDs, and
+//! gestures (tap, swipe, two-finger scroll, pinch) expanded into timed frames.
+//! Frames go out through `PadState::touch`, so they only reach the host on a
+//! pad created with `Features::TOUCH`.
+
+use crate::{PadState, TouchPoint};
+use alloc::vec::Vec;
+use core::time::Duration;
+use libm::roundf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Fingers a DS4/DS5 touchpad tracks at once.
+pub const MAX_FINGERS: usize = 2;
+/// Tracking IDs are 7 bits on the wire.
+const ID_MASK: u8 = 0x7F;
+/// Distance between the two fingers of a scroll, as a fraction of the pad width.
+pub const SCROLL_SPREAD: f32 = 0.15;
+/// Most movement frames one gesture expands to; a finer `interval` is stretched.
+pub const MAX_STEPS: u32 = 10_000;
+
+/// A point on the pad, `0.0..=1.0` from the top-left corner on each axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TouchPos {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl TouchPos {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Self { x: self.x + (to.x - self.x) * t, y: self.y + (to.y - self.y) * t }
+    }
+
+    fn offset(self, dx: f32, dy: f32) -> Self {
+        Self { x: self.x + dx, y: self.y + dy }
+    }
+}
+
+/// Normalized `0.0..=1.0` to the full u16 range `TouchPoint` uses; out of range clamps.
+fn to_full(v: f32) -> u16 {
+    roundf(v.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
+
+/// Which fingers are down, where, and under which tracking ID. A new contact
+/// gets the next ID; a lifted finger keeps its last ID and position, as real
+/// pads report it. Fingers from `MAX_FINGERS` on are ignored, like a third
+/// contact on the real pad.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Touchpad {
+    points: [TouchPoint; MAX_FINGERS],
+    next_id: u8,
+}
+
+impl Touchpad {
+    /// Pick up from `points`, e.g. the last state sent: new contacts get IDs
+    /// after the highest one there, so the host doesn't mistake them for old ones.
+    pub fn resume(points: [TouchPoint; MAX_FINGERS]) -> Self {
+        let last = points.iter().map(|p| p.id & ID_MASK).max().unwrap_or(0);
+        let used = points.iter().any(|p| *p != TouchPoint::default());
+        Self { points, next_id: if used { (last + 1) & ID_MASK } else { 0 } }
+    }
+
+    /// Put `finger` down at `at` as a new contact; one already down is lifted first.
+    pub fn press(&mut self, finger: usize, at: TouchPos) {
+        let Some(p) = self.points.get_mut(finger) else { return };
+        *p = TouchPoint { down: 1, id: self.next_id, x: to_full(at.x), y: to_full(at.y) };
+        self.next_id = (self.next_id + 1) & ID_MASK;
+    }
+
+    /// Move `finger` if it is down.
+    pub fn move_to(&mut self, finger: usize, at: TouchPos) {
+        if let Some(p) = self.points.get_mut(finger).filter(|p| p.down != 0) {
+            (p.x, p.y) = (to_full(at.x), to_full(at.y));
+        }
+    }
+
+    pub fn lift(&mut self, finger: usize) {
+        if let Some(p) = self.points.get_mut(finger) {
+            p.down = 0;
+        }
+    }
+
+    pub fn lift_all(&mut self) {
+        (0..MAX_FINGERS).for_each(|f| self.lift(f));
+    }
+
+    pub fn is_down(&self, finger: usize) -> bool {
+        self.points.get(finger).is_some_and(|p| p.down != 0)
+    }
+
+    pub fn points(&self) -> [TouchPoint; MAX_FINGERS] {
+        self.points
+    }
+
+    /// Copy the fingers into `s`, ready for the DS4/DS5 packers.
+    pub fn apply(&self, s: &mut PadState) {
+        s.touch = self.points;
+    }
+}
+
+/// The touchpad `at` after a gesture started.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TouchFrame {
+    pub at: Duration,
+    pub points: [TouchPoint; MAX_FINGERS],
+}
+
+impl TouchFrame {
+    pub fn apply(&self, s: &mut PadState) {
+        s.touch = self.points;
+    }
+}
+
+/// A touchpad gesture. Serialized with a `gesture` tag, e.g.
+/// `{"gesture": "swipe", "from": {"x": 0.2, "y": 0.5}, "to": {"x": 0.8, "y": 0.5}}`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "gesture", rename_all = "snake_case"))]
+pub enum Gesture {
+    /// One finger down at `at` for `hold_ms`.
+    Tap {
+        at: TouchPos,
+        #[cfg_attr(feature = "serde", serde(default = "default_tap_ms"))]
+        hold_ms: u32,
+    },
+    /// One finger dragged in a straight line.
+    Swipe {
+        from: TouchPos,
+        to: TouchPos,
+        #[cfg_attr(feature = "serde", serde(default = "default_move_ms"))]
+        duration_ms: u32,
+    },
+    /// Two fingers side by side, `SCROLL_SPREAD` apart around `at`, both moved
+    /// by `dx`/`dy`.
+    Scroll {
+        at: TouchPos,
+        dx: f32,
+        dy: f32,
+        #[cfg_attr(feature = "serde", serde(default = "default_move_ms"))]
+        duration_ms: u32,
+    },
+    /// Two fingers on a horizontal line through `at`, their distance going from
+    /// `from` to `to` (fractions of the pad width): apart to zoom in, together to zoom out.
+    Pinch {
+        at: TouchPos,
+        from: f32,
+        to: f32,
+        #[cfg_attr(feature = "serde", serde(default = "default_move_ms"))]
+        duration_ms: u32,
+    },
+}
+
+#[cfg(feature = "serde")]
+fn default_tap_ms() -> u32 {
+    80
+}
+
+#[cfg(feature = "serde")]
+fn default_move_ms() -> u32 {
+    250
+}
+
+impl Gesture {
+    pub fn duration(&self) -> Duration {
+        let ms = match *self {
+            Gesture::Tap { hold_ms, .. } => hold_ms,
+            Gesture::Swipe { duration_ms, .. }
+            | Gesture::Scroll { duration_ms, .. }
+            | Gesture::Pinch { duration_ms, .. } => duration_ms,
+        };
+        Duration::from_millis(ms as u64)
+    }
+
+    /// Where each finger is at `t` (`0.0..=1.0` through the gesture).
+    fn fingers(&self, t: f32) -> ([TouchPos; MAX_FINGERS], usize) {
+        match *self {
+            Gesture::Tap { at, .. } => ([at, at], 1),
+            Gesture::Swipe { from, to, .. } => {
+                let p = from.lerp(to, t);
+                ([p, p], 1)
+            }
+            Gesture::Scroll { at, dx, dy, .. } => {
+                let c = at.offset(dx * t, dy * t);
+                let half = SCROLL_SPREAD / 2.0;
+                ([c.offset(-half, 0.0), c.offset(half, 0.0)], 2)
+            }
+            Gesture::Pinch { at, from, to, .. } => {
+                let half = (from + (to - from) * t) / 2.0;
+                ([at.offset(-half, 0.0), at.offset(half, 0.0)], 2)
+            }
+        }
+    }
+
+    /// The gesture on `pad` as frames `interval` apart: fingers go down in the
+    /// first frame and move until `duration`, and a last frame one `interval`
+    /// later lifts them. At most `MAX_STEPS` frames are spread over `duration`,
+    /// however short `interval` is. New contacts take their tracking IDs from
+    /// `pad`, which is left with every finger lifted.
+    pub fn frames(&self, pad: &mut Touchpad, interval: Duration) -> Vec<TouchFrame> {
+        let duration = self.duration();
+        let steps = match interval.as_nanos() {
+            0 => 1,
+            i => u32::try_from(duration.as_nanos().div_ceil(i))
+                .unwrap_or(u32::MAX)
+                .clamp(1, MAX_STEPS),
+        };
+        let mut out = Vec::with_capacity(steps as usize + 2);
+        for step in 0..=steps {
+            let (at, n) = self.fingers(step as f32 / steps as f32);
+            for (finger, pos) in at.into_iter().enumerate().take(n) {
+                match step {
+                    0 => pad.press(finger, pos),
+                    _ => pad.move_to(finger, pos),
+                }
+            }
+            out.push(TouchFrame { at: duration * step / steps, points: pad.points() });
+        }
+        pad.lift_all();
+        out.push(TouchFrame { at: duration + interval, points: pad.points() });
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{DS4Report, DS5Report, ReportClock};
+    use crate::{DeviceConfig, Features};
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn tracking_ids() {
+        let mut pad = Touchpad::default();
+        pad.press(0, TouchPos::new(0.5, 0.5));
+        pad.press(1, TouchPos::new(1.5, -1.0));
+        assert_eq!(pad.points()[1], TouchPoint { down: 1, id: 1, x: u16::MAX, y: 0 });
+        pad.lift(0);
+        pad.move_to(0, TouchPos::new(0.0, 0.0));
+        assert_eq!(pad.points()[0], TouchPoint { down: 0, id: 0, x: 32768, y: 32768 });
+        pad.press(0, TouchPos::new(0.0, 0.0));
+        assert_eq!(pad.points()[0].id, 2);
+
+        let mut pad = Touchpad { next_id: ID_MASK, ..Default::default() };
+        pad.press(0, TouchPos::default());
+        pad.press(0, TouchPos::default());
+        assert_eq!(pad.points()[0].id, 0);
+
+        let before = pad;
+        pad.press(MAX_FINGERS, TouchPos::default());
+        pad.move_to(MAX_FINGERS, TouchPos::default());
+        pad.lift(MAX_FINGERS);
+        assert_eq!(pad, before);
+        assert!(!pad.is_down(MAX_FINGERS));
+
+        let mut pad = Touchpad::resume(before.points());
+        pad.press(1, TouchPos::default());
+        assert_eq!(pad.points()[1].id, 1);
+        assert_eq!(Touchpad::resume(Default::default()), Touchpad::default());
+    }
+
+    #[test]
+    fn swipe_frames() {
+        let swipe = Gesture::Swipe {
+            from: TouchPos::new(0.0, 0.5),
+            to: TouchPos::new(1.0, 0.5),
+            duration_ms: 100,
+        };
+        let mut pad = Touchpad::default();
+        let f = swipe.frames(&mut pad, 30 * MS);
+        let at: Vec<_> = f.iter().map(|f| f.at.as_millis()).collect();
+        assert_eq!(at, [0, 25, 50, 75, 100, 130]);
+        assert_eq!(f[0].points[0], TouchPoint { down: 1, id: 0, x: 0, y: 32768 });
+        assert_eq!(f[2].points[0].x, 32768);
+        assert_eq!(f[4].points[0].x, u16::MAX);
+        assert!(f[..5].iter().all(|f| f.points[0].id == 0 && f.points[1].down == 0));
+        assert_eq!(f[5].points[0], TouchPoint { down: 0, ..f[4].points[0] });
+        assert!(!pad.is_down(0));
+
+        // the next gesture is a new contact
+        let tap = Gesture::Tap { at: TouchPos::new(0.5, 0.5), hold_ms: 0 };
+        let f = tap.frames(&mut pad, 8 * MS);
+        assert_eq!(f.len(), 3);
+        assert_eq!(f[0].points[0].id, 1);
+        assert_eq!(f[2].at, 8 * MS);
+
+        let long = Gesture::Tap { at: TouchPos::default(), hold_ms: u32::MAX };
+        let f = long.frames(&mut pad, Duration::from_nanos(1));
+        assert_eq!(f.len(), MAX_STEPS as usize + 2);
+        assert_eq!(f[MAX_STEPS as usize].at, long.duration());
+    }
+
+    #[test]
+    fn two_finger_gestures() {
+        let mut pad = Touchpad::default();
+        let scroll =
+            Gesture::Scroll { at: TouchPos::new(0.5, 0.8), dx: 0.0, dy: -0.5, duration_ms: 40 };
+        let f = scroll.frames(&mut pad, 10 * MS);
+        let (first, last) = (f[0].points, f[4].points);
+        assert_eq!((first[0].id, first[1].id), (0, 1));
+        assert!(first.iter().all(|p| p.down == 1));
+        assert_eq!(last[1].x - last[0].x, first[1].x - first[0].x);
+        assert_eq!(last[0].y, to_full(0.3));
+
+        let pinch =
+            Gesture::Pinch { at: TouchPos::new(0.5, 0.5), from: 0.2, to: 0.6, duration_ms: 40 };
+        let f = pinch.frames(&mut pad, 10 * MS);
+        let gap = |f: &TouchFrame| f.points[1].x - f.points[0].x;
+        assert!(f.windows(2).take(4).all(|w| gap(&w[1]) > gap(&w[0])));
+        assert_eq!((f[4].points[0].x, f[4].points[1].x), (to_full(0.2), to_full(0.8)));
+        assert_eq!(f[0].points[0].id, 2);
+        assert!(f[5].points.iter().all(|p| p.down == 0));
+    }
+
+    #[test]
+    fn frames_pack_into_ds4_and_ds5() {
+        let cfg = DeviceConfig { features: Features::TOUCH, ..Default::default() };
+        let clock = ReportClock::default();
+        let mut pad = Touchpad::default();
+        pad.press(0, TouchPos::new(0.0, 0.0));
+        pad.press(1, TouchPos::new(1.0, 1.0));
+        let mut s = PadState::default();
+        pad.apply(&mut s);
+
+        let b = DS4Report::pack(&s, &clock, &cfg).0;
+        assert_eq!(b[35..39], [0x00, 0, 0, 0]);
+        // id 1, x = 1919, y = 941
+        assert_eq!(b[39..43], [0x01, 0x7F, 0xD7, 0x3A]);
+        assert_eq!(DS4Report(b).state().touch, s.touch);
+        let b = DS5Report::pack(&s, &clock, &cfg).0;
+        assert_eq!(DS5Report(b).state().touch, s.touch);
+
+        pad.lift_all();
+        pad.apply(&mut s);
+        let b = DS5Report::pack(&s, &clock, &cfg).0;
+        assert_eq!((b[33], b[37]), (0x80, 0x81));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_form() {
+        let g: Gesture = serde_json::from_str(
+            r#"{"gesture": "scroll", "at": {"x": 0.5, "y": 0.5}, "dx": 0, "dy": 0.25}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            g,
+            Gesture::Scroll { at: TouchPos::new(0.5, 0.5), dx: 0.0, dy: 0.25, duration_ms: 250 }
+        );
+        let tap: Gesture =
+            serde_json::from_str(r#"{"gesture": "tap", "at": {"x": 0.1, "y": 0.9}}"#).unwrap();
+        assert_eq!(tap.duration(), 80 * MS);
+        assert!(serde_json::from_str::<Gesture>(r#"{"gesture": "twist"}"#).is_err());
+    }
+}
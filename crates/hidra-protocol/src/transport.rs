@@ -0,0 +1,88 @@
+//! Transport a device pretends to be connected over, and the CRC32 that seals
+//! DS4/DS5 Bluetooth reports.
+
+use crate::error::{Error, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Chosen at create time. X360 has no Bluetooth variant and always reports as USB.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Transport {
+    #[default]
+    Usb = 0,
+    Bluetooth = 1,
+}
+
+/// Bluetooth HID header byte hashed in front of input (DATA | INPUT) reports.
+pub const BT_INPUT_SEED: u8 = 0xA1;
+/// Same, for output (DATA | OUTPUT) reports.
+pub const BT_OUTPUT_SEED: u8 = 0xA2;
+/// Full length of DS4 0x11 and DS5 0x31 reports, CRC included.
+pub const BT_REPORT_LEN: usize = 78;
+
+const CRC_TABLE: [u32; 256] = {
+    let mut t = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        t[i] = c;
+        i += 1;
+    }
+    t
+};
+
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |c, b| CRC_TABLE[((c ^ *b as u32) & 0xFF) as usize] ^ (c >> 8))
+}
+
+/// CRC-32 (IEEE) of `seed` followed by `data`, as the pads compute it.
+pub fn crc32(seed: u8, data: &[u8]) -> u32 {
+    !crc32_update(crc32_update(!0, &[seed]), data)
+}
+
+/// Write the CRC of everything before the last four bytes into them.
+pub(crate) fn seal(seed: u8, r: &mut [u8]) {
+    let n = r.len() - 4;
+    let crc = crc32(seed, &r[..n]);
+    r[n..].copy_from_slice(&crc.to_le_bytes());
+}
+
+/// Check the trailing CRC written by `seal`.
+pub(crate) fn verify(seed: u8, r: &[u8]) -> Result<()> {
+    let n = r.len() - 4;
+    let expected = crc32(seed, &r[..n]);
+    let got = u32::from_le_bytes([r[n], r[n + 1], r[n + 2], r[n + 3]]);
+    if got != expected {
+        return Err(Error::Crc { expected, got });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(!crc32_update(!0, b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn seal_then_verify() {
+        let mut r = [0u8; BT_REPORT_LEN];
+        r[0] = 0x31;
+        seal(BT_OUTPUT_SEED, &mut r);
+        assert_eq!(verify(BT_OUTPUT_SEED, &r), Ok(()));
+        assert!(matches!(verify(BT_INPUT_SEED, &r), Err(Error::Crc { .. })));
+        r[10] ^= 1;
+        assert!(matches!(verify(BT_OUTPUT_SEED, &r), Err(Error::Crc { .. })));
+    }
+}
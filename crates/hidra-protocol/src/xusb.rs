@@ -0,0 +1,323 @@
+//! XInput device subtypes an X360 pad can present as, and how each one's
+//! controls sit on the gamepad fields of `X360Report`. The conventions are the
+//! ones games read from real Rock Band / Guitar Hero hardware.
+
+use crate::{Buttons, PadState};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// `XINPUT_CAPABILITIES::SubType`. Chosen at create time; only X360 has subtypes.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum XusbSubtype {
+    #[default]
+    Gamepad = 0x01,
+    ArcadeStick = 0x03,
+    DancePad = 0x05,
+    Guitar = 0x06,
+    DrumKit = 0x08,
+}
+
+impl XusbSubtype {
+    pub const ALL: [XusbSubtype; 5] = [
+        XusbSubtype::Gamepad,
+        XusbSubtype::ArcadeStick,
+        XusbSubtype::DancePad,
+        XusbSubtype::Guitar,
+        XusbSubtype::DrumKit,
+    ];
+
+    /// Stable wire name.
+    pub const fn name(self) -> &'static str {
+        match self {
+            XusbSubtype::Gamepad => "gamepad",
+            XusbSubtype::ArcadeStick => "arcade_stick",
+            XusbSubtype::DancePad => "dance_pad",
+            XusbSubtype::Guitar => "guitar",
+            XusbSubtype::DrumKit => "drum_kit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name().eq_ignore_ascii_case(name))
+    }
+
+    /// 0 is what clients predating subtypes leave in the byte, and means a gamepad.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(XusbSubtype::Gamepad),
+            _ => Self::ALL.into_iter().find(|s| *s as u8 == id),
+        }
+    }
+
+    /// `s` with every control this subtype lacks cleared. Expects full-scale
+    /// values, as `DeviceConfig::prepare` returns them.
+    /// - arcade stick: no sticks, triggers are buttons (pressed past half = full)
+    /// - dance pad: no sticks, no triggers
+    /// - guitar: RX whammy, RY tilt, LT pickup selector; nothing else analog
+    /// - drum kit: sticks carry pad velocities, no triggers
+    pub fn shape(self, s: &PadState) -> PadState {
+        let mut s = *s;
+        match self {
+            XusbSubtype::Gamepad => {}
+            XusbSubtype::ArcadeStick => {
+                (s.lx, s.ly, s.rx, s.ry) = (0, 0, 0, 0);
+                s.lt = if s.lt >= 0x8000 { u16::MAX } else { 0 };
+                s.rt = if s.rt >= 0x8000 { u16::MAX } else { 0 };
+            }
+            XusbSubtype::DancePad => {
+                (s.lx, s.ly, s.rx, s.ry, s.lt, s.rt) = (0, 0, 0, 0, 0, 0);
+            }
+            XusbSubtype::Guitar => {
+                (s.lx, s.ly, s.rt) = (0, 0, 0);
+            }
+            XusbSubtype::DrumKit => {
+                (s.lt, s.rt) = (0, 0);
+            }
+        }
+        s
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct Frets: u8 {
+        const GREEN  = 1 << 0;
+        const RED    = 1 << 1;
+        const YELLOW = 1 << 2;
+        const BLUE   = 1 << 3;
+        const ORANGE = 1 << 4;
+    }
+}
+
+/// Fret colors in the face buttons the guitar reports them as.
+const FRET_BUTTONS: [(Frets, Buttons); 5] = [
+    (Frets::GREEN, Buttons::A),
+    (Frets::RED, Buttons::B),
+    (Frets::YELLOW, Buttons::Y),
+    (Frets::BLUE, Buttons::X),
+    (Frets::ORANGE, Buttons::LEFT_SHOULDER),
+];
+
+/// A guitar's controls. Strums are the d-pad, star power is BACK.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Guitar {
+    pub frets: Frets,
+    pub strum_up: bool,
+    pub strum_down: bool,
+    /// 0 at rest, `u16::MAX` pushed all the way down.
+    pub whammy: u16,
+    /// Positive with the neck raised.
+    pub tilt: i16,
+    /// Position of the pickup selector, 0..=255.
+    pub pickup: u8,
+    pub star_power: bool,
+    pub start: bool,
+    pub guide: bool,
+}
+
+impl Guitar {
+    /// As a pad state in the default `InputRanges`.
+    pub fn to_state(&self) -> PadState {
+        let mut buttons = Buttons::empty();
+        for (fret, b) in FRET_BUTTONS {
+            buttons.set(b, self.frets.contains(fret));
+        }
+        buttons.set(Buttons::DPAD_UP, self.strum_up);
+        buttons.set(Buttons::DPAD_DOWN, self.strum_down);
+        buttons.set(Buttons::BACK, self.star_power);
+        buttons.set(Buttons::START, self.start);
+        buttons.set(Buttons::GUIDE, self.guide);
+        PadState {
+            buttons,
+            rx: (self.whammy as i32 + i16::MIN as i32) as i16,
+            ry: self.tilt,
+            lt: self.pickup as u16,
+            ..Default::default()
+        }
+    }
+
+    /// Read back from a state such as `X360Report::state` returns.
+    pub fn from_state(s: &PadState) -> Self {
+        let mut frets = Frets::empty();
+        for (fret, b) in FRET_BUTTONS {
+            frets.set(fret, s.buttons.contains(b));
+        }
+        Self {
+            frets,
+            strum_up: s.buttons.contains(Buttons::DPAD_UP),
+            strum_down: s.buttons.contains(Buttons::DPAD_DOWN),
+            whammy: (s.rx as i32 - i16::MIN as i32) as u16,
+            tilt: s.ry,
+            pickup: s.lt.min(255) as u8,
+            star_power: s.buttons.contains(Buttons::BACK),
+            start: s.buttons.contains(Buttons::START),
+            guide: s.buttons.contains(Buttons::GUIDE),
+        }
+    }
+}
+
+/// A drum kit's hits, each as a velocity where 0 is not hit.
+///
+/// A hit presses its color's face button plus RS for a pad or RB for a cymbal;
+/// yellow and blue cymbals also press d-pad up and down. Velocities share one
+/// stick axis per color: LX red, LY yellow, RX blue, RY green. Green has no
+/// marker of its own, so it reads back as a cymbal only when no pad is hit
+/// alongside it, and a yellow or blue pad hit together with its cymbal reads
+/// back as the cymbal alone.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Drums {
+    pub red: u8,
+    pub yellow: u8,
+    pub blue: u8,
+    pub green: u8,
+    pub yellow_cymbal: u8,
+    pub blue_cymbal: u8,
+    pub green_cymbal: u8,
+    pub kick: bool,
+    /// Second bass pedal.
+    pub kick2: bool,
+    pub start: bool,
+    pub back: bool,
+    pub guide: bool,
+}
+
+fn velocity_to_axis(v: u8) -> i16 {
+    (v as i16) << 7
+}
+
+/// A hit never reads back as 0, whatever the axis says.
+fn axis_to_velocity(a: i16) -> u8 {
+    (a.max(0) >> 7).max(1) as u8
+}
+
+impl Drums {
+    /// As a pad state in the default `InputRanges`.
+    pub fn to_state(&self) -> PadState {
+        let hits = [
+            (Buttons::B, self.red, 0),
+            (Buttons::Y, self.yellow, self.yellow_cymbal),
+            (Buttons::X, self.blue, self.blue_cymbal),
+            (Buttons::A, self.green, self.green_cymbal),
+        ];
+        let mut buttons = Buttons::empty();
+        let mut axes = [0i16; 4];
+        for ((color, pad, cymbal), axis) in hits.into_iter().zip(&mut axes) {
+            if pad > 0 {
+                buttons |= color | Buttons::RIGHT_THUMB;
+            }
+            if cymbal > 0 {
+                buttons |= color | Buttons::RIGHT_SHOULDER;
+            }
+            *axis = velocity_to_axis(pad.max(cymbal));
+        }
+        buttons.set(Buttons::DPAD_UP, self.yellow_cymbal > 0);
+        buttons.set(Buttons::DPAD_DOWN, self.blue_cymbal > 0);
+        buttons.set(Buttons::LEFT_SHOULDER, self.kick);
+        buttons.set(Buttons::LEFT_THUMB, self.kick2);
+        buttons.set(Buttons::START, self.start);
+        buttons.set(Buttons::BACK, self.back);
+        buttons.set(Buttons::GUIDE, self.guide);
+        let [lx, ly, rx, ry] = axes;
+        PadState { buttons, lx, ly, rx, ry, ..Default::default() }
+    }
+
+    /// Read back from a state such as `X360Report::state` returns.
+    pub fn from_state(s: &PadState) -> Self {
+        let b = s.buttons;
+        let pad = b.contains(Buttons::RIGHT_THUMB);
+        let cymbal = b.contains(Buttons::RIGHT_SHOULDER);
+        let hit = |on: bool, axis: i16| if on { axis_to_velocity(axis) } else { 0 };
+        let yellow_cymbal = cymbal && b.contains(Buttons::Y | Buttons::DPAD_UP);
+        let blue_cymbal = cymbal && b.contains(Buttons::X | Buttons::DPAD_DOWN);
+        let green_cymbal = cymbal && b.contains(Buttons::A) && !pad;
+        Self {
+            red: hit(pad && b.contains(Buttons::B), s.lx),
+            yellow: hit(pad && b.contains(Buttons::Y) && !yellow_cymbal, s.ly),
+            blue: hit(pad && b.contains(Buttons::X) && !blue_cymbal, s.rx),
+            green: hit(pad && b.contains(Buttons::A), s.ry),
+            yellow_cymbal: hit(yellow_cymbal, s.ly),
+            blue_cymbal: hit(blue_cymbal, s.rx),
+            green_cymbal: hit(green_cymbal, s.ry),
+            kick: b.contains(Buttons::LEFT_SHOULDER),
+            kick2: b.contains(Buttons::LEFT_THUMB),
+            start: b.contains(Buttons::START),
+            back: b.contains(Buttons::BACK),
+            guide: b.contains(Buttons::GUIDE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids() {
+        for s in XusbSubtype::ALL {
+            assert_eq!(XusbSubtype::from_id(s as u8), Some(s));
+            assert_eq!(XusbSubtype::from_name(s.name()), Some(s));
+        }
+        assert_eq!(XusbSubtype::from_id(0), Some(XusbSubtype::Gamepad));
+        assert_eq!(XusbSubtype::from_id(0x02), None);
+    }
+
+    #[test]
+    fn shape_clears_missing_controls() {
+        let s = PadState { lx: 100, ry: -100, lt: 0x9000, rt: 0x7000, ..Default::default() };
+        assert_eq!(XusbSubtype::Gamepad.shape(&s), s);
+        let arcade = XusbSubtype::ArcadeStick.shape(&s);
+        assert_eq!((arcade.lx, arcade.ry, arcade.lt, arcade.rt), (0, 0, u16::MAX, 0));
+        let dance = XusbSubtype::DancePad.shape(&s);
+        assert_eq!(dance, PadState::default());
+        let guitar = XusbSubtype::Guitar.shape(&s);
+        assert_eq!((guitar.lx, guitar.ry, guitar.lt, guitar.rt), (0, -100, 0x9000, 0));
+        let drums = XusbSubtype::DrumKit.shape(&s);
+        assert_eq!((drums.lx, drums.ry, drums.lt, drums.rt), (100, -100, 0, 0));
+    }
+
+    #[test]
+    fn guitar_round_trip() {
+        let g = Guitar {
+            frets: Frets::GREEN | Frets::ORANGE,
+            strum_down: true,
+            whammy: 0x8000,
+            tilt: 12000,
+            pickup: 3,
+            star_power: true,
+            ..Default::default()
+        };
+        let s = g.to_state();
+        assert_eq!(
+            s.buttons,
+            Buttons::A | Buttons::LEFT_SHOULDER | Buttons::DPAD_DOWN | Buttons::BACK
+        );
+        assert_eq!((s.rx, s.ry, s.lt), (0, 12000, 3));
+        assert_eq!(Guitar::from_state(&s), g);
+        assert_eq!(Guitar::default().to_state().rx, i16::MIN);
+    }
+
+    #[test]
+    fn drums_round_trip() {
+        let d = Drums { red: 200, yellow_cymbal: 64, kick: true, ..Default::default() };
+        let s = d.to_state();
+        assert_eq!(
+            s.buttons,
+            Buttons::B
+                | Buttons::Y
+                | Buttons::RIGHT_THUMB
+                | Buttons::RIGHT_SHOULDER
+                | Buttons::DPAD_UP
+                | Buttons::LEFT_SHOULDER
+        );
+        assert_eq!((s.lx, s.ly, s.rx, s.ry), (200 << 7, 64 << 7, 0, 0));
+        assert_eq!(Drums::from_state(&s), d);
+
+        let d = Drums { blue: 255, green_cymbal: 1, kick2: true, ..Default::default() };
+        assert_eq!(Drums::from_state(&d.to_state()), Drums { green: 1, green_cymbal: 0, ..d });
+        let d = Drums { green_cymbal: 90, blue_cymbal: 10, ..Default::default() };
+        assert_eq!(Drums::from_state(&d.to_state()), d);
+    }
+}
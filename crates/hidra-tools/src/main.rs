@@ -1,9 +1,18 @@
 #![deny(warnings)]
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
-use hidra_client::{destroy, ping, spawn};
-use hidra_ipc::PadState;
-use hidra_protocol::DeviceKind;
+use hidra_client::{destroy, ping, spawn_processed};
+use hidra_protocol::report::{DS4BtReport, DS5BtReport};
+use hidra_protocol::report::{
+    DS4Report, DS5Report, GenericReport, KeyboardNkroReport, KeyboardReport, MouseReport,
+    SwitchProReport, WheelReport, X360Report, XboxOneReport,
+};
+use hidra_protocol::xusb::{Drums, Guitar};
+use hidra_protocol::{
+    Buttons, DeviceConfig, DeviceIdentity, DeviceKind, Features, GenericSpec, Gesture, InputRanges,
+    KeyboardState, MouseButtons, MouseState, PadPipeline, PadState, StickRange, Touchpad,
+    Transport, TriggerRange, XusbSubtype,
+};
 use tracing::info;
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
@@ -13,14 +22,45 @@ enum Cmd {
     Spawn {
         #[arg(value_enum)]
         kind: PadKind,
+        /// Resolution of the trigger values that will be sent (8, 10, 12 or 16 bits).
+        #[arg(long, default_value = "8", value_parser = parse_trigger_bits)]
+        trigger_bits: TriggerRange,
+        /// Sticks are sent as -16384..=16383 instead of the full i16 range.
+        #[arg(long)]
+        half_range_sticks: bool,
+        /// Present DS4/DS5 as connected over Bluetooth.
+        #[arg(long)]
+        bluetooth: bool,
+        /// Stick/trigger processing as JSON, e.g.
+        /// '{"left": [{"stage": "radial_deadzone", "radius": 0.1}]}'.
+        #[arg(long)]
+        pipeline: Option<String>,
+        /// Layout of a generic pad as JSON, e.g.
+        /// '{"axes": 4, "buttons": 12, "product": "Arcade Stick"}'.
+        #[arg(long)]
+        generic: Option<String>,
+        /// XInput subtype of an x360 pad: gamepad, arcade_stick, dance_pad, guitar or drum_kit.
+        #[arg(long, default_value = "gamepad", value_parser = parse_subtype)]
+        subtype: XusbSubtype,
+        /// Keyboards report every key down instead of at most six.
+        #[arg(long)]
+        nkro: bool,
+        /// Picks the default VID/PID, serial and MAC; the same slot is the same controller.
+        #[arg(long, default_value = "0")]
+        slot: u8,
+        /// Identity overrides as JSON, e.g.
+        /// '{"product_id": 2508, "serial": "HIDRA-01", "mac": "02:00:00:00:00:01"}'.
+        #[arg(long)]
+        identity: Option<String>,
     },
     Update {
         #[arg(long)]
         handle: u64,
         #[arg(long)]
         state_json: Option<String>,
-        #[arg(long)]
-        buttons: Option<u16>,
+        /// Raw bits, or names such as `a,paddle1,fn_left`.
+        #[arg(long, value_parser = parse_buttons)]
+        buttons: Option<Buttons>,
         #[arg(long)]
         lx: Option<i16>,
         #[arg(long)]
@@ -34,10 +74,93 @@ enum Cmd {
         #[arg(long)]
         rt: Option<u16>,
     },
+    /// Set the keys held on a keyboard; an empty list releases everything.
+    Keys {
+        #[arg(long)]
+        handle: u64,
+        /// Key names or usages, e.g. `left_shift,a` or `4,5`.
+        #[arg(default_value = "")]
+        keys: String,
+    },
+    /// Move a mouse and set the buttons it holds.
+    Mouse {
+        #[arg(long)]
+        handle: u64,
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        dx: i16,
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        dy: i16,
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        wheel: i16,
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        pan: i16,
+        /// Names such as `left,back`.
+        #[arg(long, value_parser = parse_mouse_buttons)]
+        buttons: Option<MouseButtons>,
+    },
+    /// Play a touchpad gesture on a DS4/DS5 created with the touch feature.
+    /// Every frame replaces the whole pad state: `--state-json` (default all
+    /// released) with the touchpad from the gesture.
+    Touch {
+        #[arg(long)]
+        handle: u64,
+        /// The rest of the pad while the gesture plays, as for `update`. Its
+        /// touch points carry on the tracking IDs of an earlier gesture.
+        #[arg(long)]
+        state_json: Option<String>,
+        /// The gesture as JSON, e.g.
+        /// '{"gesture": "swipe", "from": {"x": 0.2, "y": 0.5}, "to": {"x": 0.8, "y": 0.5}}'.
+        gesture: String,
+        /// Time between frames.
+        #[arg(long, default_value = "8")]
+        interval_ms: u64,
+    },
     Destroy {
         handle: u64,
     },
+    /// Print each output event (rumble, lights, ...) sent to a device as a JSON line.
+    Subscribe {
+        handle: u64,
+    },
+    /// Send a raw output report (hex) to a device on the mock backend.
+    InjectOutput {
+        #[arg(long)]
+        handle: u64,
+        report: String,
+    },
+    /// Read a feature report (ID in hex) from a device and print it as hex.
+    GetFeature {
+        #[arg(long)]
+        handle: u64,
+        #[arg(value_parser = parse_report_id)]
+        id: u8,
+    },
+    /// Send a raw feature report (hex) to a device on the mock backend.
+    SetFeature {
+        #[arg(long)]
+        handle: u64,
+        report: String,
+    },
     Ping,
+    /// Show the ABI version, kinds and features the broker and driver support.
+    Version,
+    /// Decode a captured input report (hex) back into a pad state.
+    Inspect {
+        #[arg(value_enum)]
+        kind: PadKind,
+        report: String,
+        /// Layout of the generic pad that sent the report, as for `spawn`.
+        #[arg(long)]
+        generic: Option<String>,
+        /// Subtype of the x360 pad that sent the report, as for `spawn`.
+        #[arg(long, default_value = "gamepad", value_parser = parse_subtype)]
+        subtype: XusbSubtype,
+    },
+    /// Regenerate the driver's ABI header from hidra-protocol.
+    GenHeader {
+        #[arg(long, default_value = hidra_protocol::abi::C_HEADER_PATH)]
+        out: std::path::PathBuf,
+    },
 }
 
 #[derive(Clone, Copy, clap::ValueEnum)]
@@ -45,6 +168,14 @@ enum PadKind {
     X360,
     Ds4,
     Ds5,
+    XboxOne,
+    SwitchPro,
+    Generic,
+    Wheel,
+    Ds5Edge,
+    XboxElite,
+    Keyboard,
+    Mouse,
 }
 
 #[tokio::main]
@@ -58,13 +189,56 @@ async fn main() -> Result<()> {
         .init();
 
     match Cmd::parse() {
-        Cmd::Spawn { kind } => {
+        Cmd::Spawn {
+            kind,
+            trigger_bits,
+            half_range_sticks,
+            bluetooth,
+            pipeline,
+            generic,
+            subtype,
+            nkro,
+            slot,
+            identity,
+        } => {
             let kind = match kind {
                 PadKind::X360 => DeviceKind::X360,
                 PadKind::Ds4 => DeviceKind::DS4,
                 PadKind::Ds5 => DeviceKind::DS5,
+                PadKind::XboxOne => DeviceKind::XboxOne,
+                PadKind::SwitchPro => DeviceKind::SwitchPro,
+                PadKind::Generic => DeviceKind::Generic,
+                PadKind::Wheel => DeviceKind::Wheel,
+                PadKind::Ds5Edge => DeviceKind::DS5Edge,
+                PadKind::XboxElite => DeviceKind::XboxElite,
+                PadKind::Keyboard => DeviceKind::Keyboard,
+                PadKind::Mouse => DeviceKind::Mouse,
+            };
+            let ranges = InputRanges {
+                trigger: trigger_bits,
+                stick: if half_range_sticks { StickRange::Half } else { StickRange::Full },
             };
-            let h = spawn(kind).await?;
+            let transport = if bluetooth { Transport::Bluetooth } else { Transport::Usb };
+            let generic = parse_generic(generic)?;
+            let identity = match identity {
+                Some(j) => serde_json::from_str(&j).context("invalid --identity")?,
+                None => DeviceIdentity::default(),
+            };
+            let mut config = DeviceConfig {
+                ranges,
+                transport,
+                generic,
+                subtype,
+                slot,
+                identity,
+                ..DeviceConfig::for_kind(kind)
+            };
+            config.features.set(Features::NKRO, nkro);
+            let pipeline = match pipeline {
+                Some(j) => serde_json::from_str(&j).context("invalid --pipeline")?,
+                None => PadPipeline::default(),
+            };
+            let h = spawn_processed(kind, config, pipeline).await?;
             info!(handle = h.0, "spawned handle");
             println!("{}", h.0);
         }
@@ -101,14 +275,213 @@ async fn main() -> Result<()> {
 
             hidra_client::update_state(hidra_client::GamepadHandle(handle), s).await?;
         }
+        Cmd::Keys { handle, keys } => {
+            let names: Vec<serde_json::Value> = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(|k| k.parse::<u8>().map_or_else(|_| k.into(), Into::into))
+                .collect();
+            let s: KeyboardState = serde_json::from_value(serde_json::json!({ "keys": names }))
+                .context("invalid keys")?;
+            info!(handle, state=?s, "updated keys");
+            hidra_client::update_keyboard(hidra_client::GamepadHandle(handle), s).await?;
+        }
+        Cmd::Touch { handle, state_json, gesture, interval_ms } => {
+            let gesture: Gesture = serde_json::from_str(&gesture).context("invalid gesture")?;
+            let base: PadState = match state_json {
+                Some(j) => serde_json::from_str(&j).context("invalid --state-json")?,
+                None => PadState::default(),
+            };
+            let interval = std::time::Duration::from_millis(interval_ms);
+            let start = tokio::time::Instant::now();
+            for frame in gesture.frames(&mut Touchpad::resume(base.touch), interval) {
+                tokio::time::sleep_until(start + frame.at).await;
+                let mut s = base;
+                frame.apply(&mut s);
+                hidra_client::update_state(hidra_client::GamepadHandle(handle), s).await?;
+            }
+            info!(handle, ?gesture, "played gesture");
+        }
+        Cmd::Mouse { handle, dx, dy, wheel, pan, buttons } => {
+            let s = MouseState {
+                buttons: buttons.unwrap_or_default(),
+                dx,
+                dy,
+                wheel,
+                pan,
+                ..Default::default()
+            };
+            info!(handle, state=?s, "moved mouse");
+            hidra_client::update_mouse(hidra_client::GamepadHandle(handle), s).await?;
+        }
         Cmd::Destroy { handle } => {
             destroy(hidra_client::GamepadHandle(handle)).await?;
             info!(handle, "destroyed handle");
         }
+        Cmd::Subscribe { handle } => {
+            let mut events = hidra_client::subscribe(hidra_client::GamepadHandle(handle)).await?;
+            while let Some(event) = events.next().await? {
+                println!("{}", serde_json::to_string(&event)?);
+            }
+            info!(handle, "device destroyed");
+        }
+        Cmd::InjectOutput { handle, report } => {
+            let report = parse_hex(&report)?;
+            hidra_client::inject_output(hidra_client::GamepadHandle(handle), &report).await?;
+            info!(handle, "injected output report");
+        }
+        Cmd::GetFeature { handle, id } => {
+            let report = hidra_client::get_feature(hidra_client::GamepadHandle(handle), id).await?;
+            println!("{}", report.iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>().join(" "));
+        }
+        Cmd::SetFeature { handle, report } => {
+            let report = parse_hex(&report)?;
+            hidra_client::set_feature(hidra_client::GamepadHandle(handle), &report).await?;
+            info!(handle, "set feature report");
+        }
         Cmd::Ping => {
             ping().await?;
             info!("pong");
         }
+        Cmd::Version => {
+            let info = hidra_client::hello().await?;
+            println!("abi {}", info.abi_version);
+            println!("kinds {:?}", info.kinds);
+            println!("features {:?}", info.features);
+        }
+        Cmd::Inspect { kind, report, generic, subtype } => {
+            let bytes = parse_hex(&report)?;
+            let state = match kind {
+                PadKind::X360 => X360Report::try_from(&bytes[..])?.state(),
+                PadKind::Ds4 if bytes.first() == Some(&DS4BtReport::ID) => {
+                    DS4BtReport::try_from(&bytes[..])?.state()
+                }
+                PadKind::Ds4 => DS4Report::try_from(&bytes[..])?.state(),
+                PadKind::Ds5 | PadKind::Ds5Edge if bytes.first() == Some(&DS5BtReport::ID) => {
+                    DS5BtReport::try_from(&bytes[..])?.state()
+                }
+                PadKind::Ds5 | PadKind::Ds5Edge => DS5Report::try_from(&bytes[..])?.state(),
+                PadKind::XboxOne | PadKind::XboxElite => {
+                    XboxOneReport::try_from(&bytes[..])?.state()
+                }
+                PadKind::SwitchPro => SwitchProReport::try_from(&bytes[..])?.state(),
+                PadKind::Wheel => WheelReport::try_from(&bytes[..])?.state(),
+                PadKind::Generic => {
+                    let spec = parse_generic(generic)?;
+                    GenericReport::parse(&bytes, &spec)?.state(&spec)?
+                }
+                PadKind::Keyboard => {
+                    let keys = match bytes.len() {
+                        8 => KeyboardReport::try_from(&bytes[..])?.state(),
+                        _ => KeyboardNkroReport::try_from(&bytes[..])?.state(),
+                    };
+                    println!("{}", serde_json::to_string_pretty(&keys)?);
+                    return Ok(());
+                }
+                PadKind::Mouse => {
+                    println!("{:#?}", MouseReport::try_from(&bytes[..])?.state());
+                    return Ok(());
+                }
+            };
+            println!("{state:#?}");
+            match (kind, subtype) {
+                (PadKind::X360, XusbSubtype::Guitar) => {
+                    println!("{:#?}", Guitar::from_state(&state))
+                }
+                (PadKind::X360, XusbSubtype::DrumKit) => {
+                    println!("{:#?}", Drums::from_state(&state))
+                }
+                _ => {}
+            }
+        }
+        Cmd::GenHeader { out } => {
+            std::fs::write(&out, hidra_protocol::abi::c_header())
+                .with_context(|| format!("writing {}", out.display()))?;
+            info!(path = %out.display(), "wrote ABI header");
+        }
     }
     Ok(())
 }
+
+fn parse_trigger_bits(s: &str) -> Result<TriggerRange, String> {
+    let bits: u8 = s.parse().map_err(|e| format!("{e}"))?;
+    TriggerRange::try_from(bits).map_err(|b| format!("unsupported trigger resolution: {b} bits"))
+}
+
+fn parse_buttons(s: &str) -> Result<Buttons, String> {
+    if let Ok(bits) = s.parse::<u32>() {
+        return Ok(Buttons::from_bits_truncate(bits));
+    }
+    let names: Vec<&str> = s.split(',').map(str::trim).collect();
+    serde_json::from_value(serde_json::json!(names)).map_err(|e| e.to_string())
+}
+
+fn parse_mouse_buttons(s: &str) -> Result<MouseButtons, String> {
+    let names: Vec<&str> = s.split(',').map(str::trim).filter(|n| !n.is_empty()).collect();
+    serde_json::from_value(serde_json::json!(names)).map_err(|e| e.to_string())
+}
+
+fn parse_subtype(s: &str) -> Result<XusbSubtype, String> {
+    XusbSubtype::from_name(s).ok_or_else(|| format!("unknown subtype `{s}`"))
+}
+
+fn parse_generic(json: Option<String>) -> Result<GenericSpec> {
+    let spec: GenericSpec = match json {
+        Some(j) => serde_json::from_str(&j).context("invalid --generic")?,
+        None => GenericSpec::default(),
+    };
+    spec.validate().context("invalid --generic")?;
+    Ok(spec)
+}
+
+/// Hex bytes, optionally separated by whitespace, `:` or `-`.
+fn parse_report_id(s: &str) -> Result<u8, String> {
+    let hex = s.trim_start_matches("0x").trim_start_matches("0X");
+    u8::from_str_radix(hex, 16).map_err(|_| format!("invalid report ID `{s}`"))
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let digits: String =
+        s.chars().filter(|c| !c.is_whitespace() && *c != ':' && *c != '-').collect();
+    anyhow::ensure!(digits.is_ascii(), "invalid hex byte");
+    anyhow::ensure!(digits.len().is_multiple_of(2), "odd number of hex digits");
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).context("invalid hex byte"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn button_names() {
+        assert_eq!(
+            parse_buttons("a, paddle1,FN_LEFT"),
+            Ok(Buttons::A | Buttons::PADDLE1 | Buttons::FN_LEFT)
+        );
+        assert_eq!(parse_buttons("4096"), Ok(Buttons::A));
+        assert!(parse_buttons("a,warp").is_err());
+    }
+
+    #[test]
+    fn mouse_button_names() {
+        assert_eq!(parse_mouse_buttons("left"), Ok(MouseButtons::LEFT));
+        assert_eq!(
+            parse_mouse_buttons("Right,back,"),
+            Ok(MouseButtons::RIGHT | MouseButtons::BACK)
+        );
+        assert_eq!(parse_mouse_buttons(""), Ok(MouseButtons::empty()));
+        assert!(parse_mouse_buttons("middle,side").is_err());
+    }
+
+    #[test]
+    fn generic_spec_is_validated() {
+        assert!(parse_generic(None).is_ok());
+        assert!(parse_generic(Some(r#"{"axes": 2, "buttons": 8}"#.into())).is_ok());
+        assert!(parse_generic(Some(r#"{"axes": 7}"#.into())).is_err());
+        assert!(parse_generic(Some(r#"{"axis_bits": 17}"#.into())).is_err());
+    }
+}
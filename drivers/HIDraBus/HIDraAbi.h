@@ -0,0 +1,543 @@
+// Generated from hidra-protocol (hidra_protocol::abi). DO NOT EDIT.
+// Regenerate with `hidra gen-header`; a hidra-protocol test fails when stale.
+#pragma once
+
+#define HIDRA_FFI_ABI_VERSION 2
+#define HIDRA_PAD_STATE_VERSION 3
+#define HIDRA_PAD_STATE_V2_VERSION 2
+#define HIDRA_VERSION_INFO_VERSION 1
+#define HIDRA_KEYBOARD_STATE_VERSION 1
+#define HIDRA_MOUSE_STATE_VERSION 1
+#define HIDRA_IDENTITY_VERSION 1
+
+// CTL_CODE layout: (DeviceType<<16) | (Access<<14) | (Function<<2) | Method
+#define HIDRA_DEVICE_TYPE 0x0022
+#define HIDRA_IOCTL_BASE 0x800
+#define IOCTL_HIDRA_CREATE 0x0022A000
+#define IOCTL_HIDRA_UPDATE 0x0022A004
+#define IOCTL_HIDRA_DESTROY 0x0022A008
+#define IOCTL_HIDRA_GET_VERSION 0x0022200C
+#define IOCTL_HIDRA_UPDATE_KEYBOARD 0x0022A010
+#define IOCTL_HIDRA_UPDATE_MOUSE 0x0022A014
+
+typedef enum _HIDRA_DEVICE_KIND
+{
+    HIDRA_KIND_X360 = 0x0366,
+    HIDRA_KIND_DS4 = 0x05C4,
+    HIDRA_KIND_DS5 = 0x0CE6,
+    HIDRA_KIND_XBOX_ONE = 0x0B13,
+    HIDRA_KIND_SWITCH_PRO = 0x2009,
+    HIDRA_KIND_GENERIC = 0x4844,
+    HIDRA_KIND_WHEEL = 0x5748,
+    HIDRA_KIND_DS5_EDGE = 0x0DF2,
+    HIDRA_KIND_XBOX_ELITE = 0x0B00,
+    HIDRA_KIND_KEYBOARD = 0x4B42,
+    HIDRA_KIND_MOUSE = 0x4D53,
+} HIDRA_DEVICE_KIND;
+
+typedef enum _HIDRA_TRIGGER_RANGE
+{
+    HIDRA_TRIGGER_BITS_8 = 0x0008,
+    HIDRA_TRIGGER_BITS_10 = 0x000A,
+    HIDRA_TRIGGER_BITS_12 = 0x000C,
+    HIDRA_TRIGGER_BITS_16 = 0x0010,
+} HIDRA_TRIGGER_RANGE;
+
+typedef enum _HIDRA_STICK_RANGE
+{
+    HIDRA_STICK_FULL = 0x0000,
+    HIDRA_STICK_HALF = 0x0001,
+} HIDRA_STICK_RANGE;
+
+typedef enum _HIDRA_TRANSPORT
+{
+    HIDRA_TRANSPORT_USB = 0x0000,
+    HIDRA_TRANSPORT_BLUETOOTH = 0x0001,
+} HIDRA_TRANSPORT;
+
+typedef enum _HIDRA_XUSB_SUBTYPE
+{
+    HIDRA_XUSB_SUBTYPE_GAMEPAD = 0x0001,
+    HIDRA_XUSB_SUBTYPE_ARCADE_STICK = 0x0003,
+    HIDRA_XUSB_SUBTYPE_DANCE_PAD = 0x0005,
+    HIDRA_XUSB_SUBTYPE_GUITAR = 0x0006,
+    HIDRA_XUSB_SUBTYPE_DRUM_KIT = 0x0008,
+} HIDRA_XUSB_SUBTYPE;
+
+typedef enum _HIDRA_CHARGE_STATE
+{
+    HIDRA_CHARGE_DISCHARGING = 0x0000,
+    HIDRA_CHARGE_CHARGING = 0x0001,
+    HIDRA_CHARGE_FULL = 0x0002,
+} HIDRA_CHARGE_STATE;
+
+#define HIDRA_KIND_BIT_X360 0x00000001
+#define HIDRA_KIND_BIT_DS4 0x00000002
+#define HIDRA_KIND_BIT_DS5 0x00000004
+#define HIDRA_KIND_BIT_XBOX_ONE 0x00000008
+#define HIDRA_KIND_BIT_SWITCH_PRO 0x00000010
+#define HIDRA_KIND_BIT_GENERIC 0x00000020
+#define HIDRA_KIND_BIT_WHEEL 0x00000040
+#define HIDRA_KIND_BIT_DS5_EDGE 0x00000080
+#define HIDRA_KIND_BIT_XBOX_ELITE 0x00000100
+#define HIDRA_KIND_BIT_KEYBOARD 0x00000200
+#define HIDRA_KIND_BIT_MOUSE 0x00000400
+
+#define HIDRA_FEATURE_RUMBLE 0x00000001
+#define HIDRA_FEATURE_TOUCH 0x00000002
+#define HIDRA_FEATURE_GYRO 0x00000004
+#define HIDRA_FEATURE_LED 0x00000008
+#define HIDRA_FEATURE_NKRO 0x00000010
+
+#define HIDRA_KIND_FEATURES_X360 0x00000009
+#define HIDRA_KIND_FEATURES_DS4 0x0000000F
+#define HIDRA_KIND_FEATURES_DS5 0x0000000F
+#define HIDRA_KIND_FEATURES_XBOX_ONE 0x00000001
+#define HIDRA_KIND_FEATURES_SWITCH_PRO 0x0000000D
+#define HIDRA_KIND_FEATURES_GENERIC 0x00000000
+#define HIDRA_KIND_FEATURES_WHEEL 0x00000000
+#define HIDRA_KIND_FEATURES_DS5_EDGE 0x0000000F
+#define HIDRA_KIND_FEATURES_XBOX_ELITE 0x00000001
+#define HIDRA_KIND_FEATURES_KEYBOARD 0x00000018
+#define HIDRA_KIND_FEATURES_MOUSE 0x00000000
+
+#define HIDRA_BUTTON_DPAD_UP 0x00000001
+#define HIDRA_BUTTON_DPAD_DOWN 0x00000002
+#define HIDRA_BUTTON_DPAD_LEFT 0x00000004
+#define HIDRA_BUTTON_DPAD_RIGHT 0x00000008
+#define HIDRA_BUTTON_START 0x00000010
+#define HIDRA_BUTTON_BACK 0x00000020
+#define HIDRA_BUTTON_LEFT_THUMB 0x00000040
+#define HIDRA_BUTTON_RIGHT_THUMB 0x00000080
+#define HIDRA_BUTTON_LEFT_SHOULDER 0x00000100
+#define HIDRA_BUTTON_RIGHT_SHOULDER 0x00000200
+#define HIDRA_BUTTON_GUIDE 0x00000400
+#define HIDRA_BUTTON_A 0x00001000
+#define HIDRA_BUTTON_B 0x00002000
+#define HIDRA_BUTTON_X 0x00004000
+#define HIDRA_BUTTON_Y 0x00008000
+#define HIDRA_BUTTON_TOUCHPAD 0x00010000
+#define HIDRA_BUTTON_MUTE 0x00020000
+#define HIDRA_BUTTON_PADDLE1 0x00040000
+#define HIDRA_BUTTON_PADDLE2 0x00080000
+#define HIDRA_BUTTON_PADDLE3 0x00100000
+#define HIDRA_BUTTON_PADDLE4 0x00200000
+#define HIDRA_BUTTON_SHARE 0x00400000
+#define HIDRA_BUTTON_FN_LEFT 0x00800000
+#define HIDRA_BUTTON_FN_RIGHT 0x01000000
+#define HIDRA_BUTTON_DPAD 0x0000000F
+
+#define HIDRA_MODIFIER_LEFT_CTRL 0x00000001
+#define HIDRA_MODIFIER_LEFT_SHIFT 0x00000002
+#define HIDRA_MODIFIER_LEFT_ALT 0x00000004
+#define HIDRA_MODIFIER_LEFT_GUI 0x00000008
+#define HIDRA_MODIFIER_RIGHT_CTRL 0x00000010
+#define HIDRA_MODIFIER_RIGHT_SHIFT 0x00000020
+#define HIDRA_MODIFIER_RIGHT_ALT 0x00000040
+#define HIDRA_MODIFIER_RIGHT_GUI 0x00000080
+
+#define HIDRA_KEYBOARD_LED_NUM_LOCK 0x00000001
+#define HIDRA_KEYBOARD_LED_CAPS_LOCK 0x00000002
+#define HIDRA_KEYBOARD_LED_SCROLL_LOCK 0x00000004
+#define HIDRA_KEYBOARD_LED_COMPOSE 0x00000008
+#define HIDRA_KEYBOARD_LED_KANA 0x00000010
+
+#define HIDRA_MOUSE_BUTTON_LEFT 0x00000001
+#define HIDRA_MOUSE_BUTTON_RIGHT 0x00000002
+#define HIDRA_MOUSE_BUTTON_MIDDLE 0x00000004
+#define HIDRA_MOUSE_BUTTON_BACK 0x00000008
+#define HIDRA_MOUSE_BUTTON_FORWARD 0x00000010
+
+#define HIDRA_GENERIC_MAX_AXES 6
+#define HIDRA_GENERIC_MIN_AXIS_BITS 8
+#define HIDRA_GENERIC_MAX_AXIS_BITS 16
+#define HIDRA_GENERIC_MAX_HATS 1
+#define HIDRA_GENERIC_MAX_BUTTONS 24
+#define HIDRA_GENERIC_MAX_BUTTONS_WITH_HAT 18
+
+typedef struct _HIDRA_STATE_HEADER
+{
+    UINT16 Size;
+    UINT16 Version;
+} HIDRA_STATE_HEADER, *PHIDRA_STATE_HEADER;
+C_ASSERT(sizeof(HIDRA_STATE_HEADER) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_STATE_HEADER, Size) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_STATE_HEADER, Size) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_STATE_HEADER, Version) == 2);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_STATE_HEADER, Version) == 2);
+
+typedef struct _HIDRA_TOUCH_POINT
+{
+    UINT8 Down;
+    UINT8 Id;
+    UINT16 X;
+    UINT16 Y;
+} HIDRA_TOUCH_POINT, *PHIDRA_TOUCH_POINT;
+C_ASSERT(sizeof(HIDRA_TOUCH_POINT) == 6);
+C_ASSERT(FIELD_OFFSET(HIDRA_TOUCH_POINT, Down) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_TOUCH_POINT, Down) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_TOUCH_POINT, Id) == 1);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_TOUCH_POINT, Id) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_TOUCH_POINT, X) == 2);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_TOUCH_POINT, X) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_TOUCH_POINT, Y) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_TOUCH_POINT, Y) == 2);
+
+typedef struct _HIDRA_MOTION
+{
+    SHORT Gyro[3];
+    SHORT Accel[3];
+} HIDRA_MOTION, *PHIDRA_MOTION;
+C_ASSERT(sizeof(HIDRA_MOTION) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOTION, Gyro) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOTION, Gyro) == 6);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOTION, Accel) == 6);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOTION, Accel) == 6);
+
+typedef struct _HIDRA_BATTERY
+{
+    UINT8 Level;
+    UINT8 Charge;
+} HIDRA_BATTERY, *PHIDRA_BATTERY;
+C_ASSERT(sizeof(HIDRA_BATTERY) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_BATTERY, Level) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_BATTERY, Level) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_BATTERY, Charge) == 1);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_BATTERY, Charge) == 1);
+
+typedef struct _HIDRA_PAD_STATE
+{
+    HIDRA_STATE_HEADER Header;
+    UINT32 Buttons;
+    SHORT Lx;
+    SHORT Ly;
+    SHORT Rx;
+    SHORT Ry;
+    UINT16 Lt;
+    UINT16 Rt;
+    HIDRA_TOUCH_POINT Touch[2];
+    HIDRA_MOTION Motion;
+    HIDRA_BATTERY Battery;
+    UINT8 Reserved[2];
+} HIDRA_PAD_STATE, *PHIDRA_PAD_STATE;
+C_ASSERT(sizeof(HIDRA_PAD_STATE) == 48);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Buttons) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Buttons) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Lx) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Lx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Ly) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Ly) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Rx) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Rx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Ry) == 14);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Ry) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Lt) == 16);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Lt) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Rt) == 18);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Rt) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Touch) == 20);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Touch) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Motion) == 32);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Motion) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Battery) == 44);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Battery) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE, Reserved) == 46);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE, Reserved) == 2);
+
+typedef struct _HIDRA_PAD_STATE_V2
+{
+    HIDRA_STATE_HEADER Header;
+    UINT16 Buttons;
+    UINT16 ExtButtons;
+    SHORT Lx;
+    SHORT Ly;
+    SHORT Rx;
+    SHORT Ry;
+    UINT16 Lt;
+    UINT16 Rt;
+    HIDRA_TOUCH_POINT Touch[2];
+    HIDRA_MOTION Motion;
+    HIDRA_BATTERY Battery;
+    UINT8 Reserved[2];
+} HIDRA_PAD_STATE_V2, *PHIDRA_PAD_STATE_V2;
+C_ASSERT(sizeof(HIDRA_PAD_STATE_V2) == 48);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Buttons) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Buttons) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, ExtButtons) == 6);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, ExtButtons) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Lx) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Lx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Ly) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Ly) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Rx) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Rx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Ry) == 14);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Ry) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Lt) == 16);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Lt) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Rt) == 18);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Rt) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Touch) == 20);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Touch) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Motion) == 32);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Motion) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Battery) == 44);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Battery) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V2, Reserved) == 46);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V2, Reserved) == 2);
+
+typedef struct _HIDRA_PAD_STATE_V1
+{
+    UINT16 Buttons;
+    SHORT Lx;
+    SHORT Ly;
+    SHORT Rx;
+    SHORT Ry;
+    UINT16 Lt;
+    UINT16 Rt;
+} HIDRA_PAD_STATE_V1, *PHIDRA_PAD_STATE_V1;
+C_ASSERT(sizeof(HIDRA_PAD_STATE_V1) == 14);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Buttons) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Buttons) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Lx) == 2);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Lx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Ly) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Ly) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Rx) == 6);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Rx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Ry) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Ry) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Lt) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Lt) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_PAD_STATE_V1, Rt) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_PAD_STATE_V1, Rt) == 2);
+
+typedef struct _HIDRA_INPUT_RANGES
+{
+    UINT8 TriggerBits;
+    UINT8 StickRange;
+} HIDRA_INPUT_RANGES, *PHIDRA_INPUT_RANGES;
+C_ASSERT(sizeof(HIDRA_INPUT_RANGES) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_INPUT_RANGES, TriggerBits) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_INPUT_RANGES, TriggerBits) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_INPUT_RANGES, StickRange) == 1);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_INPUT_RANGES, StickRange) == 1);
+
+typedef struct _HIDRA_VERSION_INFO
+{
+    HIDRA_STATE_HEADER Header;
+    UINT32 AbiVersion;
+    UINT32 Kinds;
+    UINT32 Features;
+} HIDRA_VERSION_INFO, *PHIDRA_VERSION_INFO;
+C_ASSERT(sizeof(HIDRA_VERSION_INFO) == 16);
+C_ASSERT(FIELD_OFFSET(HIDRA_VERSION_INFO, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_VERSION_INFO, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_VERSION_INFO, AbiVersion) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_VERSION_INFO, AbiVersion) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_VERSION_INFO, Kinds) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_VERSION_INFO, Kinds) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_VERSION_INFO, Features) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_VERSION_INFO, Features) == 4);
+
+typedef struct _HIDRA_CREATE_IN
+{
+    UINT16 Kind;
+    UINT8 Reserved0[2];
+    UINT32 Features;
+    HIDRA_INPUT_RANGES Ranges;
+    UINT8 Transport;
+    UINT8 Subtype;
+} HIDRA_CREATE_IN, *PHIDRA_CREATE_IN;
+C_ASSERT(sizeof(HIDRA_CREATE_IN) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Kind) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Kind) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Reserved0) == 2);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Reserved0) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Features) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Features) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Ranges) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Ranges) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Transport) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Transport) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_IN, Subtype) == 11);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_IN, Subtype) == 1);
+
+typedef struct _HIDRA_GENERIC_SPEC
+{
+    UINT16 VendorId;
+    UINT16 ProductId;
+    UINT8 Axes;
+    UINT8 AxisBits;
+    UINT8 Buttons;
+    UINT8 Hats;
+    WCHAR Product[32];
+} HIDRA_GENERIC_SPEC, *PHIDRA_GENERIC_SPEC;
+C_ASSERT(sizeof(HIDRA_GENERIC_SPEC) == 72);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, VendorId) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, VendorId) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, ProductId) == 2);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, ProductId) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, Axes) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, Axes) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, AxisBits) == 5);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, AxisBits) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, Buttons) == 6);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, Buttons) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, Hats) == 7);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, Hats) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_GENERIC_SPEC, Product) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_GENERIC_SPEC, Product) == 64);
+
+typedef struct _HIDRA_CREATE_GENERIC_IN
+{
+    HIDRA_CREATE_IN Create;
+    HIDRA_GENERIC_SPEC Spec;
+} HIDRA_CREATE_GENERIC_IN, *PHIDRA_CREATE_GENERIC_IN;
+C_ASSERT(sizeof(HIDRA_CREATE_GENERIC_IN) == 84);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_GENERIC_IN, Create) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_GENERIC_IN, Create) == 12);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_GENERIC_IN, Spec) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_GENERIC_IN, Spec) == 72);
+
+typedef struct _HIDRA_DEVICE_IDENTITY
+{
+    HIDRA_STATE_HEADER Header;
+    UINT16 VendorId;
+    UINT16 ProductId;
+    UINT16 Version;
+    UINT8 Mac[6];
+    WCHAR Manufacturer[32];
+    WCHAR Product[32];
+    WCHAR Serial[32];
+} HIDRA_DEVICE_IDENTITY, *PHIDRA_DEVICE_IDENTITY;
+C_ASSERT(sizeof(HIDRA_DEVICE_IDENTITY) == 208);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, VendorId) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, VendorId) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, ProductId) == 6);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, ProductId) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Version) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Version) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Mac) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Mac) == 6);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Manufacturer) == 16);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Manufacturer) == 64);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Product) == 80);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Product) == 64);
+C_ASSERT(FIELD_OFFSET(HIDRA_DEVICE_IDENTITY, Serial) == 144);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DEVICE_IDENTITY, Serial) == 64);
+
+typedef struct _HIDRA_CREATE_OUT
+{
+    ULONGLONG Handle;
+} HIDRA_CREATE_OUT, *PHIDRA_CREATE_OUT;
+C_ASSERT(sizeof(HIDRA_CREATE_OUT) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_CREATE_OUT, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_CREATE_OUT, Handle) == 8);
+
+typedef struct _HIDRA_UPDATE_IN
+{
+    ULONGLONG Handle;
+    HIDRA_PAD_STATE State;
+} HIDRA_UPDATE_IN, *PHIDRA_UPDATE_IN;
+C_ASSERT(sizeof(HIDRA_UPDATE_IN) == 56);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_IN, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_IN, Handle) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_IN, State) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_IN, State) == 48);
+
+typedef struct _HIDRA_UPDATE_IN_V1
+{
+    ULONGLONG Handle;
+    HIDRA_PAD_STATE_V1 State;
+    UINT8 Reserved[2];
+} HIDRA_UPDATE_IN_V1, *PHIDRA_UPDATE_IN_V1;
+C_ASSERT(sizeof(HIDRA_UPDATE_IN_V1) == 24);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_IN_V1, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_IN_V1, Handle) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_IN_V1, State) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_IN_V1, State) == 14);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_IN_V1, Reserved) == 22);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_IN_V1, Reserved) == 2);
+
+typedef struct _HIDRA_DESTROY_IN
+{
+    ULONGLONG Handle;
+} HIDRA_DESTROY_IN, *PHIDRA_DESTROY_IN;
+C_ASSERT(sizeof(HIDRA_DESTROY_IN) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_DESTROY_IN, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_DESTROY_IN, Handle) == 8);
+
+typedef struct _HIDRA_KEYBOARD_STATE
+{
+    HIDRA_STATE_HEADER Header;
+    UINT8 Modifiers;
+    UINT8 Reserved[3];
+    UINT8 Keys[32];
+} HIDRA_KEYBOARD_STATE, *PHIDRA_KEYBOARD_STATE;
+C_ASSERT(sizeof(HIDRA_KEYBOARD_STATE) == 40);
+C_ASSERT(FIELD_OFFSET(HIDRA_KEYBOARD_STATE, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_KEYBOARD_STATE, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_KEYBOARD_STATE, Modifiers) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_KEYBOARD_STATE, Modifiers) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_KEYBOARD_STATE, Reserved) == 5);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_KEYBOARD_STATE, Reserved) == 3);
+C_ASSERT(FIELD_OFFSET(HIDRA_KEYBOARD_STATE, Keys) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_KEYBOARD_STATE, Keys) == 32);
+
+typedef struct _HIDRA_MOUSE_STATE
+{
+    HIDRA_STATE_HEADER Header;
+    UINT8 Buttons;
+    UINT8 Reserved[3];
+    SHORT Dx;
+    SHORT Dy;
+    SHORT Wheel;
+    SHORT Pan;
+} HIDRA_MOUSE_STATE, *PHIDRA_MOUSE_STATE;
+C_ASSERT(sizeof(HIDRA_MOUSE_STATE) == 16);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Header) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Header) == 4);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Buttons) == 4);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Buttons) == 1);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Reserved) == 5);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Reserved) == 3);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Dx) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Dx) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Dy) == 10);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Dy) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Wheel) == 12);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Wheel) == 2);
+C_ASSERT(FIELD_OFFSET(HIDRA_MOUSE_STATE, Pan) == 14);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_MOUSE_STATE, Pan) == 2);
+
+typedef struct _HIDRA_UPDATE_KEYBOARD_IN
+{
+    ULONGLONG Handle;
+    HIDRA_KEYBOARD_STATE State;
+} HIDRA_UPDATE_KEYBOARD_IN, *PHIDRA_UPDATE_KEYBOARD_IN;
+C_ASSERT(sizeof(HIDRA_UPDATE_KEYBOARD_IN) == 48);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_KEYBOARD_IN, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_KEYBOARD_IN, Handle) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_KEYBOARD_IN, State) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_KEYBOARD_IN, State) == 40);
+
+typedef struct _HIDRA_UPDATE_MOUSE_IN
+{
+    ULONGLONG Handle;
+    HIDRA_MOUSE_STATE State;
+} HIDRA_UPDATE_MOUSE_IN, *PHIDRA_UPDATE_MOUSE_IN;
+C_ASSERT(sizeof(HIDRA_UPDATE_MOUSE_IN) == 24);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_MOUSE_IN, Handle) == 0);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_MOUSE_IN, Handle) == 8);
+C_ASSERT(FIELD_OFFSET(HIDRA_UPDATE_MOUSE_IN, State) == 8);
+C_ASSERT(RTL_FIELD_SIZE(HIDRA_UPDATE_MOUSE_IN, State) == 16);
+
+DEFINE_GUID(GUID_DEVINTERFACE_HIDRA,
+    0x468ADE0B, 0x0432, 0x4680, 0xB5, 0x7B, 0x18, 0x50, 0xB3, 0x3C, 0x7D, 0x9B);
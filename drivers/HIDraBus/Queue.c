@@ -1,5 +1,62 @@
 #include "HIDraBus.h"
 
+static BOOLEAN GenericSpecIsValid(_In_ const HIDRA_GENERIC_SPEC* Spec)
+{
+    if (Spec->Axes > HIDRA_GENERIC_MAX_AXES || Spec->AxisBits < HIDRA_GENERIC_MIN_AXIS_BITS ||
+        Spec->AxisBits > HIDRA_GENERIC_MAX_AXIS_BITS || Spec->Hats > HIDRA_GENERIC_MAX_HATS)
+        return FALSE;
+    if (Spec->Buttons > (Spec->Hats ? HIDRA_GENERIC_MAX_BUTTONS_WITH_HAT : HIDRA_GENERIC_MAX_BUTTONS))
+        return FALSE;
+    // an empty input report can't be described
+    return (ULONG)Spec->Axes * Spec->AxisBits + Spec->Hats * 4u + Spec->Buttons > 0;
+}
+
+static BOOLEAN XusbSubtypeIsValid(_In_ UINT8 Subtype)
+{
+    switch (Subtype)
+    {
+    case 0: // clients predating subtypes
+    case HIDRA_XUSB_SUBTYPE_GAMEPAD:
+    case HIDRA_XUSB_SUBTYPE_ARCADE_STICK:
+    case HIDRA_XUSB_SUBTYPE_DANCE_PAD:
+    case HIDRA_XUSB_SUBTYPE_GUITAR:
+    case HIDRA_XUSB_SUBTYPE_DRUM_KIT:
+        return TRUE;
+    default:
+        return FALSE;
+    }
+}
+
+static BOOLEAN IdentityStringIsValid(_In_reads_(Len) const WCHAR* Str, _In_ size_t Len)
+{
+    for (size_t i = 0; i < Len; i++)
+        if (Str[i] == 0)
+            return TRUE;
+    return FALSE;
+}
+
+// The identity trailing the create input, if any. Clients predating identities
+// send none and the driver picks one.
+static NTSTATUS ValidateIdentity(_In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen, _In_ size_t Offset)
+{
+    if (InLen <= Offset)
+        return STATUS_SUCCESS;
+
+    PHIDRA_DEVICE_IDENTITY id = (PHIDRA_DEVICE_IDENTITY)((PUCHAR)InBuf + Offset);
+    if (InLen - Offset < sizeof(HIDRA_DEVICE_IDENTITY))
+        return STATUS_BUFFER_TOO_SMALL;
+    if (id->Header.Version < HIDRA_IDENTITY_VERSION || id->Header.Size < sizeof(HIDRA_DEVICE_IDENTITY) ||
+        id->Header.Size > InLen - Offset)
+        return STATUS_INVALID_PARAMETER;
+    if (!IdentityStringIsValid(id->Manufacturer, ARRAYSIZE(id->Manufacturer)) ||
+        !IdentityStringIsValid(id->Product, ARRAYSIZE(id->Product)) ||
+        !IdentityStringIsValid(id->Serial, ARRAYSIZE(id->Serial)))
+        return STATUS_INVALID_PARAMETER;
+
+    // TODO: report VendorId/ProductId/Version and the strings through VHF, Mac through the pairing feature report
+    return STATUS_SUCCESS;
+}
+
 static NTSTATUS HandleCreate(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen, _Out_writes_bytes_(OutLen) PVOID OutBuf, _In_ size_t OutLen)
 {
     if (InLen < sizeof(HIDRA_CREATE_IN) || OutLen < sizeof(HIDRA_CREATE_OUT))
@@ -11,7 +68,62 @@ static NTSTATUS HandleCreate(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PV
     PHIDRA_CREATE_IN cin = (PHIDRA_CREATE_IN)InBuf;
     PHIDRA_CREATE_OUT cout = (PHIDRA_CREATE_OUT)OutBuf;
 
-    UNREFERENCED_PARAMETER(cin); // kind/features available here for future VHF init
+    ULONG supported;
+    switch (cin->Kind)
+    {
+    case HIDRA_KIND_X360:
+        if (!XusbSubtypeIsValid(cin->Subtype))
+            return STATUS_INVALID_PARAMETER;
+        supported = HIDRA_KIND_FEATURES_X360;
+        break;
+    case HIDRA_KIND_DS4:
+        supported = HIDRA_KIND_FEATURES_DS4;
+        break;
+    case HIDRA_KIND_DS5:
+        supported = HIDRA_KIND_FEATURES_DS5;
+        break;
+    case HIDRA_KIND_DS5_EDGE:
+        supported = HIDRA_KIND_FEATURES_DS5_EDGE;
+        break;
+    case HIDRA_KIND_XBOX_ONE:
+        supported = HIDRA_KIND_FEATURES_XBOX_ONE;
+        break;
+    case HIDRA_KIND_XBOX_ELITE:
+        supported = HIDRA_KIND_FEATURES_XBOX_ELITE;
+        break;
+    case HIDRA_KIND_SWITCH_PRO:
+        supported = HIDRA_KIND_FEATURES_SWITCH_PRO;
+        break;
+    case HIDRA_KIND_WHEEL:
+        // the host allocates effects through the PID Create New Effect / Block Load
+        // feature reports, which nothing here answers yet
+        return STATUS_NOT_SUPPORTED;
+    case HIDRA_KIND_KEYBOARD:
+        supported = HIDRA_KIND_FEATURES_KEYBOARD;
+        break;
+    case HIDRA_KIND_MOUSE:
+        supported = HIDRA_KIND_FEATURES_MOUSE;
+        break;
+    case HIDRA_KIND_GENERIC:
+        // the layout follows the common header; see HIDRA_CREATE_GENERIC_IN
+        if (InLen < sizeof(HIDRA_CREATE_GENERIC_IN))
+            return STATUS_BUFFER_TOO_SMALL;
+        if (!GenericSpecIsValid(&((PHIDRA_CREATE_GENERIC_IN)InBuf)->Spec))
+            return STATUS_INVALID_PARAMETER;
+        supported = HIDRA_KIND_FEATURES_GENERIC;
+        break;
+    default:
+        return STATUS_INVALID_PARAMETER;
+    }
+    if (cin->Features & ~supported)
+        return STATUS_NOT_SUPPORTED;
+    if (cin->Kind != HIDRA_KIND_X360 && cin->Subtype > HIDRA_XUSB_SUBTYPE_GAMEPAD)
+        return STATUS_NOT_SUPPORTED;
+
+    NTSTATUS status = ValidateIdentity(
+        InBuf, InLen, cin->Kind == HIDRA_KIND_GENERIC ? sizeof(HIDRA_CREATE_GENERIC_IN) : sizeof(HIDRA_CREATE_IN));
+    if (!NT_SUCCESS(status))
+        return status;
 
     ULONGLONG handle = ctx->NextHandle++;
     cout->Handle = handle;
@@ -22,11 +134,40 @@ static NTSTATUS HandleCreate(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PV
 
 static NTSTATUS HandleUpdate(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen)
 {
+    if (InLen == sizeof(HIDRA_UPDATE_IN_V1))
+    {
+        // v1 clients: no header, XInput-level state only
+        PHIDRA_UPDATE_IN_V1 uin1 = (PHIDRA_UPDATE_IN_V1)InBuf;
+
+        // TODO: look up instance by uin1->Handle and submit to VHF (VhfWriteReportData)
+        UNREFERENCED_PARAMETER(uin1);
+
+        WdfRequestSetInformation(Request, 0);
+        return STATUS_SUCCESS;
+    }
+
     if (InLen < sizeof(HIDRA_UPDATE_IN))
         return STATUS_BUFFER_TOO_SMALL;
 
     PHIDRA_UPDATE_IN uin = (PHIDRA_UPDATE_IN)InBuf;
 
+    // Newer clients may append fields; anything past what we know is ignored.
+    if (uin->State.Header.Version < HIDRA_PAD_STATE_V2_VERSION || uin->State.Header.Size < sizeof(HIDRA_PAD_STATE) ||
+        uin->State.Header.Size > InLen - FIELD_OFFSET(HIDRA_UPDATE_IN, State))
+        return STATUS_INVALID_PARAMETER;
+
+    if (uin->State.Header.Version == HIDRA_PAD_STATE_V2_VERSION)
+    {
+        // v2 clients: same size, XUSB Buttons plus ExtButtons (touchpad, mute)
+        PHIDRA_PAD_STATE_V2 state2 = (PHIDRA_PAD_STATE_V2)&uin->State;
+
+        // TODO: widen state2 buttons to the v3 bits, look up instance by uin->Handle and submit to VHF
+        UNREFERENCED_PARAMETER(state2);
+
+        WdfRequestSetInformation(Request, 0);
+        return STATUS_SUCCESS;
+    }
+
     // TODO: look up instance by uin->Handle and submit to VHF (VhfWriteReportData)
     UNREFERENCED_PARAMETER(uin);
 
@@ -34,6 +175,43 @@ static NTSTATUS HandleUpdate(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PV
     return STATUS_SUCCESS;
 }
 
+static NTSTATUS HandleUpdateKeyboard(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen)
+{
+    if (InLen < sizeof(HIDRA_UPDATE_KEYBOARD_IN))
+        return STATUS_BUFFER_TOO_SMALL;
+
+    PHIDRA_UPDATE_KEYBOARD_IN kin = (PHIDRA_UPDATE_KEYBOARD_IN)InBuf;
+
+    if (kin->State.Header.Version < HIDRA_KEYBOARD_STATE_VERSION || kin->State.Header.Size < sizeof(HIDRA_KEYBOARD_STATE) ||
+        kin->State.Header.Size > InLen - FIELD_OFFSET(HIDRA_UPDATE_KEYBOARD_IN, State))
+        return STATUS_INVALID_PARAMETER;
+
+    // TODO: look up the keyboard by kin->Handle, pack the boot or NKRO report and submit to VHF
+    UNREFERENCED_PARAMETER(kin);
+
+    WdfRequestSetInformation(Request, 0);
+    return STATUS_SUCCESS;
+}
+
+static NTSTATUS HandleUpdateMouse(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen)
+{
+    if (InLen < sizeof(HIDRA_UPDATE_MOUSE_IN))
+        return STATUS_BUFFER_TOO_SMALL;
+
+    PHIDRA_UPDATE_MOUSE_IN min = (PHIDRA_UPDATE_MOUSE_IN)InBuf;
+
+    if (min->State.Header.Version < HIDRA_MOUSE_STATE_VERSION || min->State.Header.Size < sizeof(HIDRA_MOUSE_STATE) ||
+        min->State.Header.Size > InLen - FIELD_OFFSET(HIDRA_UPDATE_MOUSE_IN, State))
+        return STATUS_INVALID_PARAMETER;
+
+    // TODO: look up the mouse by min->Handle and submit reports of at most +-127 per axis
+    // until the motion is used up
+    UNREFERENCED_PARAMETER(min);
+
+    WdfRequestSetInformation(Request, 0);
+    return STATUS_SUCCESS;
+}
+
 static NTSTATUS HandleDestroy(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) PVOID InBuf, _In_ size_t InLen)
 {
     if (InLen < sizeof(HIDRA_DESTROY_IN))
@@ -47,6 +225,25 @@ static NTSTATUS HandleDestroy(_In_ WDFREQUEST Request, _In_reads_bytes_(InLen) P
     return STATUS_SUCCESS;
 }
 
+static NTSTATUS HandleGetVersion(_In_ WDFREQUEST Request, _Out_writes_bytes_(OutLen) PVOID OutBuf, _In_ size_t OutLen)
+{
+    if (OutLen < sizeof(HIDRA_VERSION_INFO))
+        return STATUS_BUFFER_TOO_SMALL;
+
+    PHIDRA_VERSION_INFO info = (PHIDRA_VERSION_INFO)OutBuf;
+    RtlZeroMemory(info, sizeof(*info));
+    info->Header.Size = sizeof(HIDRA_VERSION_INFO);
+    info->Header.Version = HIDRA_VERSION_INFO_VERSION;
+    info->AbiVersion = HIDRA_FFI_ABI_VERSION;
+    info->Kinds = HIDRA_KIND_BIT_X360 | HIDRA_KIND_BIT_DS4 | HIDRA_KIND_BIT_DS5 | HIDRA_KIND_BIT_XBOX_ONE |
+                  HIDRA_KIND_BIT_SWITCH_PRO | HIDRA_KIND_BIT_GENERIC |
+                  HIDRA_KIND_BIT_DS5_EDGE | HIDRA_KIND_BIT_XBOX_ELITE | HIDRA_KIND_BIT_KEYBOARD | HIDRA_KIND_BIT_MOUSE;
+    info->Features = HIDRA_FEATURE_RUMBLE | HIDRA_FEATURE_TOUCH | HIDRA_FEATURE_GYRO | HIDRA_FEATURE_LED | HIDRA_FEATURE_NKRO;
+
+    WdfRequestSetInformation(Request, sizeof(HIDRA_VERSION_INFO));
+    return STATUS_SUCCESS;
+}
+
 VOID EvtIoDeviceControl(
     _In_ WDFQUEUE Queue,
     _In_ WDFREQUEST Request,
@@ -64,7 +261,8 @@ VOID EvtIoDeviceControl(
     size_t inLen = 0, outLen = 0;
 
     // METHOD_BUFFERED  both via system buffer
-    if (IoControlCode == IOCTL_HIDRA_CREATE || IoControlCode == IOCTL_HIDRA_UPDATE || IoControlCode == IOCTL_HIDRA_DESTROY)
+    if (IoControlCode == IOCTL_HIDRA_CREATE || IoControlCode == IOCTL_HIDRA_UPDATE || IoControlCode == IOCTL_HIDRA_DESTROY ||
+        IoControlCode == IOCTL_HIDRA_UPDATE_KEYBOARD || IoControlCode == IOCTL_HIDRA_UPDATE_MOUSE)
     {
         status = WdfRequestRetrieveInputBuffer(Request, 0, &inBuf, &inLen);
         if (!NT_SUCCESS(status))
@@ -76,6 +274,12 @@ VOID EvtIoDeviceControl(
         if (!NT_SUCCESS(status))
             goto done;
     }
+    if (IoControlCode == IOCTL_HIDRA_GET_VERSION)
+    {
+        status = WdfRequestRetrieveOutputBuffer(Request, sizeof(HIDRA_VERSION_INFO), &outBuf, &outLen);
+        if (!NT_SUCCESS(status))
+            goto done;
+    }
 
     switch (IoControlCode)
     {
@@ -85,9 +289,18 @@ VOID EvtIoDeviceControl(
     case IOCTL_HIDRA_UPDATE:
         status = HandleUpdate(Request, inBuf, inLen);
         break;
+    case IOCTL_HIDRA_UPDATE_KEYBOARD:
+        status = HandleUpdateKeyboard(Request, inBuf, inLen);
+        break;
+    case IOCTL_HIDRA_UPDATE_MOUSE:
+        status = HandleUpdateMouse(Request, inBuf, inLen);
+        break;
     case IOCTL_HIDRA_DESTROY:
         status = HandleDestroy(Request, inBuf, inLen);
         break;
+    case IOCTL_HIDRA_GET_VERSION:
+        status = HandleGetVersion(Request, outBuf, outLen);
+        break;
     default:
         status = STATUS_INVALID_DEVICE_REQUEST;
         break;